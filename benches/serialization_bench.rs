@@ -5,12 +5,11 @@ extern crate criterion;
 
 use criterion::{BenchmarkId, Criterion, Throughput, black_box};
 use rayon::prelude::*;
-use blake3;
 #[cfg(feature = "cpu_affinity")]
 use core_affinity;
 
 use reina::utils::serialization::{
-    Transaction, Serializer, Endianness, fixed_encoding, Encode,
+    Block, Transaction, Serializer, Endianness, fixed_encoding, Encode, Decode, SignatureBytes,
 };
 
 /// Optionally pin CPU affinity and initialize Rayon’s global thread pool only once.
@@ -52,11 +51,12 @@ fn serialize_fixed(
 fn bench_single_transaction(c: &mut Criterion) {
     let tx = Transaction {
         version: 1,
+        nonce: 0,
         id: 42,
         sender: "Alice".to_string(),
         recipient: "Bob".to_string(),
         amount: 1000,
-        signature: vec![1, 2, 3, 4],
+        signature: vec![1, 2, 3, 4].into(),
         fee: 0.01,
     };
 
@@ -87,15 +87,16 @@ fn bench_serialize_batch(c: &mut Criterion) {
     for &batch_size in &[1_000usize, 10_000, 100_000, 1_000_000] {
         let tx = Transaction {
             version: 1,
+            nonce: 0,
             id: 42,
             sender: "Alice".to_string(),
             recipient: "Bob".to_string(),
             amount: 1000,
-            signature: vec![1, 2, 3, 4],
+            signature: vec![1, 2, 3, 4].into(),
             fee: 0.01,
         };
         // Preallocate a vector of transactions by repeating a cloned tx.
-        let txs: Vec<Transaction> = std::iter::repeat(tx).take(batch_size).collect();
+        let txs: Vec<Transaction> = std::iter::repeat_n(tx, batch_size).collect();
         // Preallocate a buffer with an estimated size (128 bytes per transaction).
         let mut ser_buffer = Vec::with_capacity(batch_size * 128);
 
@@ -115,6 +116,78 @@ fn bench_serialize_batch(c: &mut Criterion) {
     group.finish();
 }
 
+/// --- Benchmark: Ultra-Fixed Batch vs. Varint Batch ---
+/// Compares the fixed 129-byte record layout (one checksum per batch) against
+/// the varint `serialize_batch` path for the same transactions.
+fn bench_ultra_batch_vs_varint(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ultra_batch_vs_varint");
+    for &batch_size in &[1_000usize, 10_000] {
+        let tx = Transaction {
+            version: 1,
+            nonce: 0,
+            id: 42,
+            sender: "Alice".to_string(),
+            recipient: "Bob".to_string(),
+            amount: 1000,
+            signature: vec![1; 64].into(),
+            fee: 0.01,
+        };
+        let txs: Vec<Transaction> = std::iter::repeat_n(tx, batch_size).collect();
+        group.throughput(Throughput::Elements(batch_size as u64));
+        group.bench_with_input(BenchmarkId::new("ultra", batch_size), &txs, |b, txs| {
+            b.iter(|| {
+                let ser = Serializer::serialize_ultra_batch(black_box(txs), Endianness::Little)
+                    .expect("Ultra batch serialization failed");
+                black_box(ser);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("varint", batch_size), &txs, |b, txs| {
+            b.iter(|| {
+                let ser = Serializer::serialize_batch(black_box(txs), Endianness::Little)
+                    .expect("Batch serialization failed");
+                black_box(ser);
+            })
+        });
+    }
+    group.finish();
+}
+
+/// --- Benchmark: Parallel vs Sequential Block Serialization ---
+/// Serializes a 50k-transaction block with the sequential `Block::encode_to`
+/// path and with `serialize_block_parallel`.
+fn bench_block_serialization_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("block_serialization_50k");
+    let transactions: Vec<Transaction> = (0..50_000u64)
+        .map(|i| Transaction {
+            version: 1,
+            nonce: 0,
+            id: i,
+            sender: "Alice".to_string(),
+            recipient: "Bob".to_string(),
+            amount: 1000 + i,
+            signature: vec![1; 64].into(),
+            fee: 0.01,
+        })
+        .collect();
+    let block = Block { version: 1, block_number: 1, previous_hash: vec![0; 32], transactions };
+    group.throughput(Throughput::Elements(block.transactions.len() as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let ser = Serializer::serialize(black_box(&block), Endianness::Little)
+                .expect("Block serialization failed");
+            black_box(ser);
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            let ser = Serializer::serialize_block_parallel(black_box(&block), Endianness::Little)
+                .expect("Parallel block serialization failed");
+            black_box(ser);
+        })
+    });
+    group.finish();
+}
+
 /// --- Benchmark: Batch Deserialization (Sequential) ---
 /// Pre-generated serialized data is deserialized in a tight loop to measure sequential performance.
 fn bench_batch_deserialization_seq(c: &mut Criterion) {
@@ -123,11 +196,12 @@ fn bench_batch_deserialization_seq(c: &mut Criterion) {
         let txs: Vec<Transaction> = (0..batch_size)
             .map(|_| Transaction {
                 version: 1,
+                nonce: 0,
                 id: 42,
                 sender: "Alice".to_string(),
                 recipient: "Bob".to_string(),
                 amount: 1000,
-                signature: vec![1, 2, 3, 4],
+                signature: vec![1, 2, 3, 4].into(),
                 fee: 0.01,
             })
             .collect();
@@ -151,6 +225,58 @@ fn bench_batch_deserialization_seq(c: &mut Criterion) {
     group.finish();
 }
 
+/// Storage behind `SignatureBytes` in this build.
+#[cfg(feature = "small_signature")]
+const SIGNATURE_STORAGE: &str = "smallvec";
+#[cfg(not(feature = "small_signature"))]
+const SIGNATURE_STORAGE: &str = "vec";
+
+/// --- Benchmark: Signature Storage Decode Throughput ---
+/// Decodes 64-byte signatures into a plain `Vec<u8>` and into `SignatureBytes`,
+/// and whole transactions carrying them. Run once with and once without
+/// `--features small_signature` to compare heap and inline storage.
+fn bench_signature_storage_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("signature_storage_decode");
+    let batch_size = 10_000usize;
+    let tx = Transaction {
+        version: 1,
+        nonce: 0,
+        id: 42,
+        sender: "Alice".to_string(),
+        recipient: "Bob".to_string(),
+        amount: 1000,
+        signature: vec![1; 64].into(),
+        fee: 0.01,
+    };
+    let mut signature = vec![0u8; tx.signature.encoded_size()];
+    tx.signature.encode_to(&mut signature, Endianness::Little).expect("Signature encoding failed");
+    let mut encoded_tx = vec![0u8; tx.encoded_size()];
+    tx.encode_to(&mut encoded_tx, Endianness::Little).expect("Transaction encoding failed");
+    group.throughput(Throughput::Elements(batch_size as u64));
+    group.bench_function("Vec<u8>", |b| {
+        b.iter(|| {
+            for _ in 0..batch_size {
+                black_box(Vec::<u8>::decode_from(black_box(&signature), Endianness::Little).unwrap());
+            }
+        })
+    });
+    group.bench_function(format!("SignatureBytes/{}", SIGNATURE_STORAGE), |b| {
+        b.iter(|| {
+            for _ in 0..batch_size {
+                black_box(SignatureBytes::decode_from(black_box(&signature), Endianness::Little).unwrap());
+            }
+        })
+    });
+    group.bench_function(format!("Transaction/{}", SIGNATURE_STORAGE), |b| {
+        b.iter(|| {
+            for _ in 0..batch_size {
+                black_box(Transaction::decode_from(black_box(&encoded_tx), Endianness::Little).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
 /// --- Benchmark: Parallel Deserialization with Adaptive Chunking ---
 /// Uses Rayon’s par_chunks_exact with a dynamic chunk size (here fixed at 512 for simplicity)
 /// to ensure even workload distribution while reducing thread contention.
@@ -160,11 +286,12 @@ fn bench_parallel_deserialization(c: &mut Criterion) {
         let txs: Vec<Transaction> = (0..batch_size)
             .map(|_| Transaction {
                 version: 1,
+                nonce: 0,
                 id: 42,
                 sender: "Alice".to_string(),
                 recipient: "Bob".to_string(),
                 amount: 1000,
-                signature: vec![1, 2, 3, 4],
+                signature: vec![1, 2, 3, 4].into(),
                 fee: 0.01,
             })
             .collect();
@@ -193,25 +320,60 @@ fn bench_parallel_deserialization(c: &mut Criterion) {
 }
 
 /// --- Benchmark: Deserialization with Preallocated Buffer ---
-/// Reuses a preallocated stack buffer for inputs ≤4 KB to eliminate dynamic allocation overhead.
+/// Receives frames from a stream into a reused thread-local buffer (≤4 KB by default), so no frame buffer is
+/// allocated per receive. The 2 KB case compares the pooled path (pool sized to fit) against reading each frame
+/// into a fresh `Vec` before `deserialize`.
 fn bench_deserialization_with_pool(c: &mut Criterion) {
     let tx = Transaction {
         version: 1,
+        nonce: 0,
         id: 42,
         sender: "Alice".to_string(),
         recipient: "Bob".to_string(),
         amount: 1000,
-        signature: vec![1, 2, 3, 4],
+        signature: vec![1, 2, 3, 4].into(),
         fee: 0.01,
     };
     let ser = Serializer::serialize(&tx, Endianness::Little).expect("Serialization failed");
     c.bench_function("deserialization_with_pool", |b| {
         b.iter(|| {
-            let de: Transaction = Serializer::deserialize_with_pool(black_box(&ser), Endianness::Little)
-                .expect("Deserialization with pool failed");
+            let de: Transaction = Serializer::deserialize_with_pool::<_, _, { Serializer::DEFAULT_POOL_BUFFER_SIZE }>(
+                &mut black_box(&ser[..]),
+                Endianness::Little,
+            )
+            .expect("Deserialization with pool failed");
+            black_box(de);
+        })
+    });
+
+    // ~2 KB frame (fits the 2048-byte pool buffer); the signature dominates the payload.
+    let large_tx = Transaction { signature: vec![7u8; 1900].into(), ..tx };
+    let large_ser = Serializer::serialize(&large_tx, Endianness::Little).expect("Serialization failed");
+    assert!(large_ser.len() <= 2048);
+    let mut group = c.benchmark_group("deserialization_2kb");
+    group.throughput(Throughput::Bytes(large_ser.len() as u64));
+    group.bench_function("pooled", |b| {
+        b.iter(|| {
+            let de: Transaction =
+                Serializer::deserialize_with_pool::<_, _, 2048>(&mut black_box(&large_ser[..]), Endianness::Little)
+                    .expect("Deserialization with pool failed");
+            black_box(de);
+        })
+    });
+    group.bench_function("naive", |b| {
+        b.iter(|| {
+            let mut reader = black_box(&large_ser[..]);
+            let mut prefix = [0u8; 4];
+            std::io::Read::read_exact(&mut reader, &mut prefix).expect("Read failed");
+            let mut frame = vec![0u8; 4 + u32::from_le_bytes(prefix) as usize];
+            frame[..4].copy_from_slice(&prefix);
+            std::io::Read::read_exact(&mut reader, &mut frame[4..]).expect("Read failed");
+            let de: Transaction = Serializer::deserialize(&frame, Endianness::Little)
+                .expect("Deserialization failed");
             black_box(de);
         })
     });
+    group.finish();
 }
 
 /// --- Benchmark: Ultra-Low-Latency Serialization (Fixed-Length) ---
@@ -219,15 +381,16 @@ fn bench_deserialization_with_pool(c: &mut Criterion) {
 fn bench_ultra_low_latency_serialization(c: &mut Criterion) {
     let tx = Transaction {
         version: 1,
+        nonce: 0,
         id: 42,
         sender: "Alice".to_string(),
         recipient: "Bob".to_string(),
         amount: 1000,
-        signature: vec![1, 2, 3, 4],
+        signature: vec![1, 2, 3, 4].into(),
         fee: 0.01,
     };
 
-    let mut buffer = [0u8; 128]; // ULTRA_TX_SIZE is 121 bytes; use 128 for alignment.
+    let mut buffer = [0u8; 136]; // ULTRA_TX_SIZE is 129 bytes; use 136 for alignment.
     c.bench_function("ultra_low_latency_serialization", |b| {
         b.iter(|| {
             let len = serialize_fixed(black_box(&tx), &mut buffer, Endianness::Little)
@@ -237,6 +400,40 @@ fn bench_ultra_low_latency_serialization(c: &mut Criterion) {
     });
 }
 
+/// --- Benchmark: Ultra-Fixed Encoding, Fresh vs. Reused Buffer ---
+/// Compares `serialize_ultra_fixed`, which zero-initializes a new array per call,
+/// with `serialize_ultra_fixed_into`, which overwrites a caller-owned buffer.
+fn bench_ultra_fixed_into(c: &mut Criterion) {
+    let tx = Transaction {
+        version: 1,
+        nonce: 0,
+        id: 42,
+        sender: "Alice".to_string(),
+        recipient: "Bob".to_string(),
+        amount: 1000,
+        signature: vec![1, 2, 3, 4].into(),
+        fee: 0.01,
+    };
+
+    let mut group = c.benchmark_group("ultra_fixed_fresh_vs_into");
+    group.bench_function("fresh", |b| {
+        b.iter(|| {
+            let fixed = Serializer::serialize_ultra_fixed(black_box(&tx), Endianness::Little)
+                .expect("Ultra-fixed serialization failed");
+            black_box(fixed);
+        })
+    });
+    let mut buffer = [0u8; Serializer::ULTRA_TX_SIZE];
+    group.bench_function("into", |b| {
+        b.iter(|| {
+            Serializer::serialize_ultra_fixed_into(black_box(&tx), Endianness::Little, &mut buffer)
+                .expect("Ultra-fixed serialization failed");
+            black_box(&buffer);
+        })
+    });
+    group.finish();
+}
+
 /// --- Benchmark: Varint vs. Fixed-Length Encoding for u64 ---
 /// Benchmarks encoding and decoding separately.
 fn bench_varint_vs_fixed(c: &mut Criterion) {
@@ -269,6 +466,7 @@ fn bench_varint_vs_fixed(c: &mut Criterion) {
             value >>= 7;
         }
         buf[i] = value as u8;
+        black_box(&buf);
         i + 1
     };
 
@@ -339,11 +537,12 @@ fn bench_buffer_preallocation(c: &mut Criterion) {
 
     let tx = Transaction {
         version: 1,
+        nonce: 0,
         id: 42,
         sender: "Alice".to_string(),
         recipient: "Bob".to_string(),
         amount: 1000,
-        signature: vec![1, 2, 3, 4],
+        signature: vec![1, 2, 3, 4].into(),
         fee: 0.01,
     };
 
@@ -371,14 +570,15 @@ fn bench_buffer_preallocation(c: &mut Criterion) {
 fn bench_large_scale_stress(c: &mut Criterion) {
     let tx = Transaction {
         version: 1,
+        nonce: 0,
         id: 42,
         sender: "Alice".to_string(),
         recipient: "Bob".to_string(),
         amount: 1000,
-        signature: vec![1, 2, 3, 4],
+        signature: vec![1, 2, 3, 4].into(),
         fee: 0.01,
     };
-    let batch: Vec<Transaction> = std::iter::repeat(tx.clone()).take(10_000).collect();
+    let batch: Vec<Transaction> = std::iter::repeat_n(tx.clone(), 10_000).collect();
     // Preheat to warm caches.
     let _ = Serializer::serialize_batch(&batch, Endianness::Little).expect("Preheat failed");
 
@@ -401,14 +601,15 @@ fn bench_large_scale_stress(c: &mut Criterion) {
 fn bench_concurrency_stress(c: &mut Criterion) {
     let tx = Transaction {
         version: 1,
+        nonce: 0,
         id: 42,
         sender: "Alice".to_string(),
         recipient: "Bob".to_string(),
         amount: 1000,
-        signature: vec![1, 2, 3, 4],
+        signature: vec![1, 2, 3, 4].into(),
         fee: 0.01,
     };
-    let txs: Vec<Transaction> = std::iter::repeat(tx.clone()).take(10_000).collect();
+    let txs: Vec<Transaction> = std::iter::repeat_n(tx.clone(), 10_000).collect();
     let ser_batch: Vec<Vec<u8>> = txs.iter()
         .map(|tx| Serializer::serialize(tx, Endianness::Little).expect("Serialization failed"))
         .collect();
@@ -448,11 +649,12 @@ fn bench_security_audit(c: &mut Criterion) {
         {
             let tx = Transaction {
                 version: 1,
+                nonce: 0,
                 id: 42,
                 sender: "Alice".to_string(),
                 recipient: "Bob".to_string(),
                 amount: 1000,
-                signature: vec![1, 2, 3, 4],
+                signature: vec![1, 2, 3, 4].into(),
                 fee: 0.01,
             };
             let mut valid = Serializer::serialize(&tx, Endianness::Little)
@@ -476,10 +678,14 @@ criterion_group!(
     benches,
     bench_single_transaction,
     bench_serialize_batch,
+    bench_ultra_batch_vs_varint,
+    bench_block_serialization_parallel,
     bench_batch_deserialization_seq,
+    bench_signature_storage_decode,
     bench_parallel_deserialization,
     bench_deserialization_with_pool,
     bench_ultra_low_latency_serialization,
+    bench_ultra_fixed_into,
     bench_varint_vs_fixed,
     bench_blake3_overhead,
     bench_buffer_preallocation,
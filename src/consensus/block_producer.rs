@@ -1,105 +1,719 @@
 // File: src/consensus/block_producer.rs
 //! Minimal Block Producer for Phase 1.
 //!
-//! This module simulates block production by maintaining its own block counter,
-//! pulling transactions from a mempool, and simulating validator work (via PoCUP functions).
-//! The produced block includes a sequential block number, a default previous hash,
-//! a batch of transactions, and the current timestamp. Future phases will integrate
-//! real previous block linking and advanced consensus logic.
+//! This module simulates block production by pulling transactions from a mempool
+//! and simulating validator work (via PoCUP functions). Each produced block
+//! extends the chain manager's head: it carries the next block number, the
+//! head's hash as its previous hash, a batch of transactions, and the current
+//! timestamp. Future phases will integrate advanced consensus logic.
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use crate::node::chain_manager::ChainManager;
-use crate::node::mempool::Mempool;
-use crate::pocup::pocup::{perform_useful_work, slash_if_needed};
-use crate::utils::serialization::Transaction;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey, SIGNATURE_LENGTH};
+use log::info;
+use rayon::prelude::*;
+
+use crate::node::chain_manager::{BlockValidationError, ChainManager};
+use crate::node::mempool::{Mempool, RejectReason};
+use crate::pocup::pocup::{perform_useful_work_with, slash_if_needed};
+use crate::roc::sentinel::{check_spam_with, SpamPolicy};
+use crate::utils::hasher::{merkle_root, Blake3Hasher, Hasher};
+use crate::utils::serialization::{
+    Decode, Encode, Endianness, SerializationError, SerializationResult, Transaction,
+};
 
 /// A minimal Block structure for Phase 1.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Block {
     /// Sequential block number.
     pub block_number: u64,
-    /// Previous block's hash; Phase 1 uses a default value.
+    /// Hash of the parent block; see `hash`.
     pub previous_hash: [u8; 32],
     /// List of transactions included in this block.
     pub transactions: Vec<Transaction>,
+    /// Number of transactions, committed to by `hash` so a light client holding
+    /// only the header can rely on it; see `verify_tx_count`.
+    pub tx_count: u64,
+    /// Merkle root of the transactions under the chain's hasher (see
+    /// `compute_merkle_root_with`), committed to by `hash` like `tx_count`.
+    pub merkle_root: [u8; 32],
     /// Block timestamp in seconds since UNIX_EPOCH.
     pub timestamp: u64,
-    /// Placeholder signature.
+    /// Ed25519 signature over `signing_bytes`; empty until `sign` is called.
     pub signature: Vec<u8>,
 }
 
+impl Block {
+    /// Returns the Blake3 hash of `signing_bytes`. This is the value the next
+    /// block stores as its `previous_hash`; it is the same before and after signing.
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash_with::<Blake3Hasher>()
+    }
+
+    /// Like `hash`, under hash function `H`.
+    pub fn hash_with<H: Hasher>(&self) -> [u8; 32] {
+        H::hash(&self.signing_bytes())
+    }
+
+    /// Returns the Blake3 Merkle root of the transactions (see
+    /// `hasher::merkle_root`).
+    pub fn compute_merkle_root(&self) -> [u8; 32] {
+        self.compute_merkle_root_with::<Blake3Hasher>()
+    }
+
+    /// Like `compute_merkle_root`, under hash function `H`. Each leaf is the
+    /// hash of a transaction's little-endian encoding.
+    pub fn compute_merkle_root_with<H: Hasher>(&self) -> [u8; 32] {
+        let leaves: Vec<[u8; 32]> = self
+            .transactions
+            .iter()
+            .map(|tx| {
+                let mut buffer = vec![0u8; tx.encoded_size()];
+                tx.encode_to(&mut buffer, Endianness::Little).expect("buffer sized by encoded_size");
+                H::hash(&buffer)
+            })
+            .collect();
+        merkle_root::<H>(&leaves)
+    }
+
+    /// Returns the canonical little-endian encoding of every field except
+    /// `signature`: block number, previous hash, transactions, timestamp,
+    /// transaction count, and Merkle root.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![0u8; self.unsigned_size()];
+        self.encode_unsigned_to(&mut buffer, Endianness::Little)
+            .expect("buffer sized by unsigned_size");
+        buffer
+    }
+
+    /// Sets `tx_count` and the Blake3 `merkle_root` from the current transactions.
+    pub fn commit_transactions(&mut self) {
+        self.commit_transactions_with::<Blake3Hasher>();
+    }
+
+    /// Like `commit_transactions`, under hash function `H`.
+    pub fn commit_transactions_with<H: Hasher>(&mut self) {
+        self.tx_count = self.transactions.len() as u64;
+        self.merkle_root = self.compute_merkle_root_with::<H>();
+    }
+
+    /// Returns true if the block holds no transactions.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns true if the committed `tx_count` equals the number of transactions.
+    pub fn verify_tx_count(&self) -> bool {
+        self.tx_count == self.transactions.len() as u64
+    }
+
+    /// Signs `signing_bytes` with `key`, replacing any previous signature.
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signature = key.sign(&self.signing_bytes()).to_bytes().to_vec();
+    }
+
+    /// Returns true if `signature` is a valid signature by `key` over `signing_bytes`.
+    pub fn verify_signature(&self, key: &VerifyingKey) -> bool {
+        Signature::from_slice(&self.signature).is_ok_and(|sig| key.verify(&self.signing_bytes(), &sig).is_ok())
+    }
+
+    /// Checks every transaction in parallel: a non-finite fee is
+    /// `NonFiniteFee`, and failing `check_spam_with` under `policy` is `Spam`.
+    /// Unlike mempool admission this does not stop at the first failure; the
+    /// error lists the index and reason of each rejected transaction, in
+    /// block order.
+    pub fn validate_transactions_parallel(&self, policy: &SpamPolicy) -> Result<(), Vec<(usize, RejectReason)>> {
+        let failures: Vec<(usize, RejectReason)> = self
+            .transactions
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, tx)| {
+                let reason = if tx.validate().is_err() {
+                    RejectReason::NonFiniteFee
+                } else if !check_spam_with(tx, policy) {
+                    RejectReason::Spam
+                } else {
+                    return None;
+                };
+                Some((index, reason))
+            })
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+
+    fn unsigned_size(&self) -> usize {
+        self.block_number.encoded_size() +
+        self.previous_hash.encoded_size() +
+        (self.transactions.len() as u64).encoded_size() +
+        self.transactions.iter().map(|tx| tx.encoded_size()).sum::<usize>() +
+        self.timestamp.encoded_size() +
+        self.tx_count.encoded_size() +
+        self.merkle_root.encoded_size()
+    }
+
+    fn encode_unsigned_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let mut offset = 0;
+        offset += self.block_number.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.previous_hash.encode_to(&mut buffer[offset..], endianness)?;
+        offset += (self.transactions.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+        for tx in &self.transactions {
+            offset += tx.encode_to(&mut buffer[offset..], endianness)?;
+        }
+        offset += self.timestamp.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.tx_count.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.merkle_root.encode_to(&mut buffer[offset..], endianness)?;
+        Ok(offset)
+    }
+}
+
+impl Encode for Block {
+    fn encoded_size(&self) -> usize {
+        self.unsigned_size() + self.signature.encoded_size()
+    }
+
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let mut offset = self.encode_unsigned_to(buffer, endianness)?;
+        offset += self.signature.encode_to(&mut buffer[offset..], endianness)?;
+        Ok(offset)
+    }
+}
+
+impl Decode for Block {
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let mut offset = 0;
+        let (block_number, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (previous_hash, consumed) = <[u8; 32]>::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (len, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        // Every transaction takes at least one byte, so a count beyond the buffer is corrupt.
+        if len > buffer.len() as u64 {
+            return Err(SerializationError::InvalidData("Transaction count exceeds buffer".into()));
+        }
+        let mut transactions = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let (tx, consumed) = Transaction::decode_from(&buffer[offset..], endianness)?;
+            offset += consumed;
+            transactions.push(tx);
+        }
+        let (timestamp, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (tx_count, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (merkle_root, consumed) = <[u8; 32]>::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (signature, consumed) = Vec::<u8>::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        Ok((Block { block_number, previous_hash, transactions, tx_count, merkle_root, timestamp, signature }, offset))
+    }
+}
+
+/// Sorts transactions into the canonical in-block order: fee descending, with
+/// ties broken by ascending `id`. Validators building from the same transactions
+/// therefore produce identical orderings regardless of mempool insertion order.
+pub fn canonical_order(transactions: &mut [Transaction]) {
+    transactions.sort_by(|a, b| b.fee.total_cmp(&a.fee).then(a.id.cmp(&b.id)));
+}
+
+/// Reorders priority-sorted `transactions` so each sender's transactions
+/// appear in ascending nonce order, as the ledger requires. Across senders fee
+/// priority is kept: each step takes the highest-priority transaction whose
+/// sender has no lower-nonce transaction still waiting.
+pub fn order_by_sender_nonce(transactions: &mut Vec<Transaction>) {
+    // Positions in the incoming order serve as priorities; lower is better.
+    let mut by_nonce: Vec<usize> = (0..transactions.len()).collect();
+    by_nonce.sort_by_key(|&i| transactions[i].nonce);
+    let mut queues: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for i in by_nonce {
+        queues.entry(transactions[i].sender.as_str()).or_default().push_back(i);
+    }
+    let mut heads: BinaryHeap<Reverse<(usize, &str)>> =
+        queues.iter().map(|(&sender, queue)| Reverse((queue[0], sender))).collect();
+    let mut order = Vec::with_capacity(transactions.len());
+    while let Some(Reverse((position, sender))) = heads.pop() {
+        order.push(position);
+        let queue = queues.get_mut(sender).expect("every head has a queue");
+        queue.pop_front();
+        if let Some(&next) = queue.front() {
+            heads.push(Reverse((next, sender)));
+        }
+    }
+    let mut slots: Vec<Option<Transaction>> = transactions.drain(..).map(Some).collect();
+    transactions.extend(order.into_iter().map(|i| slots[i].take().expect("each position is taken once")));
+}
+
+/// Default cap on the number of transactions pulled into a single block.
+pub const DEFAULT_MAX_BLOCK_TRANSACTIONS: usize = 2;
+
+/// Summary of the transactions a `produce_block` call would include, computed
+/// without touching the mempool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockPreview {
+    /// Number of transactions that would be selected.
+    pub transaction_count: usize,
+    /// Combined encoded size of the selected transactions, in bytes.
+    pub total_bytes: usize,
+    /// Sum of the selected transactions' fees.
+    pub total_fees: f64,
+}
+
+/// Reasons `produce_block` did not produce a block.
+#[derive(Debug, PartialEq)]
+pub enum ProduceError {
+    /// The mempool had no transactions and empty blocks are disabled.
+    EmptyMempool,
+    /// The chain manager refused to append the produced block.
+    Rejected(BlockValidationError),
+}
+
+impl fmt::Display for ProduceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProduceError::EmptyMempool => write!(f, "Mempool is empty and empty blocks are disabled"),
+            ProduceError::Rejected(e) => write!(f, "Produced block was rejected: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProduceError {}
+
+/// Measurements from the most recent successful `produce_block` call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductionStats {
+    /// Number of transactions included.
+    pub tx_count: usize,
+    /// Sum of the included transactions' fees.
+    pub total_fees: f64,
+    /// Wall-clock time spent producing the block.
+    pub duration: Duration,
+}
+
+/// Source of block timestamps, injectable so tests can fix the time.
+pub trait Clock {
+    /// Returns the current time in seconds since UNIX_EPOCH.
+    fn now_secs(&self) -> u64;
+}
+
+/// Clock backed by `SystemTime::now()`.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time before UNIX_EPOCH")
+            .as_secs()
+    }
+}
+
 /// BlockProducer produces new blocks by pulling transactions from the mempool
 /// and simulating validator work. It holds a reference to a ChainManager for access
-/// to validators (for PoCUP tasks) and its own block counter.
-pub struct BlockProducer<'a> {
-    /// Reference to the ChainManager (for validator work).
-    pub chain_manager: &'a mut ChainManager,
-    /// Internal block counter for sequential block numbering.
-    pub block_counter: u64,
-}
-
-impl<'a> BlockProducer<'a> {
-    /// Creates a new BlockProducer with the given ChainManager.
-    /// Initializes the block counter to 1.
-    pub fn new(chain_manager: &'a mut ChainManager) -> Self {
+/// to the chain head it builds on and to validators (for PoCUP tasks).
+pub struct BlockProducer<'a, H: Hasher = Blake3Hasher> {
+    /// Reference to the ChainManager (for the chain head and validator work).
+    pub chain_manager: &'a mut ChainManager<H>,
+    /// Maximum number of transactions pulled from the mempool per block.
+    pub max_transactions: usize,
+    /// Source of block timestamps.
+    pub clock: Box<dyn Clock>,
+    /// Produce blocks with no transactions instead of returning `EmptyMempool`.
+    pub allow_empty_blocks: bool,
+    /// Most transactions from any one sender per block; `None` for no limit.
+    pub max_txs_per_sender: Option<usize>,
+    /// Stats for the last block produced.
+    last_stats: Option<ProductionStats>,
+}
+
+impl<'a, H: Hasher> BlockProducer<'a, H> {
+    /// Creates a new BlockProducer with the given ChainManager. Blocks are
+    /// hashed and committed with the chain manager's hasher `H`.
+    pub fn new(chain_manager: &'a mut ChainManager<H>) -> Self {
+        Self::with_max_transactions(chain_manager, DEFAULT_MAX_BLOCK_TRANSACTIONS)
+    }
+
+    /// Creates a new BlockProducer that pulls at most `max_transactions` per block.
+    pub fn with_max_transactions(chain_manager: &'a mut ChainManager<H>, max_transactions: usize) -> Self {
         Self {
             chain_manager,
-            block_counter: 1,
+            max_transactions,
+            clock: Box::new(SystemClock),
+            allow_empty_blocks: false,
+            max_txs_per_sender: None,
+            last_stats: None,
         }
     }
 
+    /// Returns stats for the most recently produced block, if any.
+    pub fn last_stats(&self) -> Option<&ProductionStats> {
+        self.last_stats.as_ref()
+    }
+
+    /// Sets whether an empty mempool yields an empty block (`true`) or
+    /// `ProduceError::EmptyMempool` (`false`, the default).
+    pub fn with_empty_blocks(mut self, allow_empty_blocks: bool) -> Self {
+        self.allow_empty_blocks = allow_empty_blocks;
+        self
+    }
+
+    /// Replaces the clock used for block timestamps.
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Limits each block to `max` transactions from any one sender. Extras
+    /// stay in the mempool for later blocks.
+    pub fn with_max_txs_per_sender(mut self, max: usize) -> Self {
+        self.max_txs_per_sender = Some(max);
+        self
+    }
+
+    /// Reports what the next `produce_block` would collect from `mempool`
+    /// without removing anything: the same fee-priority selection capped at
+    /// `max_transactions` and `max_txs_per_sender`, trimmed to `max_block_bytes`.
+    pub fn preview_block(&self, mempool: &Mempool) -> BlockPreview {
+        let selected = mempool.peek_for_block(self.max_transactions, self.max_txs_per_sender);
+        let (block, _) = self.assemble_block(selected, self.clock.now_secs());
+        BlockPreview {
+            transaction_count: block.transactions.len(),
+            total_bytes: block.transactions.iter().map(|tx| tx.encoded_size()).sum(),
+            total_fees: block.transactions.iter().map(|tx| tx.fee).sum(),
+        }
+    }
+
+    /// Builds the block on top of the head from selected `transactions`:
+    /// puts them in per-sender nonce order, trims the block to the chain
+    /// manager's `max_block_bytes`, then commits to what is left. Returns the
+    /// block and the trimmed-off transactions.
+    fn assemble_block(&self, mut transactions: Vec<Transaction>, timestamp: u64) -> (Block, Vec<Transaction>) {
+        order_by_sender_nonce(&mut transactions);
+        let head = self.chain_manager.head();
+        let mut block = Block {
+            block_number: head.block_number + 1,
+            previous_hash: head.hash_with::<H>(),
+            tx_count: transactions.len() as u64,
+            transactions,
+            merkle_root: [0u8; 32],
+            timestamp,
+            signature: Vec::new(), // Placeholder; no real signature yet.
+        };
+        let overflow = trim_to_size(&mut block, self.chain_manager.max_block_bytes());
+        block.commit_transactions_with::<H>();
+        (block, overflow)
+    }
+
     /// Produces a new block by:
-    /// 1. Using the internal block counter as the new block number.
-    /// 2. Setting previous_hash to a default ([0u8;32]) since no prior block is tracked.
-    /// 3. Pulling up to two transactions from the mempool.
-    /// 4. Running PoCUP tasks on each validator (simulate work and slashing).
-    /// 5. Setting the block timestamp to SystemTime::now().
-    /// 6. Incrementing the block counter.
-    pub fn produce_block(&mut self, mempool: &mut Mempool) -> Block {
-        let block_number = self.block_counter;
-        let previous_hash = [0u8; 32]; // Phase 1 uses a default previous hash.
-
-        // Pull up to 2 transactions from the mempool (FIFO).
-        let mut transactions = Vec::new();
-        for _ in 0..2 {
-            if let Some(tx) = mempool.remove_transaction() {
-                transactions.push(tx);
-            }
+    /// 1. Numbering the block one past the chain manager's head.
+    /// 2. Setting previous_hash to the head's hash.
+    /// 3. Pulling up to `max_transactions` transactions from the mempool in its
+    ///    `FeeOrdering`, at most `max_txs_per_sender` from each sender.
+    /// 4. Keeping that fee priority across senders while putting each sender's
+    ///    transactions in nonce order (see `order_by_sender_nonce`).
+    /// 5. Running PoCUP tasks on each validator for the head at the chain's
+    ///    `puzzle_difficulty` (simulate work and slashing).
+    /// 6. Setting the block timestamp from the producer's clock.
+    /// 7. Dropping transactions from the end until the block, once signed, fits
+    ///    the chain manager's `max_block_bytes`; they go back to the mempool.
+    /// 8. Recording `ProductionStats`.
+    ///
+    /// The block is not appended; producing again before the chain grows
+    /// builds another candidate at the same height.
+    ///
+    /// Returns `ProduceError::EmptyMempool` without side effects when the
+    /// mempool is empty, unless `allow_empty_blocks` is set.
+    pub fn produce_block(&mut self, mempool: &mut Mempool) -> Result<Block, ProduceError> {
+        if mempool.size() == 0 && !self.allow_empty_blocks {
+            return Err(ProduceError::EmptyMempool);
         }
+        let started = Instant::now();
+        let previous_hash = self.chain_manager.head().hash_with::<H>();
+
+        // Pull the highest-priority transactions from the mempool.
+        let transactions = mempool.select_for_block_capped(self.max_transactions, self.max_txs_per_sender);
 
         // Simulate PoCUP work on validators.
-        // For each validator in the chain manager, perform useful work and check for slashing.
+        // Each validator solves the puzzle for the head at the chain's difficulty,
+        // then is checked for slashing.
+        let difficulty = self.chain_manager.puzzle_difficulty();
         for v in &mut self.chain_manager.validators {
-            perform_useful_work(v);
+            perform_useful_work_with::<H>(v, &previous_hash, difficulty);
             slash_if_needed(v);
         }
 
-        // Get current timestamp.
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("System time before UNIX_EPOCH")
-            .as_secs();
+        let timestamp = self.clock.now_secs();
+        let (block, overflow) = self.assemble_block(transactions, timestamp);
+        if block.is_empty() {
+            info!("Producing empty block #{}.", block.block_number);
+        }
+        if !overflow.is_empty() {
+            info!("Block #{} is full; {} transactions stay pending.", block.block_number, overflow.len());
+            mempool.reinsert(overflow);
+        }
 
-        let block = Block {
-            block_number,
-            previous_hash,
-            transactions,
-            timestamp,
-            signature: Vec::new(), // Placeholder; no real signature yet.
-        };
+        self.last_stats = Some(ProductionStats {
+            tx_count: block.transactions.len(),
+            total_fees: block.transactions.iter().map(|tx| tx.fee).sum(),
+            duration: started.elapsed(),
+        });
+        Ok(block)
+    }
+
+    /// Produces a block like `produce_block` and appends it to the chain
+    /// manager, returning a copy. If the chain rejects it, its transactions go
+    /// back to the mempool and `ProduceError::Rejected` is returned.
+    pub fn produce_and_append(&mut self, mempool: &mut Mempool) -> Result<Block, ProduceError> {
+        let block = self.produce_block(mempool)?;
+        if let Err(e) = self.chain_manager.append_block(block.clone()) {
+            mempool.reinsert(block.transactions);
+            return Err(ProduceError::Rejected(e));
+        }
+        Ok(block)
+    }
+}
+
+/// Removes transactions from the end of `block` until its encoded size, with
+/// room for a signature, is at most `max_bytes`, returning them in block
+/// order. Trimming from the end keeps each sender's remaining nonces contiguous.
+/// `tx_count` and `merkle_root` are left for the caller to recommit.
+fn trim_to_size(block: &mut Block, max_bytes: usize) -> Vec<Transaction> {
+    // An unsigned block encodes its empty signature in one byte; a signed one
+    // adds the signature bytes. Dropping a transaction can also shrink the
+    // count prefix and `tx_count`, so the running total only ever overestimates.
+    let mut size = block.encoded_size() + SIGNATURE_LENGTH;
+    let mut keep = block.transactions.len();
+    while size > max_bytes && keep > 0 {
+        keep -= 1;
+        size -= block.transactions[keep].encoded_size();
+    }
+    block.transactions.split_off(keep)
+}
+
+/// Runs the continuous block production loop.
+///
+/// Runs a production cycle every `interval`, appending each produced block to
+/// the chain and passing it to `on_block`; cycles that find nothing to produce are logged and skipped.
+/// When `max_cycles` is `Some(n)` the loop returns after `n` cycles; with
+/// `None` it runs forever, which is what the node binary uses.
+pub fn run_production_loop<H: Hasher, F>(
+    producer: &mut BlockProducer<H>,
+    mempool: &mut Mempool,
+    max_cycles: Option<u64>,
+    interval: Duration,
+    mut on_block: F,
+) where
+    F: FnMut(&Block),
+{
+    let mut cycles = 0u64;
+    while max_cycles.is_none_or(|max| cycles < max) {
+        let block_number = producer.chain_manager.height() + 1;
+        info!("Producing block #{}...", block_number);
+        match producer.produce_and_append(mempool) {
+            Ok(block) => on_block(&block),
+            Err(e) => info!("Skipped block #{}: {}", block_number, e),
+        }
+        cycles += 1;
+        if max_cycles.is_none_or(|max| cycles < max) {
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// Default cap on the blocks a scheduled cycle produces while catching up.
+pub const DEFAULT_MAX_BLOCKS_PER_CYCLE: usize = 4;
+
+/// Default mempool backlog at which `ProductionScheduler::submit` refuses work.
+pub const DEFAULT_MAX_PENDING: usize = 10_000;
+
+/// Counters kept by a `ProductionScheduler`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchedulerMetrics {
+    /// Production cycles run.
+    pub cycles_run: u64,
+    /// Ticks missed because a cycle overran its interval; each run of missed
+    /// ticks is coalesced into the next cycle instead of replayed.
+    pub cycles_skipped: u64,
+    /// Blocks produced across all cycles.
+    pub blocks_produced: u64,
+    /// Transactions refused by `submit` because the backlog was full.
+    pub transactions_deferred: u64,
+}
 
-        self.block_counter += 1;
-        block
+/// Drives block production on a fixed interval with bounded work per cycle.
+///
+/// Each cycle produces one block, plus up to `max_blocks_per_cycle - 1` more
+/// while the mempool still has a backlog. If a cycle overruns, the missed
+/// ticks are skipped rather than run back to back. Transactions submitted
+/// while `max_pending` are already waiting are refused, pushing the backlog
+/// back onto their senders instead of letting the mempool grow without bound.
+pub struct ProductionScheduler {
+    /// Time between cycle starts.
+    interval: Duration,
+    /// Most blocks produced in a single cycle.
+    max_blocks_per_cycle: usize,
+    /// Mempool size at which new transactions are refused.
+    max_pending: usize,
+    metrics: SchedulerMetrics,
+}
+
+impl ProductionScheduler {
+    /// Creates a scheduler that starts a cycle every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            max_blocks_per_cycle: DEFAULT_MAX_BLOCKS_PER_CYCLE,
+            max_pending: DEFAULT_MAX_PENDING,
+            metrics: SchedulerMetrics::default(),
+        }
+    }
+
+    /// Sets the most blocks a single cycle may produce; at least one.
+    pub fn with_max_blocks_per_cycle(mut self, max_blocks_per_cycle: usize) -> Self {
+        self.max_blocks_per_cycle = max_blocks_per_cycle.max(1);
+        self
+    }
+
+    /// Sets the mempool size at which `submit` starts refusing transactions.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+
+    /// Returns the scheduler's counters.
+    pub fn metrics(&self) -> &SchedulerMetrics {
+        &self.metrics
+    }
+
+    /// Returns true if `mempool` holds at least `max_pending` transactions.
+    pub fn is_backlogged(&self, mempool: &Mempool) -> bool {
+        mempool.size() >= self.max_pending
+    }
+
+    /// Adds `tx` to `mempool` unless production is backlogged, in which case
+    /// it is refused with `RejectReason::Backpressure`.
+    pub fn submit(&mut self, mempool: &mut Mempool, tx: Transaction) -> Result<(), RejectReason> {
+        if self.is_backlogged(mempool) {
+            self.metrics.transactions_deferred += 1;
+            return Err(RejectReason::Backpressure { pending: mempool.size() });
+        }
+        mempool.try_add_transaction(tx)
+    }
+
+    /// Runs one cycle, appending each produced block to the chain and passing
+    /// it to `on_block`, and returns the number of blocks produced.
+    pub fn run_cycle<H: Hasher, F>(
+        &mut self,
+        producer: &mut BlockProducer<H>,
+        mempool: &mut Mempool,
+        mut on_block: F,
+    ) -> usize
+    where
+        F: FnMut(&Block),
+    {
+        self.metrics.cycles_run += 1;
+        let mut produced = 0;
+        while produced < self.max_blocks_per_cycle && (produced == 0 || mempool.size() > 0) {
+            match producer.produce_and_append(mempool) {
+                Ok(block) => on_block(&block),
+                Err(e) => {
+                    info!("Skipped block #{}: {}", producer.chain_manager.height() + 1, e);
+                    break;
+                }
+            }
+            produced += 1;
+        }
+        self.metrics.blocks_produced += produced as u64;
+        if mempool.size() > 0 {
+            info!("Production behind after cycle: {} transactions pending", mempool.size());
+        }
+        produced
+    }
+
+    /// Runs cycles every `interval`, like `run_production_loop`, returning
+    /// after `max_cycles` cycles if given.
+    pub fn run<H: Hasher, F>(
+        &mut self,
+        producer: &mut BlockProducer<H>,
+        mempool: &mut Mempool,
+        max_cycles: Option<u64>,
+        on_block: F,
+    ) where
+        F: FnMut(&Block),
+    {
+        self.run_until(producer, mempool, max_cycles, &AtomicBool::new(false), on_block);
+    }
+
+    /// Like `run`, but also returns once `shutdown` is set. The flag is checked
+    /// before each cycle and while waiting for the next one, so a cycle in
+    /// progress always finishes and no block is abandoned half-built.
+    pub fn run_until<H: Hasher, F>(
+        &mut self,
+        producer: &mut BlockProducer<H>,
+        mempool: &mut Mempool,
+        max_cycles: Option<u64>,
+        shutdown: &AtomicBool,
+        mut on_block: F,
+    ) where
+        F: FnMut(&Block),
+    {
+        let mut next_tick = Instant::now();
+        let mut cycles = 0u64;
+        while max_cycles.is_none_or(|max| cycles < max) && !shutdown.load(Ordering::SeqCst) {
+            self.run_cycle(producer, mempool, &mut on_block);
+            cycles += 1;
+            let (skipped, next) = next_cycle_start(next_tick, Instant::now(), self.interval);
+            if skipped > 0 {
+                info!("Production overran; skipping {} cycle(s)", skipped);
+                self.metrics.cycles_skipped += skipped;
+            }
+            next_tick = next;
+            if max_cycles.is_none_or(|max| cycles < max) {
+                sleep_until(next_tick, shutdown);
+            }
+        }
     }
 }
 
+/// Longest stretch `sleep_until` sleeps without re-checking its shutdown flag.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Sleeps until `deadline`, waking early once `shutdown` is set.
+fn sleep_until(deadline: Instant, shutdown: &AtomicBool) {
+    while !shutdown.load(Ordering::SeqCst) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return;
+        }
+        thread::sleep(remaining.min(SHUTDOWN_POLL_INTERVAL));
+    }
+}
+
+/// Given the start of the cycle that just ran, returns how many ticks have
+/// already been missed at `now` and when the next cycle should start.
+fn next_cycle_start(started: Instant, now: Instant, interval: Duration) -> (u64, Instant) {
+    let next = started + interval;
+    if interval.is_zero() || now <= next {
+        return (0, next);
+    }
+    let missed = ((now - next).as_nanos() / interval.as_nanos()) as u64 + 1;
+    (missed, next + interval * missed as u32)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::node::chain_manager::ChainManager;
-    use crate::node::mempool::Mempool;
-    use crate::utils::serialization::Transaction;
+    use crate::node::mempool::{FeeOrdering, Mempool};
+    use crate::utils::serialization::{Serializer, Transaction};
 
     fn dummy_tx(id: u64, fee: f64) -> Transaction {
         Transaction {
@@ -107,9 +721,10 @@ mod tests {
             amount: 1000,
             fee,
             version: 1,
+            nonce: 0,
             sender: "Alice".to_string(),
             recipient: "Bob".to_string(),
-            signature: vec![1, 2, 3, 4],
+            signature: vec![1, 2, 3, 4].into(),
         }
     }
 
@@ -117,8 +732,8 @@ mod tests {
     fn test_produce_block() {
         // Create a dummy ChainManager with some validators.
         let mut chain_manager = ChainManager::new();
-        chain_manager.add_validator("Validator_A".to_string(), 100);
-        chain_manager.add_validator("Validator_B".to_string(), 200);
+        chain_manager.add_validator("Validator_A".to_string(), 100).unwrap();
+        chain_manager.add_validator("Validator_B".to_string(), 200).unwrap();
 
         // Create a mempool and add a few transactions.
         let mut mempool = Mempool::new();
@@ -127,13 +742,533 @@ mod tests {
         }
 
         let mut producer = BlockProducer::new(&mut chain_manager);
-        let block = producer.produce_block(&mut mempool);
+        let block = producer.produce_block(&mut mempool).unwrap();
 
-        // Block number should match initial counter.
+        // The block extends genesis.
         assert_eq!(block.block_number, 1);
-        // Previous hash is default.
-        assert_eq!(block.previous_hash, [0u8; 32]);
+        assert_eq!(block.previous_hash, <ChainManager>::genesis().hash());
+        assert_eq!(producer.chain_manager.validate_block(&block), Ok(()));
+        // Validators solved the puzzle for the head the block extends.
+        assert!(producer.chain_manager.verify_work("Validator_A"));
         // Up to 2 transactions are pulled.
         assert!(block.transactions.len() <= 2);
     }
+
+    #[test]
+    fn test_preview_matches_produced_block() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        for i in 1..=5 {
+            mempool.add_transaction(dummy_tx(i, i as f64));
+        }
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 3);
+        let preview = producer.preview_block(&mempool);
+        assert_eq!(mempool.size(), 5);
+
+        let block = producer.produce_block(&mut mempool).unwrap();
+        assert_eq!(preview.transaction_count, block.transactions.len());
+        assert_eq!(preview.total_bytes, block.transactions.iter().map(|tx| tx.encoded_size()).sum::<usize>());
+        assert_eq!(preview.total_fees, block.transactions.iter().map(|tx| tx.fee).sum::<f64>());
+        assert_eq!(producer.preview_block(&mempool).transaction_count, 2);
+    }
+
+    #[test]
+    fn test_per_byte_ordering_prefers_dense_fees() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new().with_ordering(FeeOrdering::PerByte);
+        let mut large = dummy_tx(1, 50.0);
+        large.signature = vec![1u8; 1024].into();
+        let mut small = dummy_tx(2, 20.0);
+        small.sender = "Carol".to_string();
+        assert!(large.fee > small.fee && large.fee_per_byte() < small.fee_per_byte());
+        mempool.add_transaction(large);
+        mempool.add_transaction(small);
+
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 1);
+        assert_eq!(producer.preview_block(&mempool).total_fees, 20.0);
+        let block = producer.produce_block(&mut mempool).unwrap();
+        assert_eq!(block.transactions.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![2]);
+        assert!(mempool.contains(1));
+    }
+
+    #[test]
+    fn test_block_order_follows_mempool_ordering() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new().with_ordering(FeeOrdering::PerByte);
+        let mut large = dummy_tx(1, 50.0);
+        large.signature = vec![1u8; 1024].into();
+        mempool.add_transaction(large);
+        for (id, nonce) in [(2, 6), (3, 5)] {
+            let mut tx = dummy_tx(id, 20.0 - nonce as f64);
+            tx.sender = "Carol".to_string();
+            tx.nonce = nonce;
+            mempool.add_transaction(tx);
+        }
+
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 3);
+        let block = producer.produce_block(&mut mempool).unwrap();
+        // Carol's denser fees come first, nonce 5 before 6; the large one last.
+        assert_eq!(block.transactions.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_max_txs_per_sender() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        for i in 1..=10 {
+            mempool.add_transaction(dummy_tx(i, 50.0));
+        }
+        for (id, sender) in [(11, "Carol"), (12, "Dave")] {
+            let mut tx = dummy_tx(id, 5.0);
+            tx.sender = sender.to_string();
+            mempool.add_transaction(tx);
+        }
+
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 8).with_max_txs_per_sender(3);
+        assert_eq!(producer.preview_block(&mempool).transaction_count, 5);
+        let block = producer.produce_block(&mut mempool).unwrap();
+        let from = |sender: &str| block.transactions.iter().filter(|tx| tx.sender == sender).count();
+        assert_eq!(from("Alice"), 3);
+        assert_eq!(from("Carol"), 1);
+        assert_eq!(from("Dave"), 1);
+
+        // Alice's extras wait for later blocks, in arrival order.
+        let remaining: Vec<u64> = mempool.iter().map(|tx| tx.id).collect();
+        assert_eq!(remaining, (4..=10).collect::<Vec<_>>());
+    }
+
+    /// Clock that starts at a fixed time and advances 10 seconds per reading.
+    struct StepClock(std::cell::Cell<u64>);
+
+    impl Clock for StepClock {
+        fn now_secs(&self) -> u64 {
+            let now = self.0.get();
+            self.0.set(now + 10);
+            now
+        }
+    }
+
+    fn produce_two_blocks() -> Vec<Block> {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        for i in 1..=3 {
+            mempool.add_transaction(dummy_tx(i, i as f64 * 10.0));
+        }
+        let mut producer = BlockProducer::new(&mut chain_manager)
+            .with_clock(Box::new(StepClock(std::cell::Cell::new(1_700_000_000))));
+        (0..2).map(|_| producer.produce_and_append(&mut mempool).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_produce_block_respects_max_block_bytes() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        for i in 1..=5 {
+            mempool.add_transaction(dummy_tx(i, 10.0));
+        }
+        let tx_size = dummy_tx(1, 10.0).encoded_size();
+        let empty = Block {
+            block_number: 1,
+            previous_hash: [0u8; 32],
+            transactions: Vec::new(),
+            tx_count: 0,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            signature: Vec::new(),
+        };
+        // Room for two transactions plus a signature, but not three.
+        chain_manager.set_max_block_bytes(empty.encoded_size() + SIGNATURE_LENGTH + 2 * tx_size + 1);
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 5)
+            .with_clock(Box::new(StepClock(std::cell::Cell::new(1_700_000_000))));
+
+        let mut block = producer.produce_block(&mut mempool).unwrap();
+        assert_eq!(block.transactions.len(), 2);
+        assert!(block.verify_tx_count());
+        assert_eq!(mempool.size(), 3);
+        block.sign(&SigningKey::from_bytes(&[7u8; 32]));
+        assert!(block.encoded_size() <= producer.chain_manager.max_block_bytes());
+    }
+
+    #[test]
+    fn test_preview_matches_trimmed_block() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        for i in 1..=5 {
+            mempool.add_transaction(dummy_tx(i, i as f64));
+        }
+        let tx_size = dummy_tx(1, 1.0).encoded_size();
+        let genesis_size = <ChainManager>::genesis().encoded_size();
+        chain_manager.set_max_block_bytes(genesis_size + SIGNATURE_LENGTH + 2 * tx_size + 8);
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 5)
+            .with_clock(Box::new(StepClock(std::cell::Cell::new(1_700_000_000))));
+
+        let preview = producer.preview_block(&mempool);
+        let block = producer.produce_block(&mut mempool).unwrap();
+        assert_eq!(block.transactions.len(), 2);
+        assert_eq!(preview.transaction_count, block.transactions.len());
+        assert_eq!(preview.total_bytes, block.transactions.iter().map(|tx| tx.encoded_size()).sum::<usize>());
+        assert_eq!(preview.total_fees, 9.0);
+    }
+
+    #[test]
+    fn test_mock_clock_gives_stable_hashes() {
+        let first = produce_two_blocks();
+        assert_eq!(first[0].timestamp, 1_700_000_000);
+        assert_eq!(first[1].timestamp, 1_700_000_010);
+        assert_eq!(first[1].previous_hash, first[0].hash());
+        let hashes = |blocks: &[Block]| blocks.iter().map(Block::hash).collect::<Vec<_>>();
+        assert_eq!(hashes(&produce_two_blocks()), hashes(&first));
+    }
+
+    #[test]
+    fn test_empty_mempool_is_an_error_by_default() {
+        let mut chain_manager = ChainManager::new();
+        let genesis_hash = chain_manager.head().hash();
+        let mut mempool = Mempool::new();
+        let mut producer = BlockProducer::new(&mut chain_manager);
+        assert_eq!(producer.produce_block(&mut mempool), Err(ProduceError::EmptyMempool));
+        assert!(producer.last_stats().is_none());
+
+        let mut skipped = Vec::new();
+        run_production_loop(&mut producer, &mut mempool, Some(2), Duration::ZERO, |b| skipped.push(b.clone()));
+        assert!(skipped.is_empty());
+        assert_eq!(chain_manager.height(), 0);
+        assert_eq!(chain_manager.head().hash(), genesis_hash);
+    }
+
+    #[test]
+    fn test_empty_blocks_when_allowed() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        let mut producer = BlockProducer::new(&mut chain_manager).with_empty_blocks(true);
+        let block = producer.produce_block(&mut mempool).unwrap();
+        assert!(block.is_empty());
+        assert_eq!(block.tx_count, 0);
+        assert!(block.verify_tx_count());
+        assert_eq!(producer.chain_manager.height(), 0);
+    }
+
+    #[test]
+    fn test_production_stats() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        for i in 1..=3 {
+            mempool.add_transaction(dummy_tx(i, i as f64 * 10.0));
+        }
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 2);
+        let block = producer.produce_block(&mut mempool).unwrap();
+        let stats = producer.last_stats().expect("stats recorded");
+        assert_eq!(stats.tx_count, block.transactions.len());
+        assert_eq!(stats.tx_count, 2);
+        assert_eq!(stats.total_fees, 50.0);
+    }
+
+    #[test]
+    fn test_canonical_order_ignores_insertion_order() {
+        let txs = vec![dummy_tx(4, 10.0), dummy_tx(1, 5.0), dummy_tx(3, 10.0), dummy_tx(2, 20.0)];
+        let mut orderings = Vec::new();
+        for rotation in 0..txs.len() {
+            let mut chain_manager = ChainManager::new();
+            let mut mempool = Mempool::new();
+            let mut rotated = txs.clone();
+            rotated.rotate_left(rotation);
+            rotated.into_iter().for_each(|tx| assert!(mempool.add_transaction(tx)));
+            let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, txs.len());
+            let block = producer.produce_block(&mut mempool).unwrap();
+            orderings.push(block.transactions.iter().map(|tx| tx.id).collect::<Vec<_>>());
+        }
+        // Fee descending; the two 10.0-fee transactions tie-break on ascending id.
+        for ordering in orderings {
+            assert_eq!(ordering, vec![2, 3, 4, 1]);
+        }
+    }
+
+    fn sender_tx(id: u64, sender: &str, nonce: u64, fee: f64) -> Transaction {
+        Transaction { sender: sender.to_string(), nonce, ..dummy_tx(id, fee) }
+    }
+
+    #[test]
+    fn test_sender_nonces_ascend_despite_fees() {
+        // Alice's nonce-6 transaction pays more than her nonce-5 one.
+        let txs = vec![sender_tx(1, "Alice", 6, 50.0), sender_tx(2, "Alice", 5, 1.0), sender_tx(3, "Carol", 0, 20.0)];
+        for rotation in 0..txs.len() {
+            let mut chain_manager = ChainManager::new();
+            let mut mempool = Mempool::new();
+            let mut rotated = txs.clone();
+            rotated.rotate_left(rotation);
+            rotated.into_iter().for_each(|tx| assert!(mempool.add_transaction(tx)));
+            let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, txs.len());
+            let block = producer.produce_block(&mut mempool).unwrap();
+            let nonces: Vec<(&str, u64)> = block.transactions.iter().map(|tx| (tx.sender.as_str(), tx.nonce)).collect();
+            // Carol's fee beats Alice's nonce-5 transaction, which must precede nonce 6.
+            assert_eq!(nonces, vec![("Carol", 0), ("Alice", 5), ("Alice", 6)]);
+        }
+    }
+
+    #[test]
+    fn test_nonce_ordering_keeps_fee_priority_across_senders() {
+        let mut txs = vec![
+            sender_tx(1, "Alice", 1, 10.0),
+            sender_tx(2, "Bob", 1, 8.0),
+            sender_tx(3, "Alice", 2, 6.0),
+            sender_tx(4, "Bob", 2, 4.0),
+        ];
+        canonical_order(&mut txs);
+        order_by_sender_nonce(&mut txs);
+        // Already nonce-consistent, so the fee order is untouched.
+        assert_eq!(txs.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_block_serialization_round_trip() {
+        let block = Block {
+            block_number: 7,
+            previous_hash: [3u8; 32],
+            transactions: vec![dummy_tx(1, 5.0), dummy_tx(2, 6.0)],
+            tx_count: 2,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            signature: vec![9, 9, 9],
+        };
+        let ser = Serializer::serialize(&block, Endianness::Little).unwrap();
+        let de: Block = Serializer::deserialize(&ser, Endianness::Little).unwrap();
+        assert_eq!(de, block);
+        assert_eq!(de.hash(), block.hash());
+        assert!(!de.is_empty());
+    }
+
+    #[test]
+    fn test_empty_block_round_trip() {
+        let block = Block {
+            block_number: 7,
+            previous_hash: [3u8; 32],
+            transactions: Vec::new(),
+            tx_count: 0,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            signature: vec![9, 9, 9],
+        };
+        assert!(block.is_empty());
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let ser = Serializer::serialize(&block, endianness).unwrap();
+            let de: Block = Serializer::deserialize(&ser, endianness).unwrap();
+            assert_eq!(de, block);
+            assert!(de.is_empty() && de.verify_tx_count());
+        }
+    }
+
+    #[test]
+    fn test_truncated_block_never_panics() {
+        let block = Block {
+            block_number: 7,
+            previous_hash: [3u8; 32],
+            transactions: vec![dummy_tx(1, 5.0), dummy_tx(2, 6.0)],
+            tx_count: 2,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            signature: vec![9, 9, 9],
+        };
+        let mut encoded = vec![0u8; block.encoded_size()];
+        block.encode_to(&mut encoded, Endianness::Little).unwrap();
+        for len in 0..encoded.len() {
+            assert!(Block::decode_from(&encoded[..len], Endianness::Little).is_err());
+        }
+        // A forged transaction count must not trigger a huge allocation.
+        let mut forged = encoded.clone();
+        forged[1 + 32] = 0xff;
+        assert!(Block::decode_from(&forged, Endianness::Little).is_err());
+    }
+
+    #[test]
+    fn test_validate_transactions_parallel_reports_every_failure() {
+        let mut self_transfer = dummy_tx(3, 5.0);
+        self_transfer.recipient = self_transfer.sender.clone();
+        let mut block = Block {
+            block_number: 1,
+            previous_hash: [0u8; 32],
+            transactions: vec![dummy_tx(1, 5.0), dummy_tx(2, f64::NAN), self_transfer, dummy_tx(4, 5.0)],
+            tx_count: 4,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            signature: Vec::new(),
+        };
+        assert_eq!(
+            block.validate_transactions_parallel(&SpamPolicy::default()),
+            Err(vec![(1, RejectReason::NonFiniteFee), (2, RejectReason::Spam)])
+        );
+        let lenient = SpamPolicy { allow_self_transfer: true, ..SpamPolicy::default() };
+        assert_eq!(block.validate_transactions_parallel(&lenient), Err(vec![(1, RejectReason::NonFiniteFee)]));
+
+        block.transactions.remove(1);
+        assert_eq!(block.validate_transactions_parallel(&lenient), Ok(()));
+    }
+
+    #[test]
+    fn test_signing_leaves_hash_unchanged() {
+        let mut block = Block {
+            block_number: 3,
+            previous_hash: [9u8; 32],
+            transactions: vec![dummy_tx(1, 10.0)],
+            tx_count: 1,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            signature: Vec::new(),
+        };
+        let unsigned_hash = block.hash();
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        block.sign(&key);
+        assert_eq!(block.signature.len(), 64);
+        assert_eq!(block.hash(), unsigned_hash);
+        assert!(block.verify_signature(&key.verifying_key()));
+
+        let other_key = SigningKey::from_bytes(&[8u8; 32]);
+        let resigned = Block { signature: other_key.sign(&block.signing_bytes()).to_vec(), ..block.clone() };
+        assert_ne!(resigned.signature, block.signature);
+        assert_eq!(resigned.hash(), block.hash());
+        assert!(!resigned.verify_signature(&key.verifying_key()));
+    }
+
+    #[test]
+    fn test_tx_count_commitment() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut block = Block {
+            block_number: 3,
+            previous_hash: [9u8; 32],
+            transactions: vec![dummy_tx(1, 10.0), dummy_tx(2, 10.0)],
+            tx_count: 2,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            signature: Vec::new(),
+        };
+        block.sign(&key);
+        assert!(block.verify_tx_count());
+
+        let tampered = Block { tx_count: 3, ..block.clone() };
+        assert!(!tampered.verify_tx_count());
+        // The count is committed to, so changing it also breaks the hash and signature.
+        assert_ne!(tampered.hash(), block.hash());
+        assert!(!tampered.verify_signature(&key.verifying_key()));
+        let ser = Serializer::serialize(&tampered, Endianness::Little).unwrap();
+        let decoded: Block = Serializer::deserialize(&ser, Endianness::Little).unwrap();
+        assert!(!decoded.verify_tx_count());
+    }
+
+    #[test]
+    fn test_sha256_chain_links_and_commits_under_sha256() {
+        use crate::utils::hasher::Sha256Hasher;
+
+        let mut chain_manager = ChainManager::<Sha256Hasher>::with_hasher();
+        chain_manager.add_validator("Validator_A".to_string(), 100).unwrap();
+        let mut mempool = Mempool::new();
+        for i in 1..=3 {
+            mempool.add_transaction(dummy_tx(i, 10.0));
+        }
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 3)
+            .with_clock(Box::new(StepClock(std::cell::Cell::new(1_700_000_000))));
+
+        let block = producer.produce_block(&mut mempool).unwrap();
+        let genesis = producer.chain_manager.head().clone();
+        assert_eq!(block.previous_hash, genesis.hash_with::<Sha256Hasher>());
+        assert_ne!(block.previous_hash, genesis.hash());
+        assert_eq!(block.merkle_root, block.compute_merkle_root_with::<Sha256Hasher>());
+        assert_eq!(producer.chain_manager.validate_block(&block), Ok(()));
+        assert!(producer.chain_manager.verify_work("Validator_A"));
+
+        // A Blake3 commitment or link does not validate on a SHA-256 chain.
+        let mut blake3_committed = block.clone();
+        blake3_committed.commit_transactions();
+        assert_eq!(
+            producer.chain_manager.validate_block(&blake3_committed),
+            Err(BlockValidationError::MerkleRootMismatch)
+        );
+        let blake3_linked = Block { previous_hash: genesis.hash(), ..block.clone() };
+        assert_eq!(
+            producer.chain_manager.validate_block(&blake3_linked),
+            Err(BlockValidationError::PreviousHashMismatch)
+        );
+
+        producer.chain_manager.append_block(block.clone()).unwrap();
+        mempool.add_transaction(dummy_tx(4, 10.0));
+        let child = producer.produce_and_append(&mut mempool).unwrap();
+        assert_eq!(child.previous_hash, block.hash_with::<Sha256Hasher>());
+    }
+
+    #[test]
+    fn test_scheduler_applies_backpressure_to_bursts() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        let mut scheduler = ProductionScheduler::new(Duration::ZERO).with_max_blocks_per_cycle(2).with_max_pending(20);
+
+        let results: Vec<_> = (1..=50).map(|i| scheduler.submit(&mut mempool, dummy_tx(i, 10.0))).collect();
+        assert!(results[..20].iter().all(|r| r.is_ok()));
+        assert_eq!(results[20], Err(RejectReason::Backpressure { pending: 20 }));
+        assert_eq!(mempool.size(), 20);
+        assert_eq!(scheduler.metrics().transactions_deferred, 30);
+
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 2);
+        let mut blocks = 0;
+        scheduler.run(&mut producer, &mut mempool, Some(3), |_| blocks += 1);
+        // Three cycles of at most two blocks each, two transactions per block.
+        assert_eq!(blocks, 6);
+        assert_eq!(mempool.size(), 8);
+        assert!(!scheduler.is_backlogged(&mempool));
+        let metrics = scheduler.metrics();
+        assert_eq!((metrics.cycles_run, metrics.blocks_produced), (3, 6));
+    }
+
+    #[test]
+    fn test_scheduler_stops_on_shutdown_flag() {
+        let mut chain_manager = ChainManager::new();
+        let mut mempool = Mempool::new();
+        let mut producer = BlockProducer::new(&mut chain_manager);
+        producer.allow_empty_blocks = true;
+        let mut scheduler = ProductionScheduler::new(Duration::from_secs(3600));
+
+        let shutdown = AtomicBool::new(true);
+        scheduler.run_until(&mut producer, &mut mempool, None, &shutdown, |_| panic!("produced after shutdown"));
+        assert_eq!(scheduler.metrics().cycles_run, 0);
+
+        // Set mid-run, the flag cuts the hour-long wait short after the current cycle.
+        let shutdown = AtomicBool::new(false);
+        let started = Instant::now();
+        scheduler.run_until(&mut producer, &mut mempool, None, &shutdown, |_| shutdown.store(true, Ordering::SeqCst));
+        assert_eq!(scheduler.metrics().cycles_run, 1);
+        assert!(started.elapsed() < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_overrun_cycles_are_coalesced() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(100);
+        assert_eq!(next_cycle_start(start, start + Duration::from_millis(40), interval), (0, start + interval));
+        assert_eq!(
+            next_cycle_start(start, start + Duration::from_millis(350), interval),
+            (3, start + Duration::from_millis(400))
+        );
+    }
+
+    #[test]
+    fn test_production_loop_increments_block_numbers() {
+        let mut chain_manager = ChainManager::new();
+        chain_manager.add_validator("Validator_A".to_string(), 100).unwrap();
+
+        let mut mempool = Mempool::new();
+        for i in 1..=5 {
+            mempool.add_transaction(dummy_tx(i, i as f64 * 10.0));
+        }
+
+        let mut producer = BlockProducer::with_max_transactions(&mut chain_manager, 3);
+        producer.allow_empty_blocks = true;
+        let mut blocks = Vec::new();
+        run_production_loop(&mut producer, &mut mempool, Some(3), Duration::ZERO, |block| {
+            blocks.push(block.clone());
+        });
+
+        let numbers: Vec<u64> = blocks.iter().map(|b| b.block_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        // The configured cap applies to every block: 3 + 2 + 0 transactions.
+        let counts: Vec<usize> = blocks.iter().map(|b| b.transactions.len()).collect();
+        assert_eq!(counts, vec![3, 2, 0]);
+        assert_eq!(producer.chain_manager.height(), 3);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+//! Difficulty adjustment for PoCUP puzzles.
+//!
+//! `DifficultyController` keeps a rolling window of recent block timestamps
+//! and nudges the puzzle difficulty so the average block interval tracks a
+//! target. Difficulty counts leading zero bits, so each step of one doubles
+//! or halves the expected work.
+
+use std::collections::VecDeque;
+
+/// Highest meaningful difficulty: every bit of a 256-bit hash is zero.
+pub const MAX_DIFFICULTY: u32 = 256;
+
+/// Tracks recent block timestamps and computes the next difficulty.
+pub struct DifficultyController {
+    /// Number of timestamps kept; intervals are measured across this window.
+    window: usize,
+    /// Largest change in difficulty allowed per adjustment.
+    max_step: u32,
+    timestamps: VecDeque<u64>,
+}
+
+impl DifficultyController {
+    /// Creates a controller averaging over the last `window` blocks and moving
+    /// difficulty by at most `max_step` bits per adjustment.
+    pub fn new(window: usize, max_step: u32) -> Self {
+        assert!(window >= 2, "need at least two timestamps to measure an interval");
+        Self { window, max_step, timestamps: VecDeque::with_capacity(window) }
+    }
+
+    /// Records a block timestamp (seconds), evicting the oldest beyond the window.
+    pub fn record(&mut self, timestamp: u64) {
+        if self.timestamps.len() == self.window {
+            self.timestamps.pop_front();
+        }
+        self.timestamps.push_back(timestamp);
+    }
+
+    /// Returns the mean interval between recorded blocks, or `None` with fewer than two.
+    pub fn average_interval(&self) -> Option<f64> {
+        let (first, last) = (self.timestamps.front()?, self.timestamps.back()?);
+        let intervals = self.timestamps.len() - 1;
+        if intervals == 0 {
+            return None;
+        }
+        Some(last.saturating_sub(*first) as f64 / intervals as f64)
+    }
+
+    /// Returns the difficulty to use next. Blocks arriving faster than
+    /// `target_interval_secs` raise it, slower ones lower it, by the base-2
+    /// log of the ratio rounded to whole bits and clamped to `max_step`.
+    /// Without enough history, `current` is returned unchanged.
+    pub fn next_difficulty(&self, current: u32, target_interval_secs: u64) -> u32 {
+        let Some(average) = self.average_interval() else { return current };
+        // Treat sub-second averages as one second to keep the ratio finite.
+        let ratio = target_interval_secs as f64 / average.max(1.0);
+        let step = (ratio.log2().round() as i64).clamp(-i64::from(self.max_step), i64::from(self.max_step));
+        (i64::from(current) + step).clamp(0, i64::from(MAX_DIFFICULTY)) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller_with(interval: u64) -> DifficultyController {
+        let mut controller = DifficultyController::new(10, 2);
+        for i in 0..10 {
+            controller.record(1_000 + i * interval);
+        }
+        controller
+    }
+
+    #[test]
+    fn test_fast_blocks_raise_difficulty() {
+        assert_eq!(controller_with(5).next_difficulty(10, 10), 11);
+    }
+
+    #[test]
+    fn test_slow_blocks_lower_difficulty() {
+        assert_eq!(controller_with(20).next_difficulty(10, 10), 9);
+        assert_eq!(controller_with(10).next_difficulty(10, 10), 10);
+    }
+
+    #[test]
+    fn test_step_is_bounded() {
+        // 1s blocks against a 60s target would suggest ~6 bits; capped at 2.
+        assert_eq!(controller_with(1).next_difficulty(10, 60), 12);
+        assert_eq!(controller_with(600).next_difficulty(1, 10), 0);
+        assert_eq!(DifficultyController::new(4, 2).next_difficulty(7, 10), 7);
+    }
+}
@@ -1 +1,2 @@
-pub mod block_producer;
\ No newline at end of file
+pub mod block_producer;
+pub mod difficulty;
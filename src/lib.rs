@@ -4,4 +4,5 @@ pub mod node;
 pub mod roc;
 pub mod rsl;
 pub mod consensus;
-pub mod networking;
\ No newline at end of file
+pub mod networking;
+pub mod state;
\ No newline at end of file
@@ -3,64 +3,90 @@
 //!
 //! This binary demonstrates a single–node flow by integrating a ChainManager 
 //! (holding validators), a Mempool (storing unconfirmed transactions), PoCUP tasks,
-//! and a basic RSL contract parser. It now includes a continuous block production loop,
-//! simulating ongoing block creation. No HTTP server is included.
+//! and a basic RSL contract parser. It now drives a BlockProducer in a continuous
+//! production loop, simulating ongoing block creation. No HTTP server is included.
 
-use reina::node::chain_manager::ChainManager;
-use reina::node::mempool::Mempool;
-use reina::consensus::block_producer::Block; // Minimal Block struct
-use reina::pocup::pocup::{stake, perform_useful_work, slash_if_needed};
+use log::{error, info, warn};
+use reina::node::chain_manager::GenesisConfig;
+use reina::node::mempool::{Mempool, DEFAULT_MIN_FEE};
+use reina::consensus::block_producer::{BlockProducer, ProductionScheduler};
+use reina::pocup::pocup::{perform_useful_work, slash_if_needed};
 use reina::rsl::parse_rsl;
 use reina::utils::serialization::Transaction;
-use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Where pending transactions are kept across restarts.
+const MEMPOOL_PATH: &str = "reina-mempool.bin";
 
 fn main() {
-    println!("Starting Reina Phase 1 node demo...");
+    // Default to `info` so the demo output stays visible; override with RUST_LOG.
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    info!("Starting Reina Phase 1 node demo...");
 
-    // Create a ChainManager and add validators.
-    let mut chain_manager = ChainManager::new();
-    chain_manager.add_validator("Validator_A".to_string(), 100);
-    println!("Added Validator_A with stake 100.");
-    chain_manager.add_validator("Validator_B".to_string(), 200);
-    println!("Added Validator_B with stake 200.");
-    chain_manager.add_validator("Validator_C".to_string(), 150);
-    println!("Added Validator_C with stake 150.");
+    // Build the ChainManager from the genesis spec shared by all nodes.
+    let genesis = GenesisConfig {
+        validators: vec![
+            ("Validator_A".to_string(), 100),
+            ("Validator_B".to_string(), 200),
+            ("Validator_C".to_string(), 150),
+        ],
+        min_fee: DEFAULT_MIN_FEE,
+        block_interval_secs: 5,
+    };
+    let mut chain_manager = genesis.chain_manager();
+    info!("Loaded {} genesis validators.", chain_manager.validators.len());
 
     // Run PoCUP tasks on validators.
-    println!("Running PoCUP tasks on validators...");
+    info!("Running PoCUP tasks on validators...");
+    let tip = chain_manager.head().hash();
     for validator in &mut chain_manager.validators {
-        perform_useful_work(validator);
+        perform_useful_work(validator, &tip);
         slash_if_needed(validator);
-        println!("Validator {}: stake = {}, puzzle_passed = {}",
+        info!("Validator {}: stake = {}, puzzle_passed = {}",
             validator.id, validator.stake_amount, validator.puzzle_passed);
     }
 
-    // Create a Mempool and add some dummy transactions.
-    let mut mempool = Mempool::new();
+    // Restore transactions left pending by the last run, then add some dummy ones.
+    let mut mempool = match Mempool::load(MEMPOOL_PATH, genesis.min_fee) {
+        Ok(mempool) => {
+            info!("Restored {} pending transactions from {}.", mempool.size(), MEMPOOL_PATH);
+            mempool
+        }
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Ignoring saved mempool {}: {}", MEMPOOL_PATH, e);
+            }
+            Mempool::with_min_fee(genesis.min_fee)
+        }
+    };
     for i in 1..=5 {
         let tx = Transaction {
             id: i,
             amount: 1000,
             fee: (i * 10) as f64,
             version: 1,
+            nonce: 0,
             sender: "Alice".to_string(),
             recipient: "Bob".to_string(),
-            signature: vec![1, 2, 3, 4],
+            signature: vec![1, 2, 3, 4].into(),
         };
         if mempool.add_transaction(tx) {
-            println!("Inserted transaction {} into mempool.", i);
+            info!("Inserted transaction {} into mempool.", i);
         } else {
-            println!("Failed to insert transaction {}.", i);
+            warn!("Failed to insert transaction {}.", i);
         }
     }
-    println!("Mempool size: {}", mempool.size());
+    info!("Mempool size: {}", mempool.size());
 
     // Run one PoCUP round on mempool transactions (for demo, remove one transaction).
     if let Some(tx) = mempool.remove_transaction() {
-        println!("Removed transaction {} from mempool.", tx.id);
+        info!("Removed transaction {} from mempool.", tx.id);
     }
-    println!("Mempool size after removal: {}", mempool.size());
+    info!("Mempool size after removal: {}", mempool.size());
 
     // Optionally, parse a small RSL contract.
     let rsl_source = r#"
@@ -72,42 +98,39 @@ fn main() {
         }
     "#;
     match parse_rsl(rsl_source) {
-        Ok(ast) => println!("Parsed RSL contract: {:?}", ast),
-        Err(e) => println!("RSL parsing error: {:?}", e),
+        Ok(ast) => info!("Parsed RSL contract: {:?}", ast),
+        Err(e) => error!("RSL parsing error: {:?}", e),
+    }
+
+    // Ctrl-C only raises a flag; the loop notices it between cycles, so a block
+    // is never abandoned half-built.
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&shutdown);
+    if let Err(e) = ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst)) {
+        error!("Failed to install Ctrl-C handler: {}", e);
+        process::exit(1);
     }
 
     // Continuous Block Production Loop:
-    // In Phase 1, we simulate block production by creating a new block in each loop iteration.
-    println!("Entering continuous block production loop...");
-    let mut block_number = 1u64;
-    loop {
-        println!("Producing block #{}...", block_number);
-        // Pull up to 3 transactions from the mempool (FIFO).
-        let mut txs = Vec::new();
-        for _ in 0..3 {
-            if let Some(tx) = mempool.remove_transaction() {
-                txs.push(tx);
-            }
-        }
-        // Get current timestamp.
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("System time error")
-            .as_secs();
-        // Construct a new block with default previous hash.
-        let block = Block {
-            block_number,
-            previous_hash: [0u8; 32],
-            transactions: txs,
-            timestamp,
-            signature: Vec::new(), // Placeholder signature.
-        };
-        println!(
+    // In Phase 1, a ProductionScheduler runs the BlockProducer every genesis block
+    // interval, catching up in bounded bursts when transactions back up.
+    info!("Entering continuous block production loop (Ctrl-C to stop)...");
+    let mut producer = BlockProducer::new(&mut chain_manager);
+    let mut scheduler = ProductionScheduler::new(Duration::from_secs(genesis.block_interval_secs));
+    scheduler.run_until(&mut producer, &mut mempool, None, &shutdown, |block| {
+        info!(
             "Produced block #{} with {} transactions at timestamp {}.",
             block.block_number, block.transactions.len(), block.timestamp
         );
-        block_number += 1;
-        // Sleep for 5 seconds before producing the next block.
-        thread::sleep(Duration::from_secs(5));
+    });
+
+    // Flush pending transactions so the next start picks them up.
+    let metrics = scheduler.metrics();
+    info!(
+        "Shutting down after {} cycles and {} blocks; {} transactions left pending.",
+        metrics.cycles_run, metrics.blocks_produced, mempool.size()
+    );
+    if let Err(e) = mempool.save(MEMPOOL_PATH) {
+        error!("Failed to save mempool to {}: {}", MEMPOOL_PATH, e);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,355 @@
+// File: src/networking/async_network.rs
+//! Tokio-based networking for Reina.
+//!
+//! `AsyncNetworkNode` speaks the same length-framed `NetMessage` protocol as
+//! `NetworkNode`, including the `Hello` handshake, keep-alive pings and I/O
+//! timeouts, but serves each connection as a task on a Tokio runtime instead
+//! of a dedicated OS thread. Available with the `async` feature.
+
+use log::{error, info, warn};
+use std::io;
+use std::net::SocketAddr;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{timeout, Instant};
+
+use crate::networking::error::{NetError, NetResult};
+use crate::networking::message::{
+    decode_frame, encode_frame, frame_payload_len, FrameDecoder, NetMessage, FRAME_HEADER_LEN,
+};
+use crate::networking::network::{NodeServices, PeerSession, READ_CHUNK_SIZE};
+use crate::node::chain_manager::ChainManager;
+use crate::node::mempool::ConcurrentMempool;
+
+/// A network node that accepts TCP connections on a Tokio runtime.
+pub struct AsyncNetworkNode {
+    /// The TCP listener bound to a port.
+    listener: TcpListener,
+    /// State shared with every connection task.
+    services: NodeServices,
+}
+
+impl AsyncNetworkNode {
+    /// Creates a new AsyncNetworkNode listening on `addr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Any socket address string, e.g. "127.0.0.1:8000" or "[::1]:8000".
+    pub async fn bind(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let services = NodeServices { node_id: listener.local_addr()?.to_string(), ..NodeServices::default() };
+        Ok(Self { listener, services })
+    }
+
+    /// Returns the address the listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Sets the largest frame length accepted from peers.
+    pub fn set_max_message_size(&mut self, max_message_size: usize) {
+        self.services.max_message_size = max_message_size;
+    }
+
+    /// Sets the identifier announced to peers in `Hello`.
+    pub fn set_node_id(&mut self, node_id: &str) {
+        self.services.node_id = node_id.to_string();
+    }
+
+    /// Sets the protocol version announced in `Hello` and the range of peer
+    /// versions accepted.
+    pub fn set_protocol_version(&mut self, version: u32, supported: RangeInclusive<u32>) {
+        self.services.protocol_version = version;
+        self.services.supported_versions = supported;
+    }
+
+    /// Sets the keep-alive policy for incoming connections, as in
+    /// `NetworkNode::set_keepalive`.
+    pub fn set_keepalive(&mut self, interval: Duration, timeout: Duration) {
+        self.services.ping_interval = interval;
+        self.services.pong_timeout = timeout;
+    }
+
+    /// Sets the socket timeouts for incoming connections, as in
+    /// `NetworkNode::set_io_timeouts`.
+    pub fn set_io_timeouts(&mut self, read: Duration, write: Duration) {
+        self.services.read_timeout = read;
+        self.services.write_timeout = write;
+    }
+
+    /// Sets the connection flood limits, as in `NetworkNode::set_connection_limits`.
+    pub fn set_connection_limits(&mut self, max_connections: usize, max_per_ip: usize, window: Duration) {
+        self.services.max_connections = max_connections;
+        self.services.max_connections_per_ip = max_per_ip;
+        self.services.connection_rate_window = window;
+    }
+
+    /// Serves blocks from `chain` to peers that send `GetBlocks`.
+    pub fn serve_chain(&mut self, chain: Arc<Mutex<ChainManager>>) {
+        self.services.chain = Some(chain);
+    }
+
+    /// Adds gossiped transactions that are accepted into `mempool`.
+    pub fn serve_mempool(&mut self, mempool: Arc<ConcurrentMempool>) {
+        self.services.mempool = Some(mempool);
+    }
+
+    /// Registers a peer address that gossip is broadcast to.
+    pub fn add_peer(&self, peer_addr: &str) {
+        let mut peers = self.services.peers.lock().unwrap();
+        if !peers.iter().any(|p| p == peer_addr) {
+            peers.push(peer_addr.to_string());
+        }
+    }
+
+    /// Runs the accept loop, spawning a task for each incoming connection.
+    ///
+    /// Never returns under normal operation; accept errors are logged.
+    pub async fn run(self) {
+        info!("AsyncNetworkNode listening on {}", self.listener.local_addr().unwrap());
+        let services = Arc::new(self.services);
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, peer_addr)) => {
+                    let slot = match services.admit(peer_addr.ip()) {
+                        Ok(slot) => slot,
+                        Err(reason) => {
+                            warn!("Refusing connection from {}: {}", peer_addr, reason);
+                            continue;
+                        }
+                    };
+                    let services = Arc::clone(&services);
+                    tokio::spawn(async move {
+                        let _slot = slot;
+                        match handle_connection(stream, peer_addr, Arc::clone(&services)).await {
+                            // Only `send` times out; a stalled reader is not a protocol violation.
+                            Err(NetError::Timeout) => warn!(
+                                "Closing connection from {}: write blocked for {:?}",
+                                peer_addr, services.write_timeout
+                            ),
+                            Err(e) => {
+                                services.penalize_error(peer_addr.ip(), &e);
+                                error!("Error handling connection from {}: {}", peer_addr, e);
+                            }
+                            Ok(()) => {}
+                        }
+                    });
+                }
+                Err(e) => error!("Connection failed: {}", e),
+            }
+        }
+    }
+
+    /// Sends `message` to `peer` and waits for a single framed response.
+    ///
+    /// # Returns
+    ///
+    /// The decoded response; `NetError::Timeout` if the exchange does not
+    /// complete within `timeout`, or `NetError::ProtocolMismatch` if the
+    /// peer's `Hello` is incompatible.
+    pub async fn request(&self, peer: &str, message: &NetMessage, timeout_after: Duration) -> NetResult<NetMessage> {
+        let exchange = async {
+            let mut stream = TcpStream::connect(peer).await?;
+            write_frame(&mut stream, &self.services.hello()).await?;
+            write_frame(&mut stream, message).await?;
+            let hello = read_frame(&mut stream, self.services.max_message_size).await?;
+            self.services.negotiate(&hello)?;
+            read_frame(&mut stream, self.services.max_message_size).await
+        };
+        timeout(timeout_after, exchange).await.map_err(|_| NetError::Timeout)?
+    }
+}
+
+/// Writes `message` to `writer` as a single frame.
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, message: &NetMessage) -> NetResult<()> {
+    writer.write_all(&encode_frame(message)?).await?;
+    Ok(writer.flush().await?)
+}
+
+/// Reads one frame from `reader` and decodes it, rejecting frames whose
+/// length prefix exceeds `max_size` before reading the payload.
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R, max_size: usize) -> NetResult<NetMessage> {
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    reader.read_exact(&mut header).await?;
+    let len = frame_payload_len(header, max_size)?;
+    let mut frame = vec![0u8; FRAME_HEADER_LEN + len];
+    frame[..FRAME_HEADER_LEN].copy_from_slice(&header);
+    reader.read_exact(&mut frame[FRAME_HEADER_LEN..]).await?;
+    decode_frame(&frame)
+}
+
+/// Serves one connection with the same handshake, keep-alive, timeout, reply,
+/// and peer scoring rules as the synchronous handler.
+///
+/// Replies are computed on the blocking pool, since answering a message may
+/// lock the chain or relay gossip over blocking sockets.
+async fn handle_connection(mut stream: TcpStream, peer_addr: SocketAddr, services: Arc<NodeServices>) -> NetResult<()> {
+    let mut session: Option<PeerSession> = None;
+    let mut frames = FrameDecoder::new(services.max_message_size);
+    let mut chunk = [0u8; READ_CHUNK_SIZE];
+    let mut last_received = Instant::now();
+    let mut ping_sent: Option<Instant> = None;
+    loop {
+        // Before the handshake there is nothing to probe; only the read timeout applies.
+        let keepalive = match (ping_sent, &session) {
+            (Some(sent), _) => services.pong_timeout.saturating_sub(sent.elapsed()),
+            (None, Some(_)) => services.ping_interval,
+            (None, None) => Duration::MAX,
+        };
+        let idle = services.read_timeout.saturating_sub(last_received.elapsed());
+        let read = match timeout(keepalive.min(idle), stream.read(&mut chunk)).await {
+            Ok(Ok(0)) => break, // Connection closed.
+            Ok(Ok(read)) => read,
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) if last_received.elapsed() >= services.read_timeout => {
+                warn!("Closing connection from {}: nothing received for {:?}", peer_addr, services.read_timeout);
+                return Ok(());
+            }
+            Err(_) if ping_sent.is_some() => {
+                warn!("Closing connection from {}: no pong within {:?}", peer_addr, services.pong_timeout);
+                return Ok(());
+            }
+            Err(_) => {
+                send(&mut stream, &NetMessage::Ping, &services).await?;
+                ping_sent = Some(Instant::now());
+                continue;
+            }
+        };
+        last_received = Instant::now();
+        ping_sent = None;
+        for message in frames.feed(&chunk[..read])? {
+            let Some(current) = &session else {
+                let (reply, negotiated) = services.handshake(&message);
+                if let Some(reply) = reply {
+                    send(&mut stream, &reply, &services).await?;
+                }
+                match negotiated {
+                    Ok(established) => {
+                        info!(
+                            "Handshake with {} ({}) negotiated protocol version {}",
+                            established.node_id, peer_addr, established.protocol_version
+                        );
+                        session = Some(established);
+                    }
+                    Err(reason) => {
+                        warn!("Closing connection from {}: {}", peer_addr, reason);
+                        return Ok(());
+                    }
+                }
+                continue;
+            };
+            let responder = Arc::clone(&services);
+            let current = current.clone();
+            let reply = tokio::task::spawn_blocking(move || responder.respond(&message, peer_addr.ip(), &current))
+                .await
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            if let Some(reply) = reply {
+                send(&mut stream, &reply, &services).await?;
+            }
+            if services.is_banned(peer_addr.ip()) {
+                warn!("Closing connection from {}: peer is banned", peer_addr);
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes `message` as in `write_frame`, giving up with `NetError::Timeout`
+/// once the write has blocked for the services' write timeout.
+async fn send(stream: &mut TcpStream, message: &NetMessage, services: &NodeServices) -> NetResult<()> {
+    timeout(services.write_timeout, write_frame(stream, message)).await.map_err(|_| NetError::Timeout)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networking::network::NetworkNode;
+    use std::sync::atomic::Ordering;
+
+    async fn spawn_node() -> SocketAddr {
+        let node = AsyncNetworkNode::bind("127.0.0.1:0").await.expect("Failed to bind async node");
+        let addr = node.local_addr().unwrap();
+        tokio::spawn(node.run());
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_async_request_ping_pong() {
+        let addr = spawn_node().await;
+        let client = AsyncNetworkNode::bind("127.0.0.1:0").await.expect("Failed to bind client");
+        let reply = client
+            .request(&addr.to_string(), &NetMessage::Ping, Duration::from_secs(2))
+            .await
+            .expect("Request failed");
+        assert_eq!(reply, NetMessage::Pong);
+    }
+
+    #[tokio::test]
+    async fn test_sync_client_talks_to_async_node() {
+        let addr = spawn_node().await;
+        let reply = tokio::task::spawn_blocking(move || {
+            let client = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind client");
+            client.request(&addr.to_string(), &NetMessage::Ping, Duration::from_secs(2))
+        })
+        .await
+        .unwrap()
+        .expect("Request failed");
+        assert_eq!(reply, NetMessage::Pong);
+    }
+
+    #[tokio::test]
+    async fn test_async_request_reports_protocol_mismatch() {
+        let mut node = AsyncNetworkNode::bind("127.0.0.1:0").await.unwrap();
+        node.set_protocol_version(2, 2..=2);
+        let addr = node.local_addr().unwrap();
+        tokio::spawn(node.run());
+
+        let client = AsyncNetworkNode::bind("127.0.0.1:0").await.unwrap();
+        let result = client.request(&addr.to_string(), &NetMessage::Ping, Duration::from_secs(2)).await;
+        assert!(matches!(result, Err(NetError::ProtocolMismatch { theirs: 2, ours: 1 })), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_async_read_timeout_closes_silent_connection() {
+        let mut node = AsyncNetworkNode::bind("127.0.0.1:0").await.unwrap();
+        node.set_io_timeouts(Duration::from_millis(200), Duration::from_secs(1));
+        let addr = node.local_addr().unwrap();
+        tokio::spawn(node.run());
+
+        // Connect without ever sending a handshake.
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let started = Instant::now();
+        let mut buf = [0u8; 1];
+        let read = timeout(Duration::from_secs(5), stream.read(&mut buf)).await.expect("connection left open");
+        assert!(matches!(read, Ok(0) | Err(_)), "{:?}", read);
+        assert!(started.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_frees_its_slot() {
+        let mut node = AsyncNetworkNode::bind("127.0.0.1:0").await.unwrap();
+        node.set_io_timeouts(Duration::from_millis(200), Duration::from_secs(1));
+        node.set_connection_limits(1, 8, Duration::from_secs(60));
+        let active = Arc::clone(&node.services.active_connections);
+        let addr = node.local_addr().unwrap();
+        tokio::spawn(node.run());
+
+        let mut idle = TcpStream::connect(addr).await.unwrap();
+        let mut buf = [0u8; 1];
+        timeout(Duration::from_secs(5), idle.read(&mut buf)).await.expect("idle connection left open").ok();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while active.load(Ordering::SeqCst) > 0 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(active.load(Ordering::SeqCst), 0);
+
+        // The only slot is free again.
+        let client = AsyncNetworkNode::bind("127.0.0.1:0").await.unwrap();
+        let reply = client.request(&addr.to_string(), &NetMessage::Ping, Duration::from_secs(2)).await;
+        assert_eq!(reply.expect("request refused"), NetMessage::Pong);
+    }
+}
@@ -0,0 +1,73 @@
+//! Error type for the networking layer.
+//!
+//! Separates socket failures from malformed frames, incompatible peers, and
+//! timeouts so callers can react to each differently.
+
+use std::fmt;
+use std::io;
+
+use crate::node::chain_manager::BlockValidationError;
+use crate::utils::serialization::SerializationError;
+
+/// Result type for networking operations.
+pub type NetResult<T> = Result<T, NetError>;
+
+/// Reasons a network operation failed.
+#[derive(Debug)]
+pub enum NetError {
+    /// The underlying socket failed.
+    Io(io::Error),
+    /// A frame or message could not be decoded, or was not the message expected.
+    Framing(String),
+    /// The peer speaks a protocol version outside the supported range.
+    ProtocolMismatch { theirs: u32, ours: u32 },
+    /// A frame declared a length above the configured maximum.
+    MessageTooLarge { size: usize, max: usize },
+    /// The peer did not respond in time.
+    Timeout,
+    /// A block received from the peer failed validation.
+    InvalidBlock(BlockValidationError),
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(e) => write!(f, "I/O error: {}", e),
+            NetError::Framing(reason) => write!(f, "Framing error: {}", reason),
+            NetError::ProtocolMismatch { theirs, ours } => {
+                write!(f, "Protocol mismatch: peer speaks version {}, we speak {}", theirs, ours)
+            }
+            NetError::MessageTooLarge { size, max } => {
+                write!(f, "Frame length {} exceeds maximum message size {}", size, max)
+            }
+            NetError::Timeout => write!(f, "Timed out waiting for peer"),
+            NetError::InvalidBlock(e) => write!(f, "Invalid block: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetError::Io(e) => Some(e),
+            NetError::InvalidBlock(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    /// Socket read/write timeouts become `Timeout`; everything else is `Io`.
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => NetError::Timeout,
+            _ => NetError::Io(e),
+        }
+    }
+}
+
+impl From<SerializationError> for NetError {
+    fn from(e: SerializationError) -> Self {
+        NetError::Framing(e.to_string())
+    }
+}
@@ -0,0 +1,504 @@
+// File: src/networking/message.rs
+//! Typed Network Messages for Reina.
+//!
+//! `NetMessage` is the unit exchanged between peers. Each message is encoded as a
+//! one-byte discriminant followed by the variant's payload, using the crate's
+//! `Encode`/`Decode` traits, and travels inside a length-prefixed frame produced
+//! by `Serializer::serialize_with`. Frames carry a 4-byte CRC-32 rather than the
+//! 32-byte Blake3 digest used for data at rest.
+
+use std::io::{Read, Write};
+
+use crate::consensus::block_producer::Block;
+use crate::networking::error::{NetError, NetResult};
+use crate::pocup::pocup::Validator;
+use crate::utils::serialization::{
+    Checksum, Crc32Checksum, Decode, Encode, Endianness, FieldReader, FieldWriter, SerializationError, SerializationResult,
+    Serializer, Transaction,
+};
+
+/// Endianness used for all network frames.
+pub const WIRE_ENDIANNESS: Endianness = Endianness::Little;
+
+/// Checksum closing every network frame.
+pub type WireChecksum = Crc32Checksum;
+
+/// Default upper bound on a frame's declared length (16 MiB).
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Messages exchanged between Reina nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetMessage {
+    /// Liveness probe; the receiver answers with `Pong`.
+    Ping,
+    /// Reply to a `Ping`.
+    Pong,
+    /// Free-form text, used for demo traffic and diagnostics.
+    Text(String),
+    /// Requests up to `count` blocks starting at block number `from_height`.
+    GetBlocks { from_height: u64, count: u32 },
+    /// Reply to `GetBlocks`, in ascending block order.
+    Blocks(Vec<Block>),
+    /// Announces a transaction for inclusion in peers' mempools.
+    NewTransaction(Transaction),
+    /// First message on every connection, announcing the sender's protocol version.
+    Hello { protocol_version: u32, node_id: String },
+    /// Announces the validators the sender knows about.
+    ValidatorSet(Vec<Validator>),
+    /// Liveness proof from a validator, signed over the other fields
+    /// (see `pocup::liveness::heartbeat_signing_bytes`).
+    Heartbeat { validator_id: String, height: u64, timestamp: u64, signature: Vec<u8> },
+}
+
+impl NetMessage {
+    const TAG_PING: u8 = 0;
+    const TAG_PONG: u8 = 1;
+    const TAG_TEXT: u8 = 2;
+    const TAG_GET_BLOCKS: u8 = 3;
+    const TAG_BLOCKS: u8 = 4;
+    const TAG_NEW_TRANSACTION: u8 = 5;
+    const TAG_HELLO: u8 = 6;
+    const TAG_VALIDATOR_SET: u8 = 7;
+    const TAG_HEARTBEAT: u8 = 8;
+
+    fn tag(&self) -> u8 {
+        match self {
+            NetMessage::Ping => Self::TAG_PING,
+            NetMessage::Pong => Self::TAG_PONG,
+            NetMessage::Text(_) => Self::TAG_TEXT,
+            NetMessage::GetBlocks { .. } => Self::TAG_GET_BLOCKS,
+            NetMessage::Blocks(_) => Self::TAG_BLOCKS,
+            NetMessage::NewTransaction(_) => Self::TAG_NEW_TRANSACTION,
+            NetMessage::Hello { .. } => Self::TAG_HELLO,
+            NetMessage::ValidatorSet(_) => Self::TAG_VALIDATOR_SET,
+            NetMessage::Heartbeat { .. } => Self::TAG_HEARTBEAT,
+        }
+    }
+
+    /// Returns the length of the frame `encode_frame` produces for this
+    /// message: length prefix, encoded message, and checksum.
+    pub fn framed_size(&self) -> usize {
+        FRAME_HEADER_LEN + self.encoded_size() + WireChecksum::LEN
+    }
+}
+
+impl Encode for NetMessage {
+    fn encoded_size(&self) -> usize {
+        1 + match self {
+            NetMessage::Ping | NetMessage::Pong => 0,
+            NetMessage::Text(text) => text.encoded_size(),
+            NetMessage::GetBlocks { from_height, count } => from_height.encoded_size() + count.encoded_size(),
+            NetMessage::Blocks(blocks) => {
+                (blocks.len() as u64).encoded_size() + blocks.iter().map(|b| b.encoded_size()).sum::<usize>()
+            }
+            NetMessage::NewTransaction(tx) => tx.encoded_size(),
+            NetMessage::Hello { protocol_version, node_id } => {
+                protocol_version.encoded_size() + node_id.encoded_size()
+            }
+            NetMessage::ValidatorSet(validators) => {
+                (validators.len() as u64).encoded_size() + validators.iter().map(|v| v.encoded_size()).sum::<usize>()
+            }
+            NetMessage::Heartbeat { validator_id, height, timestamp, signature } => {
+                validator_id.encoded_size() + height.encoded_size() + timestamp.encoded_size() + signature.encoded_size()
+            }
+        }
+    }
+
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        if buffer.is_empty() {
+            return Err(SerializationError::BufferTooSmall);
+        }
+        buffer[0] = self.tag();
+        let mut offset = 1;
+        match self {
+            NetMessage::Ping | NetMessage::Pong => {}
+            NetMessage::Text(text) => offset += text.encode_to(&mut buffer[offset..], endianness)?,
+            NetMessage::GetBlocks { from_height, count } => {
+                offset += from_height.encode_to(&mut buffer[offset..], endianness)?;
+                offset += count.encode_to(&mut buffer[offset..], endianness)?;
+            }
+            NetMessage::Blocks(blocks) => {
+                offset += (blocks.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+                for block in blocks {
+                    offset += block.encode_to(&mut buffer[offset..], endianness)?;
+                }
+            }
+            NetMessage::NewTransaction(tx) => offset += tx.encode_to(&mut buffer[offset..], endianness)?,
+            NetMessage::Hello { protocol_version, node_id } => {
+                offset += protocol_version.encode_to(&mut buffer[offset..], endianness)?;
+                offset += node_id.encode_to(&mut buffer[offset..], endianness)?;
+            }
+            NetMessage::ValidatorSet(validators) => {
+                offset += (validators.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+                for validator in validators {
+                    offset += validator.encode_to(&mut buffer[offset..], endianness)?;
+                }
+            }
+            NetMessage::Heartbeat { validator_id, height, timestamp, signature } => {
+                offset += FieldWriter::new(&mut buffer[offset..], endianness)
+                    .field(validator_id)?
+                    .field(height)?
+                    .field(timestamp)?
+                    .field(signature)?
+                    .finish();
+            }
+        }
+        Ok(offset)
+    }
+}
+
+impl Decode for NetMessage {
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let tag = *buffer
+            .first()
+            .ok_or_else(|| SerializationError::InvalidData("Empty buffer for NetMessage".into()))?;
+        let payload = &buffer[1..];
+        let (message, consumed) = match tag {
+            Self::TAG_PING => (NetMessage::Ping, 0),
+            Self::TAG_PONG => (NetMessage::Pong, 0),
+            Self::TAG_TEXT => {
+                let (text, consumed) = String::decode_from(payload, endianness)?;
+                (NetMessage::Text(text), consumed)
+            }
+            Self::TAG_GET_BLOCKS => {
+                let (from_height, first) = u64::decode_from(payload, endianness)?;
+                let (count, second) = u32::decode_from(&payload[first..], endianness)?;
+                (NetMessage::GetBlocks { from_height, count }, first + second)
+            }
+            Self::TAG_BLOCKS => {
+                let (len, mut offset) = u64::decode_from(payload, endianness)?;
+                if len > payload.len() as u64 {
+                    return Err(SerializationError::InvalidData("Block count exceeds buffer".into()));
+                }
+                let mut blocks = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    let (block, consumed) = Block::decode_from(&payload[offset..], endianness)?;
+                    offset += consumed;
+                    blocks.push(block);
+                }
+                (NetMessage::Blocks(blocks), offset)
+            }
+            Self::TAG_NEW_TRANSACTION => {
+                let (tx, consumed) = Transaction::decode_from(payload, endianness)?;
+                (NetMessage::NewTransaction(tx), consumed)
+            }
+            Self::TAG_HELLO => {
+                let (protocol_version, first) = u32::decode_from(payload, endianness)?;
+                let (node_id, second) = String::decode_from(&payload[first..], endianness)?;
+                (NetMessage::Hello { protocol_version, node_id }, first + second)
+            }
+            Self::TAG_VALIDATOR_SET => {
+                let (len, mut offset) = u64::decode_from(payload, endianness)?;
+                if len > payload.len() as u64 {
+                    return Err(SerializationError::InvalidData("Validator count exceeds buffer".into()));
+                }
+                let mut validators = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    let (validator, consumed) = Validator::decode_from(&payload[offset..], endianness)?;
+                    offset += consumed;
+                    validators.push(validator);
+                }
+                (NetMessage::ValidatorSet(validators), offset)
+            }
+            Self::TAG_HEARTBEAT => {
+                let mut reader = FieldReader::new(payload, endianness);
+                let message = NetMessage::Heartbeat {
+                    validator_id: reader.field()?,
+                    height: reader.field()?,
+                    timestamp: reader.field()?,
+                    signature: reader.field()?,
+                };
+                (message, reader.finish())
+            }
+            other => {
+                return Err(SerializationError::InvalidData(format!(
+                    "Unknown NetMessage tag: {}",
+                    other
+                )))
+            }
+        };
+        Ok((message, 1 + consumed))
+    }
+}
+
+/// Writes `message` to `writer` as a single length-prefixed, checksummed frame.
+pub fn write_frame<W: Write>(writer: &mut W, message: &NetMessage) -> NetResult<()> {
+    writer.write_all(&encode_frame(message)?)?;
+    Ok(writer.flush()?)
+}
+
+/// Encodes `message` as a single length-prefixed, checksummed frame.
+pub fn encode_frame(message: &NetMessage) -> NetResult<Vec<u8>> {
+    Ok(Serializer::serialize_with::<WireChecksum, _>(message, WIRE_ENDIANNESS)?)
+}
+
+/// Decodes one complete frame, length prefix included, produced by `encode_frame`.
+pub fn decode_frame(frame: &[u8]) -> NetResult<NetMessage> {
+    Ok(Serializer::deserialize_with::<WireChecksum, _>(frame, WIRE_ENDIANNESS)?)
+}
+
+/// Checks a frame's 4-byte length prefix against `max_size`, returning the
+/// payload length that follows it.
+pub fn frame_payload_len(header: [u8; FRAME_HEADER_LEN], max_size: usize) -> NetResult<usize> {
+    let len = u32::from_le_bytes(header) as usize;
+    if len > max_size {
+        return Err(NetError::MessageTooLarge { size: len, max: max_size });
+    }
+    Ok(len)
+}
+
+/// Reads exactly one frame from `reader` and decodes it into a `NetMessage`.
+///
+/// The declared length is checked against `max_size` before any buffer is
+/// allocated, so a peer cannot force a huge allocation with a forged header.
+/// Returns `NetError::Io` with `UnexpectedEof` if the stream closes before a
+/// full frame arrives.
+pub fn read_frame<R: Read>(reader: &mut R, max_size: usize) -> NetResult<NetMessage> {
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    let len = frame_payload_len(header, max_size)?;
+    let mut frame = vec![0u8; FRAME_HEADER_LEN + len];
+    frame[..FRAME_HEADER_LEN].copy_from_slice(&header);
+    reader.read_exact(&mut frame[FRAME_HEADER_LEN..])?;
+    decode_frame(&frame)
+}
+
+/// Size of the length prefix at the start of every frame.
+pub const FRAME_HEADER_LEN: usize = 4;
+
+/// Accumulates bytes from a stream whose reads may split or coalesce frames,
+/// yielding each complete frame's message once all of its bytes have arrived.
+/// Shared by the sync and async connection handlers.
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_size: usize,
+}
+
+impl FrameDecoder {
+    /// Creates an empty buffer that rejects frames longer than `max_size`.
+    pub fn new(max_size: usize) -> Self {
+        Self { buffer: Vec::new(), max_size }
+    }
+
+    /// Appends bytes received from the stream.
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Appends `bytes` and decodes every frame now complete, in order, keeping
+    /// any trailing partial frame for the next call. Fails on the first
+    /// oversized header or undecodable frame; the stream cannot be resynced
+    /// after that, so the decoder should be discarded.
+    pub fn feed(&mut self, bytes: &[u8]) -> NetResult<Vec<NetMessage>> {
+        self.extend(bytes);
+        let mut messages = Vec::new();
+        while let Some(message) = self.next_message()? {
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+
+    /// Returns the number of buffered bytes not yet consumed by a complete frame.
+    pub fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Removes and decodes the next complete frame, or returns `Ok(None)` if
+    /// only part of one has arrived. An oversized length prefix is rejected as
+    /// soon as the header is complete, before its payload is buffered.
+    pub fn next_message(&mut self) -> NetResult<Option<NetMessage>> {
+        if self.buffer.len() < FRAME_HEADER_LEN {
+            return Ok(None);
+        }
+        let header: [u8; FRAME_HEADER_LEN] = self.buffer[..FRAME_HEADER_LEN].try_into().expect("header length");
+        let len = frame_payload_len(header, self.max_size)?;
+        let frame_len = FRAME_HEADER_LEN + len;
+        if self.buffer.len() < frame_len {
+            return Ok(None);
+        }
+        let message = decode_frame(&self.buffer[..frame_len]);
+        self.buffer.drain(..frame_len);
+        Ok(Some(message?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::serialization::TransactionBuilder;
+
+    fn sample_messages() -> Vec<NetMessage> {
+        let block = Block {
+            block_number: 1,
+            previous_hash: [0u8; 32],
+            transactions: Vec::new(),
+            tx_count: 0,
+            merkle_root: [0u8; 32],
+            timestamp: 42,
+            signature: Vec::new(),
+        };
+        vec![
+            NetMessage::Ping,
+            NetMessage::Pong,
+            NetMessage::Text("hello".into()),
+            NetMessage::GetBlocks { from_height: 10, count: 64 },
+            NetMessage::Blocks(vec![block.clone(), block]),
+            NetMessage::NewTransaction(
+                TransactionBuilder::new()
+                    .id(1)
+                    .amount(1000)
+                    .fee(5.0)
+                    .sender("Alice")
+                    .recipient("Bob")
+                    .signature(vec![1, 2, 3, 4])
+                    .build()
+                    .unwrap(),
+            ),
+            NetMessage::Hello { protocol_version: 1, node_id: "node-a".into() },
+            NetMessage::ValidatorSet(vec![Validator {
+                id: "Validator_A".into(),
+                stake_amount: 100,
+                puzzle_passed: true,
+                jailed: false,
+                last_solution: Some(42),
+            }]),
+            NetMessage::Heartbeat {
+                validator_id: "Validator_A".into(),
+                height: 12,
+                timestamp: 1_700_000_000,
+                signature: vec![7; 64],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        for message in sample_messages() {
+            let mut wire = Vec::new();
+            write_frame(&mut wire, &message).unwrap();
+            let decoded = read_frame(&mut wire.as_slice(), DEFAULT_MAX_MESSAGE_SIZE).unwrap();
+            assert_eq!(decoded, message);
+        }
+    }
+
+    #[test]
+    fn test_framed_size_matches_encoded_frame() {
+        for message in sample_messages() {
+            assert_eq!(message.framed_size(), encode_frame(&message).unwrap().len(), "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn test_unknown_tag_rejected() {
+        assert!(NetMessage::decode_from(&[0xff], WIRE_ENDIANNESS).is_err());
+    }
+
+    #[test]
+    fn test_oversized_frame_rejected_before_allocation() {
+        // Header claims ~4 GiB; only the 4 header bytes are actually present.
+        let wire = u32::MAX.to_le_bytes();
+        let err = read_frame(&mut wire.as_slice(), 1024).expect_err("oversized frame accepted");
+        assert!(matches!(err, NetError::MessageTooLarge { size, max: 1024 } if size == u32::MAX as usize));
+    }
+
+    fn framed(messages: &[NetMessage]) -> Vec<u8> {
+        let mut wire = Vec::new();
+        for message in messages {
+            write_frame(&mut wire, message).unwrap();
+        }
+        wire
+    }
+
+    fn drain(frames: &mut FrameDecoder) -> Vec<NetMessage> {
+        let mut out = Vec::new();
+        while let Some(message) = frames.next_message().unwrap() {
+            out.push(message);
+        }
+        out
+    }
+
+    #[test]
+    fn test_frame_decoder_reassembles_any_chunking() {
+        let messages = vec![NetMessage::Ping, NetMessage::Text("hello, peer".into()), NetMessage::Pong];
+        let wire = framed(&messages);
+        for chunk_size in 1..=wire.len() {
+            let mut frames = FrameDecoder::new(DEFAULT_MAX_MESSAGE_SIZE);
+            let mut received = Vec::new();
+            for chunk in wire.chunks(chunk_size) {
+                frames.extend(chunk);
+                received.extend(drain(&mut frames));
+            }
+            assert_eq!(received, messages, "chunk size {}", chunk_size);
+            assert_eq!(frames.pending(), 0);
+        }
+    }
+
+    #[test]
+    fn test_frame_decoder_keeps_partial_remainder() {
+        let wire = framed(&[NetMessage::Ping, NetMessage::Text("split".into())]);
+        let split = wire.len() - 3;
+        let mut frames = FrameDecoder::new(DEFAULT_MAX_MESSAGE_SIZE);
+        frames.extend(&wire[..split]);
+        assert_eq!(drain(&mut frames), vec![NetMessage::Ping]);
+        assert!(frames.pending() > 0);
+        frames.extend(&wire[split..]);
+        assert_eq!(drain(&mut frames), vec![NetMessage::Text("split".into())]);
+    }
+
+    #[test]
+    fn test_feed_reassembles_across_split_points() {
+        let messages = vec![
+            NetMessage::Hello { protocol_version: 1, node_id: "node-a".into() },
+            NetMessage::GetBlocks { from_height: 3, count: 16 },
+            NetMessage::Text("feed me".into()),
+        ];
+        let wire = framed(&messages);
+        // Every pair of cut points, so headers, payloads, and checksums are all split.
+        for first in 0..=wire.len() {
+            for second in first..=wire.len() {
+                let mut decoder = FrameDecoder::new(DEFAULT_MAX_MESSAGE_SIZE);
+                let mut received = decoder.feed(&wire[..first]).unwrap();
+                received.extend(decoder.feed(&wire[first..second]).unwrap());
+                received.extend(decoder.feed(&wire[second..]).unwrap());
+                assert_eq!(received, messages, "cuts at {} and {}", first, second);
+                assert_eq!(decoder.pending(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_feed_enforces_max_size_while_accumulating() {
+        let wire = framed(&[NetMessage::Text("x".repeat(64))]);
+        let mut decoder = FrameDecoder::new(32);
+        // Half a header is not enough to judge the frame.
+        assert_eq!(decoder.feed(&wire[..2]).unwrap(), Vec::new());
+        assert!(matches!(decoder.feed(&wire[2..5]), Err(NetError::MessageTooLarge { max: 32, .. })));
+    }
+
+    #[test]
+    fn test_frame_decoder_rejects_oversized_header() {
+        let mut frames = FrameDecoder::new(16);
+        frames.extend(&1024u32.to_le_bytes());
+        assert!(matches!(frames.next_message(), Err(NetError::MessageTooLarge { size: 1024, max: 16 })));
+    }
+
+    #[test]
+    fn test_corrupt_frame_is_framing_error() {
+        let mut wire = framed(&[NetMessage::Text("hello".into())]);
+        let last = wire.len() - 1;
+        wire[last] ^= 1;
+        assert!(matches!(read_frame(&mut wire.as_slice(), DEFAULT_MAX_MESSAGE_SIZE), Err(NetError::Framing(_))));
+        let truncated = &wire[..wire.len() - 5];
+        assert!(matches!(read_frame(&mut &truncated[..], DEFAULT_MAX_MESSAGE_SIZE), Err(NetError::Io(_))));
+    }
+
+    #[test]
+    fn test_wire_frames_carry_crc32() {
+        let message = NetMessage::Text("hello".into());
+        let frame = encode_frame(&message).unwrap();
+        assert_eq!(frame.len(), FRAME_HEADER_LEN + message.encoded_size() + 4);
+        assert_eq!(decode_frame(&frame).unwrap(), message);
+        // A storage-style Blake3 frame is not a valid wire frame.
+        assert!(Serializer::deserialize::<NetMessage>(&frame, WIRE_ENDIANNESS).is_err());
+    }
+}
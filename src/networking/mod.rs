@@ -1 +1,6 @@
-pub mod network;
\ No newline at end of file
+#[cfg(feature = "async")]
+pub mod async_network;
+pub mod error;
+pub mod message;
+pub mod network;
+pub mod peer_score;
@@ -2,52 +2,330 @@
 //! Minimal Networking Module for Reina Phase 1.
 //!
 //! This module simulates basic P2P networking using TCP. It provides a NetworkNode
-//! that listens on a specified port, a function to send messages to peers, and a simple
-//! connection handler that logs incoming messages. Future versions will expand these
-//! capabilities for block propagation and consensus. 
+//! that listens on a specified port, functions to send messages to peers, and a simple
+//! connection handler that logs incoming messages and answers pings. Messages are
+//! typed `NetMessage`s carried in length-prefixed frames. Future versions will expand
+//! these capabilities for block propagation and consensus.
 
-use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
+use log::{error, info, warn};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
+
+use ed25519_dalek::SigningKey;
+
+use crate::consensus::block_producer::{Clock, SystemClock};
+use crate::networking::error::{NetError, NetResult};
+use crate::networking::message::{read_frame, write_frame, FrameDecoder, NetMessage, DEFAULT_MAX_MESSAGE_SIZE};
+use crate::networking::peer_score::{PeerScore, Violation};
+use crate::node::chain_manager::ChainManager;
+use crate::node::mempool::ConcurrentMempool;
+use crate::pocup::liveness::{sign_heartbeat, HeartbeatError, LivenessTracker};
+use crate::pocup::pocup::Validator;
+use crate::utils::serialization::Transaction;
+
+/// Number of blocks requested per `GetBlocks` round during sync.
+pub const SYNC_BATCH_SIZE: u32 = 64;
+
+/// Most blocks sent in reply to one `GetBlocks`, whatever count the peer asks
+/// for. At least `SYNC_BATCH_SIZE`, so a full sync batch is never cut short.
+pub const MAX_BLOCKS_PER_RESPONSE: u32 = 256;
+
+/// Timeout applied to each request issued while syncing.
+const SYNC_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default delay before the first retry in `send_with_retry`.
+pub const DEFAULT_RETRY_BACKOFF_BASE: Duration = Duration::from_millis(50);
+
+/// Default upper bound on a single retry delay in `send_with_retry`.
+pub const DEFAULT_RETRY_BACKOFF_CAP: Duration = Duration::from_secs(2);
+
+/// Wire protocol version this build speaks.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest peer protocol version this build accepts.
+pub const MIN_SUPPORTED_PROTOCOL_VERSION: u32 = 1;
+
+/// Default idle time after which a connection is probed with `Ping`.
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default time a probed peer has to answer with `Pong` before it is dropped.
+pub const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time a connection may go without receiving anything before it is closed.
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Default time a single write to a peer may block before the connection is closed.
+pub const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default most incoming connections served at once.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 256;
+
+/// Default most connections accepted from one address per rate window.
+pub const DEFAULT_MAX_CONNECTIONS_PER_IP: usize = 32;
+
+/// Default window over which connections per address are counted.
+pub const DEFAULT_CONNECTION_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Shared state that connection handlers use to answer requests.
+#[derive(Clone)]
+pub(crate) struct NodeServices {
+    /// Largest frame length accepted from a peer.
+    pub(crate) max_message_size: usize,
+    /// Chain served to peers that send `GetBlocks`.
+    pub(crate) chain: Option<Arc<Mutex<ChainManager>>>,
+    /// Mempool that receives gossiped transactions.
+    pub(crate) mempool: Option<Arc<ConcurrentMempool>>,
+    /// Tracker updated from received validator heartbeats.
+    pub(crate) liveness: Option<Arc<Mutex<LivenessTracker>>>,
+    /// Addresses of peers that gossip is broadcast to.
+    pub(crate) peers: Arc<Mutex<Vec<String>>>,
+    /// Misbehaviour scores and bans of connecting peers.
+    pub(crate) peer_scores: Arc<Mutex<PeerScore>>,
+    /// Identifier announced to peers in `Hello`.
+    pub(crate) node_id: String,
+    /// Protocol version announced to peers in `Hello`.
+    pub(crate) protocol_version: u32,
+    /// Peer protocol versions accepted during the handshake.
+    pub(crate) supported_versions: RangeInclusive<u32>,
+    /// Idle time after which a connection is probed with `Ping`.
+    pub(crate) ping_interval: Duration,
+    /// Time a probed peer has to send any traffic before it is dropped.
+    pub(crate) pong_timeout: Duration,
+    /// Time a connection may receive nothing, handshake included, before it is closed.
+    pub(crate) read_timeout: Duration,
+    /// Time a single write may block before the connection is closed.
+    pub(crate) write_timeout: Duration,
+    /// Incoming connections currently being served.
+    pub(crate) active_connections: Arc<AtomicUsize>,
+    /// Most incoming connections served at once.
+    pub(crate) max_connections: usize,
+    /// Accept times of recent connections per address, oldest first.
+    pub(crate) recent_connections: Arc<Mutex<HashMap<IpAddr, VecDeque<Instant>>>>,
+    /// Most connections accepted from one address per `connection_rate_window`.
+    pub(crate) max_connections_per_ip: usize,
+    /// Window over which connections per address are counted.
+    pub(crate) connection_rate_window: Duration,
+}
+
+/// Holds one of the node's `max_connections` slots; dropping it frees the slot.
+pub(crate) struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// State of one peer connection, established by the handshake.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PeerSession {
+    /// Identifier the peer announced in its `Hello`.
+    pub(crate) node_id: String,
+    /// Version both sides speak: the lower of the two announced versions.
+    pub(crate) protocol_version: u32,
+}
 
 /// A network node that listens for incoming TCP connections.
 pub struct NetworkNode {
     /// The TCP listener bound to a port.
     listener: TcpListener,
+    /// State shared with every connection handler.
+    services: NodeServices,
+    /// Delay before the first retry; doubled after each failed attempt.
+    retry_backoff_base: Duration,
+    /// Upper bound on a single retry delay.
+    retry_backoff_cap: Duration,
 }
 
 impl NetworkNode {
-    /// Creates a new NetworkNode listening on the specified port.
+    /// Creates a new NetworkNode listening on the specified port on all
+    /// interfaces (`0.0.0.0`). Use `bind` to restrict the node to one interface.
     ///
     /// # Arguments
     ///
     /// * `port` - The port number to bind the listener.
     pub fn new(port: u16) -> std::io::Result<Self> {
-        let addr = format!("0.0.0.0:{}", port);
+        Self::bind(&format!("0.0.0.0:{}", port))
+    }
+
+    /// Creates a new NetworkNode listening on `addr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Any socket address string, e.g. "127.0.0.1:8000" or "[::1]:8000".
+    pub fn bind(addr: &str) -> std::io::Result<Self> {
         let listener = TcpListener::bind(addr)?;
-        Ok(Self { listener })
+        let services = NodeServices { node_id: listener.local_addr()?.to_string(), ..NodeServices::default() };
+        Ok(Self {
+            listener,
+            services,
+            retry_backoff_base: DEFAULT_RETRY_BACKOFF_BASE,
+            retry_backoff_cap: DEFAULT_RETRY_BACKOFF_CAP,
+        })
+    }
+
+    /// Sets the exponential backoff used by `send_with_retry`: the first retry waits
+    /// `base`, each later one twice as long as the previous, never exceeding `cap`.
+    pub fn set_retry_backoff(&mut self, base: Duration, cap: Duration) {
+        self.retry_backoff_base = base;
+        self.retry_backoff_cap = cap;
+    }
+
+    /// Sets the largest frame length accepted from peers. Connections that declare
+    /// a larger frame are dropped before any buffer is allocated.
+    pub fn set_max_message_size(&mut self, max_message_size: usize) {
+        self.services.max_message_size = max_message_size;
+    }
+
+    /// Sets the identifier announced to peers in `Hello`.
+    pub fn set_node_id(&mut self, node_id: &str) {
+        self.services.node_id = node_id.to_string();
+    }
+
+    /// Sets the protocol version announced in `Hello` and the range of peer
+    /// versions accepted. Peers announcing a version outside `supported` are
+    /// disconnected before any other message is handled.
+    pub fn set_protocol_version(&mut self, version: u32, supported: RangeInclusive<u32>) {
+        self.services.protocol_version = version;
+        self.services.supported_versions = supported;
+    }
+
+    /// Sets the keep-alive policy for incoming connections: after `interval`
+    /// without traffic the node sends `Ping`, and closes the connection if
+    /// nothing arrives within `timeout`. Any received traffic resets the timer.
+    pub fn set_keepalive(&mut self, interval: Duration, timeout: Duration) {
+        self.services.ping_interval = interval;
+        self.services.pong_timeout = timeout;
+    }
+
+    /// Sets the socket timeouts for incoming connections: a connection that
+    /// receives nothing for `read`, or whose write blocks for `write`, is
+    /// closed and the reason logged. Keep `read` above the keep-alive interval
+    /// plus pong timeout, or healthy idle peers are dropped too.
+    pub fn set_io_timeouts(&mut self, read: Duration, write: Duration) {
+        self.services.read_timeout = read;
+        self.services.write_timeout = write;
+    }
+
+    /// Sets the peer scoring policy: a peer whose score falls below
+    /// `ban_threshold` is disconnected and its address refused for `ban_duration`.
+    /// Scores and bans recorded so far are discarded.
+    pub fn set_ban_policy(&mut self, ban_threshold: i32, ban_duration: Duration) {
+        let scores = PeerScore::new().with_ban_threshold(ban_threshold).with_ban_duration(ban_duration);
+        self.services.peer_scores = Arc::new(Mutex::new(scores));
+    }
+
+    /// Returns true if connections from `peer` are currently refused.
+    pub fn is_banned(&self, peer: IpAddr) -> bool {
+        self.services.is_banned(peer)
+    }
+
+    /// Sets the connection flood limits: at most `max_connections` incoming
+    /// connections are served at once, and at most `max_per_ip` are accepted
+    /// from one address within any `window`. Connections beyond either limit
+    /// are closed as soon as they are accepted.
+    pub fn set_connection_limits(&mut self, max_connections: usize, max_per_ip: usize, window: Duration) {
+        self.services.max_connections = max_connections;
+        self.services.max_connections_per_ip = max_per_ip;
+        self.services.connection_rate_window = window;
+    }
+
+    /// Returns the number of incoming connections currently being served.
+    pub fn active_connections(&self) -> usize {
+        self.services.active_connections.load(Ordering::Acquire)
+    }
+
+    /// Serves blocks from `chain` to peers that send `GetBlocks`.
+    pub fn serve_chain(&mut self, chain: Arc<Mutex<ChainManager>>) {
+        self.services.chain = Some(chain);
+    }
+
+    /// Adds gossiped transactions that are accepted into `mempool`.
+    pub fn serve_mempool(&mut self, mempool: Arc<ConcurrentMempool>) {
+        self.services.mempool = Some(mempool);
+    }
+
+    /// Records validator heartbeats received from peers in `tracker`.
+    pub fn serve_liveness(&mut self, tracker: Arc<Mutex<LivenessTracker>>) {
+        self.services.liveness = Some(tracker);
+    }
+
+    /// Registers a peer address that gossip is broadcast to.
+    pub fn add_peer(&self, peer_addr: &str) {
+        let mut peers = self.services.peers.lock().unwrap();
+        if !peers.iter().any(|p| p == peer_addr) {
+            peers.push(peer_addr.to_string());
+        }
+    }
+
+    /// Broadcasts `tx` to every known peer as a `NewTransaction` message.
+    ///
+    /// Delivery failures are logged and do not stop the broadcast.
+    pub fn gossip_transaction(&self, tx: &Transaction) {
+        self.services.broadcast(&NetMessage::NewTransaction(tx.clone()));
+    }
+
+    /// Broadcasts `validators` to every known peer as a `ValidatorSet` message.
+    /// Peers serving a chain merge the set into their own.
+    pub fn gossip_validators(&self, validators: &[Validator]) {
+        self.services.broadcast(&NetMessage::ValidatorSet(validators.to_vec()));
+    }
+
+    /// Broadcasts a heartbeat for `validator_id` at `height`, stamped with the
+    /// current time and signed with `key`.
+    pub fn gossip_heartbeat(&self, validator_id: &str, height: u64, key: &SigningKey) {
+        let timestamp = SystemClock.now_secs();
+        self.services.broadcast(&NetMessage::Heartbeat {
+            validator_id: validator_id.to_string(),
+            height,
+            timestamp,
+            signature: sign_heartbeat(key, validator_id, height, timestamp),
+        });
     }
 
     /// Runs the network node, accepting and handling incoming connections.
     ///
     /// For each connection, a new thread is spawned to handle messages.
+    /// Connections from banned addresses, or beyond the connection limits
+    /// (see `set_connection_limits`), are closed immediately.
     pub fn run(&self) {
-        println!("NetworkNode listening on {}", self.listener.local_addr().unwrap());
+        info!("NetworkNode listening on {}", self.listener.local_addr().unwrap());
         for stream in self.listener.incoming() {
             match stream {
                 Ok(stream) => {
+                    let slot = match stream.peer_addr() {
+                        Ok(peer_addr) => match self.services.admit(peer_addr.ip()) {
+                            Ok(slot) => slot,
+                            Err(reason) => {
+                                warn!("Refusing connection from {}: {}", peer_addr, reason);
+                                continue;
+                            }
+                        },
+                        Err(e) => {
+                            error!("Connection failed: {}", e);
+                            continue;
+                        }
+                    };
+                    let services = self.services.clone();
                     thread::spawn(move || {
-                        if let Err(e) = handle_connection(stream) {
-                            eprintln!("Error handling connection: {}", e);
+                        let _slot = slot;
+                        if let Err(e) = handle_connection(stream, &services) {
+                            error!("Error handling connection: {}", e);
                         }
                     });
                 }
-                Err(e) => eprintln!("Connection failed: {}", e),
+                Err(e) => error!("Connection failed: {}", e),
             }
         }
     }
 
-    /// Sends a message to a peer at the given address.
+    /// Sends a text message to a peer at the given address.
     ///
     /// # Arguments
     ///
@@ -57,25 +335,441 @@ impl NetworkNode {
     /// # Returns
     ///
     /// Ok(()) on success; otherwise, an error.
-    pub fn send_message(peer_addr: &str, message: &str) -> std::io::Result<()> {
-        let mut stream = TcpStream::connect(peer_addr)?;
-        stream.write_all(message.as_bytes())?;
-        Ok(())
+    pub fn send_message(peer_addr: &str, message: &str) -> NetResult<()> {
+        NodeServices::default().send(peer_addr, &NetMessage::Text(message.to_string()))
+    }
+
+    /// Sends `message` to `peer`, retrying failed connects or writes with
+    /// exponential backoff.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer's address (e.g., "127.0.0.1:8000").
+    /// * `message` - The message to send.
+    /// * `max_attempts` - Total number of attempts, including the first. Zero
+    ///   makes no attempt.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) once an attempt succeeds; otherwise, the error from the last
+    /// attempt, or an `InvalidInput` I/O error if `max_attempts` is zero.
+    pub fn send_with_retry(&self, peer: &str, message: &NetMessage, max_attempts: u32) -> NetResult<()> {
+        if max_attempts == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "max_attempts is zero").into());
+        }
+        let mut delay = self.retry_backoff_base;
+        let mut attempt = 1;
+        loop {
+            match self.services.send(peer, message) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= max_attempts => return Err(e),
+                Err(e) => {
+                    warn!("Send to {} failed (attempt {}/{}): {}; retrying in {:?}", peer, attempt, max_attempts, e, delay);
+                    thread::sleep(delay);
+                    delay = (delay * 2).min(self.retry_backoff_cap);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Sends `message` to `peer` and waits for a single framed response.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer's address (e.g., "127.0.0.1:8000").
+    /// * `message` - The request to send.
+    /// * `timeout` - Upper bound for connecting, sending, and receiving the reply.
+    ///
+    /// # Returns
+    ///
+    /// The decoded response; `NetError::Timeout` if the peer does not answer in
+    /// time, or `NetError::ProtocolMismatch` if its `Hello` is incompatible.
+    pub fn request(&self, peer: &str, message: &NetMessage, timeout: Duration) -> NetResult<NetMessage> {
+        let addr = peer.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("could not resolve peer {}", peer))
+        })?;
+        let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        write_frame(&mut stream, &self.services.hello())?;
+        write_frame(&mut stream, message)?;
+        let hello = read_frame(&mut stream, self.services.max_message_size)?;
+        self.services.negotiate(&hello)?;
+        read_frame(&mut stream, self.services.max_message_size)
+    }
+
+    /// Catches `chain` up with `peer` by repeatedly requesting the blocks above the
+    /// local head and appending them.
+    ///
+    /// Stops once the peer returns fewer blocks than requested. A block that fails
+    /// validation aborts the sync with `NetError::InvalidBlock` and costs the peer
+    /// score for `Violation::InvalidBlock`; blocks appended before it are kept.
+    ///
+    /// # Returns
+    ///
+    /// The local chain height after syncing.
+    pub fn sync_from(&self, peer: &str, chain: &mut ChainManager) -> NetResult<u64> {
+        loop {
+            let request = NetMessage::GetBlocks { from_height: chain.height() + 1, count: SYNC_BATCH_SIZE };
+            let blocks = match self.request(peer, &request, SYNC_REQUEST_TIMEOUT)? {
+                NetMessage::Blocks(blocks) => blocks,
+                other => return Err(NetError::Framing(format!("unexpected reply to GetBlocks: {:?}", other))),
+            };
+            let received = blocks.len();
+            for block in blocks {
+                let number = block.block_number;
+                chain.append_block(block).map_err(|e| {
+                    warn!("Rejected block #{} from {}: {}", number, peer, e);
+                    if let Some(addr) = peer.to_socket_addrs().ok().and_then(|mut addrs| addrs.next()) {
+                        self.services.penalize(addr.ip(), Violation::InvalidBlock);
+                    }
+                    NetError::InvalidBlock(e)
+                })?;
+            }
+            info!("Synced {} blocks from {}; height is now {}", received, peer, chain.height());
+            if received < SYNC_BATCH_SIZE as usize {
+                return Ok(chain.height());
+            }
+        }
     }
 }
 
-/// Handles an incoming connection by reading messages and logging them.
+impl Default for NodeServices {
+    fn default() -> Self {
+        Self {
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            chain: None,
+            mempool: None,
+            liveness: None,
+            peers: Arc::default(),
+            peer_scores: Arc::default(),
+            node_id: String::new(),
+            protocol_version: PROTOCOL_VERSION,
+            supported_versions: MIN_SUPPORTED_PROTOCOL_VERSION..=PROTOCOL_VERSION,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            pong_timeout: DEFAULT_PONG_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            active_connections: Arc::default(),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            recent_connections: Arc::default(),
+            max_connections_per_ip: DEFAULT_MAX_CONNECTIONS_PER_IP,
+            connection_rate_window: DEFAULT_CONNECTION_RATE_WINDOW,
+        }
+    }
+}
+
+impl NodeServices {
+    /// Returns the `Hello` this node opens every connection with.
+    pub(crate) fn hello(&self) -> NetMessage {
+        NetMessage::Hello { protocol_version: self.protocol_version, node_id: self.node_id.clone() }
+    }
+
+    /// Checks a peer's opening message, returning the session on success or
+    /// the reason the connection must be refused.
+    pub(crate) fn negotiate(&self, message: &NetMessage) -> NetResult<PeerSession> {
+        match message {
+            NetMessage::Hello { protocol_version, node_id } if self.supported_versions.contains(protocol_version) => {
+                Ok(PeerSession {
+                    node_id: node_id.clone(),
+                    protocol_version: (*protocol_version).min(self.protocol_version),
+                })
+            }
+            NetMessage::Hello { protocol_version, .. } => {
+                Err(NetError::ProtocolMismatch { theirs: *protocol_version, ours: self.protocol_version })
+            }
+            other => Err(NetError::Framing(format!("expected Hello, got {:?}", other))),
+        }
+    }
+
+    /// Opens a connection to `peer` and sends `Hello` followed by `message`,
+    /// without waiting for a reply.
+    fn send(&self, peer: &str, message: &NetMessage) -> NetResult<()> {
+        let mut stream = TcpStream::connect(peer)?;
+        write_frame(&mut stream, &self.hello())?;
+        write_frame(&mut stream, message)
+    }
+
+    /// Sends `message` to every known peer, logging failures.
+    fn broadcast(&self, message: &NetMessage) {
+        self.broadcast_except(message, |_| false);
+    }
+
+    /// Like `broadcast`, skipping the peers for which `skip` returns true.
+    fn broadcast_except(&self, message: &NetMessage, skip: impl Fn(&str) -> bool) {
+        let peers = self.peers.lock().unwrap().clone();
+        for peer in peers.into_iter().filter(|peer| !skip(peer)) {
+            if let Err(e) = self.send(&peer, message) {
+                warn!("Failed to gossip to {}: {}", peer, e);
+            }
+        }
+    }
+
+    /// Answers a peer's first message: returns the `Hello` to send back, if the
+    /// message was a `Hello` at all, and the outcome of the negotiation. The
+    /// reply goes out even on a version mismatch so the peer learns ours.
+    pub(crate) fn handshake(&self, message: &NetMessage) -> (Option<NetMessage>, NetResult<PeerSession>) {
+        let reply = matches!(message, NetMessage::Hello { .. }).then(|| self.hello());
+        (reply, self.negotiate(message))
+    }
+
+    /// Returns true if connections from `peer` are currently refused.
+    pub(crate) fn is_banned(&self, peer: IpAddr) -> bool {
+        self.peer_scores.lock().unwrap().is_banned(peer, Instant::now())
+    }
+
+    /// Decides whether to serve a new connection from `peer`. Returns the
+    /// slot the connection holds while it is served, or why it is refused:
+    /// the peer is banned, has connected too often within the rate window, or
+    /// every slot is taken.
+    pub(crate) fn admit(&self, peer: IpAddr) -> Result<ConnectionSlot, &'static str> {
+        if self.is_banned(peer) {
+            return Err("peer is banned");
+        }
+        let now = Instant::now();
+        let mut recent = self.recent_connections.lock().unwrap();
+        recent.retain(|_, times| {
+            while times.front().is_some_and(|t| now.duration_since(*t) >= self.connection_rate_window) {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+        if recent.get(&peer).is_some_and(|times| times.len() >= self.max_connections_per_ip) {
+            return Err("connection rate limit exceeded");
+        }
+        self.active_connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < self.max_connections).then_some(active + 1)
+            })
+            .map_err(|_| "too many connections")?;
+        recent.entry(peer).or_default().push_back(now);
+        Ok(ConnectionSlot(Arc::clone(&self.active_connections)))
+    }
+
+    /// Lowers `peer`'s score for `violation`, logging a resulting ban.
+    pub(crate) fn penalize(&self, peer: IpAddr, violation: Violation) {
+        let mut scores = self.peer_scores.lock().unwrap();
+        if scores.penalize(peer, violation, Instant::now()) {
+            warn!("Banning peer {} after {}", peer, violation);
+        } else {
+            warn!("Peer {} sent a {}; score now {}", peer, violation, scores.score(peer));
+        }
+    }
+
+    /// Penalizes `peer` if `error` ended its connection because of a protocol
+    /// violation rather than a socket failure.
+    pub(crate) fn penalize_error(&self, peer: IpAddr, error: &NetError) {
+        match error {
+            NetError::Framing(_) => self.penalize(peer, Violation::MalformedFrame),
+            NetError::MessageTooLarge { .. } => self.penalize(peer, Violation::OversizedMessage),
+            _ => {}
+        }
+    }
+
+    /// Returns the reply, if any, that the node sends for an incoming message
+    /// from `peer`, which opened `session`. Well-formed messages recover a
+    /// little of the peer's score; violations cost it.
+    pub(crate) fn respond(&self, message: &NetMessage, peer: IpAddr, session: &PeerSession) -> Option<NetMessage> {
+        match self.reply_to(message, peer, session) {
+            Ok(reply) => {
+                self.peer_scores.lock().unwrap().reward(peer);
+                reply
+            }
+            Err(violation) => {
+                self.penalize(peer, violation);
+                None
+            }
+        }
+    }
+
+    /// Computes the reply for `respond`, or the violation `message` commits.
+    fn reply_to(
+        &self,
+        message: &NetMessage,
+        peer: IpAddr,
+        session: &PeerSession,
+    ) -> Result<Option<NetMessage>, Violation> {
+        let reply = match message {
+            NetMessage::Ping => Some(NetMessage::Pong),
+            NetMessage::NewTransaction(tx) => {
+                // Only newly accepted transactions are relayed, so gossip terminates
+                // once every node has seen the transaction. The sender already has it.
+                if let Some(mempool) = &self.mempool {
+                    if mempool.add_transaction(tx.clone()) {
+                        info!("Accepted gossiped transaction {}", tx.id);
+                        self.broadcast_except(message, |addr| is_origin(addr, peer, &session.node_id));
+                    }
+                }
+                None
+            }
+            NetMessage::ValidatorSet(validators) => {
+                if let Some(chain) = &self.chain {
+                    let changed = chain.lock().unwrap().merge_validators(validators.clone());
+                    info!("Merged validator set of {}; {} entries added or updated", validators.len(), changed);
+                }
+                None
+            }
+            NetMessage::Heartbeat { validator_id, height, timestamp, signature } => {
+                if let Some(liveness) = &self.liveness {
+                    let now = SystemClock.now_secs();
+                    if let Err(e) = liveness.lock().unwrap().record(validator_id, *height, *timestamp, signature, now) {
+                        warn!("Ignoring heartbeat from {}: {}", validator_id, e);
+                        if e == HeartbeatError::InvalidSignature {
+                            return Err(Violation::InvalidSignature);
+                        }
+                    }
+                }
+                None
+            }
+            // Repeated handshakes are ignored once a session is established.
+            NetMessage::Pong | NetMessage::Blocks(_) | NetMessage::Hello { .. } => None,
+            NetMessage::Text(text) => {
+                info!("Received message: {}", text);
+                None
+            }
+            NetMessage::GetBlocks { from_height, count } => {
+                let blocks = match &self.chain {
+                    Some(chain) => {
+                        let count = (*count).min(MAX_BLOCKS_PER_RESPONSE) as usize;
+                        chain.lock().unwrap().blocks_from(*from_height, count)
+                    }
+                    None => Vec::new(),
+                };
+                Some(NetMessage::Blocks(blocks))
+            }
+        };
+        Ok(reply)
+    }
+}
+
+/// Returns true if the peer registered as `addr` is the node that connected
+/// from `ip` and announced `node_id` in its `Hello`. Node ids default to the
+/// listening address, whose host may be unspecified (e.g. `0.0.0.0:8000`);
+/// the connection's IP stands in for it then.
+fn is_origin(addr: &str, ip: IpAddr, node_id: &str) -> bool {
+    if addr == node_id {
+        return true;
+    }
+    match (addr.parse::<SocketAddr>(), node_id.parse::<SocketAddr>()) {
+        (Ok(addr), Ok(announced)) => {
+            addr.port() == announced.port()
+                && (addr.ip() == announced.ip() || (announced.ip().is_unspecified() && addr.ip() == ip))
+        }
+        _ => false,
+    }
+}
+
+/// Size of each read from a peer's socket.
+pub(crate) const READ_CHUNK_SIZE: usize = 4096;
+
+/// Shortest socket read timeout used to drive keep-alive checks.
+const MIN_KEEPALIVE_TICK: Duration = Duration::from_millis(1);
+
+/// Handles an incoming connection by reading framed messages, logging them, and
+/// writing back any reply.
+///
+/// The first message must be a `Hello`; the node answers with its own `Hello`
+/// either way, so the peer learns which version it speaks. If the peer's
+/// version is supported, the negotiated version is recorded for the
+/// connection; otherwise the connection is closed and the reason logged.
 ///
+/// Once the handshake completes, a connection idle for the keep-alive interval
+/// is sent `Ping`; if no traffic follows within the pong timeout, the
+/// connection is closed and logged.
+///
+/// A connection that receives nothing for the read timeout, or whose write
+/// blocks past the write timeout, is closed and logged the same way.
+///
+/// Bytes are accumulated in a `FrameDecoder`, so frames split across reads or
+/// several frames arriving in one read are all handled.
+///
+/// Malformed or oversized frames and invalid signatures lower the peer's
+/// score; once it is banned the connection is closed.
 /// Returns Ok(()) when the connection is closed or an error occurs.
-fn handle_connection(mut stream: TcpStream) -> std::io::Result<()> {
-    let mut buffer = [0u8; 512];
+fn handle_connection(stream: TcpStream, services: &NodeServices) -> NetResult<()> {
+    let peer_addr = stream.peer_addr()?;
+    match serve_connection(stream, peer_addr, services) {
+        Err(NetError::Timeout) => {
+            warn!("Closing connection from {}: write blocked for {:?}", peer_addr, services.write_timeout);
+            Ok(())
+        }
+        Err(e) => {
+            services.penalize_error(peer_addr.ip(), &e);
+            Err(e)
+        }
+        result => result,
+    }
+}
+
+/// Runs the read loop for `handle_connection`; write timeouts surface as `NetError::Timeout`.
+fn serve_connection(mut stream: TcpStream, peer_addr: SocketAddr, services: &NodeServices) -> NetResult<()> {
+    let mut session: Option<PeerSession> = None;
+    let mut frames = FrameDecoder::new(services.max_message_size);
+    let mut chunk = [0u8; READ_CHUNK_SIZE];
+    // Short read timeouts let the loop check the keep-alive and idle timers.
+    let tick = (services.ping_interval.min(services.pong_timeout).min(services.read_timeout) / 4)
+        .max(MIN_KEEPALIVE_TICK);
+    stream.set_read_timeout(Some(tick))?;
+    stream.set_write_timeout(Some(services.write_timeout))?;
+    let mut last_received = Instant::now();
+    let mut ping_sent: Option<Instant> = None;
     loop {
-        let bytes_read = stream.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break; // Connection closed.
+        let read = match stream.read(&mut chunk) {
+            Ok(0) => break, // Connection closed.
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                if last_received.elapsed() >= services.read_timeout {
+                    warn!("Closing connection from {}: nothing received for {:?}", peer_addr, services.read_timeout);
+                    return Ok(());
+                }
+                match ping_sent {
+                    Some(sent) if sent.elapsed() >= services.pong_timeout => {
+                        warn!("Closing connection from {}: no pong within {:?}", peer_addr, services.pong_timeout);
+                        return Ok(());
+                    }
+                    None if session.is_some() && last_received.elapsed() >= services.ping_interval => {
+                        write_frame(&mut stream, &NetMessage::Ping)?;
+                        ping_sent = Some(Instant::now());
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        last_received = Instant::now();
+        ping_sent = None;
+        for message in frames.feed(&chunk[..read])? {
+            let Some(current) = &session else {
+                let (reply, negotiated) = services.handshake(&message);
+                if let Some(reply) = reply {
+                    write_frame(&mut stream, &reply)?;
+                }
+                match negotiated {
+                    Ok(established) => {
+                        info!(
+                            "Handshake with {} ({}) negotiated protocol version {}",
+                            established.node_id, peer_addr, established.protocol_version
+                        );
+                        session = Some(established);
+                    }
+                    Err(reason) => {
+                        warn!("Closing connection from {}: {}", peer_addr, reason);
+                        return Ok(());
+                    }
+                }
+                continue;
+            };
+            if let Some(reply) = services.respond(&message, peer_addr.ip(), current) {
+                write_frame(&mut stream, &reply)?;
+            }
+            if services.is_banned(peer_addr.ip()) {
+                warn!("Closing connection from {}: peer is banned", peer_addr);
+                return Ok(());
+            }
         }
-        let msg = String::from_utf8_lossy(&buffer[..bytes_read]);
-        println!("Received message: {}", msg);
     }
     Ok(())
 }
@@ -83,7 +777,9 @@ fn handle_connection(mut stream: TcpStream) -> std::io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
+    use crate::consensus::block_producer::Block;
+    use crate::utils::serialization::TransactionBuilder;
+    use std::io::{Read, Write};
 
     #[test]
     fn test_network_node_send_receive() {
@@ -111,4 +807,578 @@ mod tests {
         let mut buf = [0u8; 512];
         let _ = stream.read(&mut buf).unwrap_or(0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_request_ping_pong() {
+        let responder = NetworkNode::new(0).expect("Failed to bind responder");
+        let port = responder.listener.local_addr().unwrap().port();
+        thread::spawn(move || responder.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let client = NetworkNode::new(0).expect("Failed to bind client");
+        let reply = client
+            .request(&format!("127.0.0.1:{}", port), &NetMessage::Ping, Duration::from_secs(2))
+            .expect("Request failed");
+        assert_eq!(reply, NetMessage::Pong);
+    }
+
+    #[test]
+    fn test_handler_decodes_coalesced_and_split_frames() {
+        let responder = NetworkNode::new(0).expect("Failed to bind responder");
+        let port = responder.listener.local_addr().unwrap().port();
+        thread::spawn(move || responder.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let mut wire = Vec::new();
+        write_frame(&mut wire, &NodeServices::default().hello()).unwrap();
+        for _ in 0..3 {
+            write_frame(&mut wire, &NetMessage::Ping).unwrap();
+        }
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        // Two frames plus half of the third in one write, the rest later.
+        let split = wire.len() - 10;
+        stream.write_all(&wire[..split]).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        stream.write_all(&wire[split..]).unwrap();
+        assert!(matches!(read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap(), NetMessage::Hello { .. }));
+        for _ in 0..3 {
+            assert_eq!(read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap(), NetMessage::Pong);
+        }
+    }
+
+    #[test]
+    fn test_request_times_out_on_silent_peer() {
+        // A raw listener that accepts but never answers.
+        let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = silent.local_addr().unwrap();
+        let client = NetworkNode::new(0).expect("Failed to bind client");
+        let err = client
+            .request(&addr.to_string(), &NetMessage::Ping, Duration::from_millis(200))
+            .expect_err("Silent peer should time out");
+        assert!(matches!(err, NetError::Timeout));
+        drop(silent);
+    }
+
+    fn seeded_chain(blocks: u64) -> ChainManager {
+        let mut chain = ChainManager::new();
+        for _ in 0..blocks {
+            let mut block = Block {
+                block_number: chain.height() + 1,
+                previous_hash: chain.head().hash(),
+                transactions: Vec::new(),
+                tx_count: 0,
+                merkle_root: [0u8; 32],
+                timestamp: chain.height(),
+                signature: Vec::new(),
+            };
+            block.commit_transactions();
+            chain.append_block(block).unwrap();
+        }
+        chain
+    }
+
+    #[test]
+    fn test_sync_from_peer() {
+        // More than one batch so the sync loops before stopping on a short reply.
+        let source_height = SYNC_BATCH_SIZE as u64 * 2 + 5;
+        let source_chain = Arc::new(Mutex::new(seeded_chain(source_height)));
+        let mut source = NetworkNode::new(0).expect("Failed to bind source");
+        source.serve_chain(source_chain.clone());
+        let port = source.listener.local_addr().unwrap().port();
+        thread::spawn(move || source.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let node = NetworkNode::new(0).expect("Failed to bind node");
+        let mut chain = ChainManager::new();
+        let height = node.sync_from(&format!("127.0.0.1:{}", port), &mut chain).expect("Sync failed");
+        assert_eq!(height, source_height);
+        assert_eq!(chain.head(), source_chain.lock().unwrap().head());
+    }
+
+    #[test]
+    fn test_sync_penalizes_peer_serving_invalid_blocks() {
+        let mut source = NetworkNode::new(0).expect("Failed to bind source");
+        source.serve_chain(Arc::new(Mutex::new(seeded_chain(3))));
+        let port = source.listener.local_addr().unwrap().port();
+        thread::spawn(move || source.run());
+        thread::sleep(Duration::from_millis(100));
+
+        // A local block 1 that differs from the source's, so its block 2 does not link.
+        let mut chain = ChainManager::new();
+        let mut fork = Block {
+            block_number: 1,
+            previous_hash: chain.head().hash(),
+            transactions: Vec::new(),
+            tx_count: 0,
+            merkle_root: [0u8; 32],
+            timestamp: 99,
+            signature: Vec::new(),
+        };
+        fork.commit_transactions();
+        chain.append_block(fork).unwrap();
+
+        let mut node = NetworkNode::new(0).expect("Failed to bind node");
+        node.set_ban_policy(60, Duration::from_secs(60));
+        let result = node.sync_from(&format!("127.0.0.1:{}", port), &mut chain);
+        assert!(matches!(result, Err(NetError::InvalidBlock(_))), "{:?}", result);
+        assert_eq!(chain.height(), 1);
+        assert!(node.is_banned(IpAddr::from([127, 0, 0, 1])));
+    }
+
+    #[test]
+    fn test_gossip_reaches_all_nodes() {
+        // Line topology A <-> B <-> C: C only hears about the transaction via B's relay.
+        let mut nodes = Vec::new();
+        let mut mempools = Vec::new();
+        let mut addrs = Vec::new();
+        for _ in 0..3 {
+            let mut node = NetworkNode::new(0).expect("Failed to bind node");
+            let mempool = Arc::new(ConcurrentMempool::default());
+            node.serve_mempool(mempool.clone());
+            addrs.push(format!("127.0.0.1:{}", node.listener.local_addr().unwrap().port()));
+            mempools.push(mempool);
+            nodes.push(node);
+        }
+        nodes[0].add_peer(&addrs[1]);
+        nodes[1].add_peer(&addrs[0]);
+        nodes[1].add_peer(&addrs[2]);
+        nodes[2].add_peer(&addrs[1]);
+        let node_a = nodes.remove(0);
+        for node in nodes {
+            thread::spawn(move || node.run());
+        }
+        thread::sleep(Duration::from_millis(100));
+
+        let tx = TransactionBuilder::new()
+            .id(77)
+            .amount(1000)
+            .fee(5.0)
+            .sender("Alice")
+            .recipient("Bob")
+            .signature(vec![1, 2, 3, 4])
+            .build()
+            .unwrap();
+        assert!(mempools[0].add_transaction(tx.clone()));
+        node_a.gossip_transaction(&tx);
+
+        for _ in 0..50 {
+            if mempools[1].contains(77) && mempools[2].contains(77) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(mempools[1].contains(77));
+        assert!(mempools[2].contains(77));
+        // Relays back to nodes that already have the transaction are deduplicated.
+        assert_eq!(mempools[0].size(), 1);
+        assert_eq!(mempools[1].size(), 1);
+        assert_eq!(mempools[2].size(), 1);
+    }
+
+    #[test]
+    fn test_gossip_is_not_relayed_back_to_its_origin() {
+        let origin = TcpListener::bind("127.0.0.1:0").unwrap();
+        let other = TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin_addr = origin.local_addr().unwrap().to_string();
+        let mut node = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind node");
+        node.serve_mempool(Arc::new(ConcurrentMempool::default()));
+        node.add_peer(&origin_addr);
+        node.add_peer(&other.local_addr().unwrap().to_string());
+        let port = node.listener.local_addr().unwrap().port();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        // The origin announces its listening address as its node id, as `NetworkNode` does by default.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let hello = NetMessage::Hello { protocol_version: PROTOCOL_VERSION, node_id: origin_addr };
+        write_frame(&mut stream, &hello).unwrap();
+        let tx = TransactionBuilder::new()
+            .id(78)
+            .amount(1000)
+            .fee(5.0)
+            .sender("Alice")
+            .recipient("Bob")
+            .signature(vec![1, 2, 3, 4])
+            .build()
+            .unwrap();
+        write_frame(&mut stream, &NetMessage::NewTransaction(tx)).unwrap();
+
+        // The relay reaches the other peer; the origin gets nothing.
+        other.accept().expect("relay to the other peer");
+        thread::sleep(Duration::from_millis(100));
+        origin.set_nonblocking(true).unwrap();
+        assert_eq!(origin.accept().map_err(|e| e.kind()).err(), Some(io::ErrorKind::WouldBlock));
+    }
+
+    #[test]
+    fn test_validator_set_gossip_merges_into_chain() {
+        let chain = Arc::new(Mutex::new(ChainManager::new()));
+        chain.lock().unwrap().add_validator("A".to_string(), 100).unwrap();
+        let mut receiver = NetworkNode::new(0).expect("Failed to bind receiver");
+        receiver.serve_chain(chain.clone());
+        let addr = format!("127.0.0.1:{}", receiver.listener.local_addr().unwrap().port());
+        thread::spawn(move || receiver.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let sender = NetworkNode::new(0).expect("Failed to bind sender");
+        sender.add_peer(&addr);
+        let validator = |id: &str, stake_amount| Validator { id: id.into(), stake_amount, puzzle_passed: true, jailed: false, last_solution: None };
+        sender.gossip_validators(&[validator("A", 250), validator("B", 80)]);
+
+        for _ in 0..50 {
+            if chain.lock().unwrap().validators.len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        let stakes: Vec<(String, u64)> =
+            chain.lock().unwrap().validators.iter().map(|v| (v.id.clone(), v.stake_amount)).collect();
+        assert_eq!(stakes, vec![("A".to_string(), 250), ("B".to_string(), 80)]);
+    }
+
+    #[test]
+    fn test_heartbeat_gossip_marks_validator_live() {
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let tracker = Arc::new(Mutex::new(LivenessTracker::new()));
+        tracker.lock().unwrap().register("A", key.verifying_key());
+        let mut receiver = NetworkNode::new(0).expect("Failed to bind receiver");
+        receiver.serve_liveness(tracker.clone());
+        let addr = format!("127.0.0.1:{}", receiver.listener.local_addr().unwrap().port());
+        thread::spawn(move || receiver.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let sender = NetworkNode::new(0).expect("Failed to bind sender");
+        sender.add_peer(&addr);
+        sender.gossip_heartbeat("A", 5, &key);
+
+        let max_silence = Duration::from_secs(60);
+        for _ in 0..50 {
+            if tracker.lock().unwrap().is_live("A", SystemClock.now_secs(), max_silence) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(tracker.lock().unwrap().is_live("A", SystemClock.now_secs(), max_silence));
+        assert_eq!(tracker.lock().unwrap().last_heartbeat("A").map(|h| h.height), Some(5));
+    }
+
+    #[test]
+    fn test_oversized_frame_drops_connection() {
+        let mut node = NetworkNode::new(0).expect("Failed to bind node");
+        node.set_max_message_size(1024);
+        let port = node.listener.local_addr().unwrap().port();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        stream.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        // The node closes the connection instead of waiting for 4 GiB of payload.
+        let mut buf = [0u8; 1];
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_repeated_violations_ban_peer_until_expiry() {
+        let mut node = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind node");
+        node.set_max_message_size(1024);
+        node.set_ban_policy(0, Duration::from_millis(500));
+        let port = node.listener.local_addr().unwrap().port();
+        let node = Arc::new(node);
+        let server = Arc::clone(&node);
+        thread::spawn(move || server.run());
+        thread::sleep(Duration::from_millis(100));
+
+        // Each oversized frame costs 40 of the starting 100 points.
+        for _ in 0..3 {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+            stream.write_all(&u32::MAX.to_le_bytes()).unwrap();
+            let mut buf = [0u8; 1];
+            assert_eq!(stream.read(&mut buf).unwrap(), 0);
+        }
+        let localhost = IpAddr::from([127, 0, 0, 1]);
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !node.is_banned(localhost) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(node.is_banned(localhost));
+
+        // A well-behaved reconnect is refused while the ban lasts.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let _ = write_frame(&mut stream, &NodeServices::default().hello());
+        assert!(read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).is_err());
+
+        thread::sleep(Duration::from_millis(500));
+        assert!(!node.is_banned(localhost));
+        let reply = node.request(&format!("127.0.0.1:{}", port), &NetMessage::Ping, Duration::from_secs(2));
+        assert_eq!(reply.unwrap(), NetMessage::Pong);
+    }
+
+    #[test]
+    fn test_send_with_retry_waits_for_peer() {
+        // Reserve a free port, then release it so the first attempts are refused.
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let peer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(120));
+            let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            let hello = read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap();
+            assert!(matches!(hello, NetMessage::Hello { .. }));
+            read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap()
+        });
+
+        let mut client = NetworkNode::new(0).expect("Failed to bind client");
+        client.set_retry_backoff(Duration::from_millis(50), Duration::from_millis(400));
+        client
+            .send_with_retry(&format!("127.0.0.1:{}", port), &NetMessage::Ping, 6)
+            .expect("Retry should eventually succeed");
+        assert_eq!(peer.join().unwrap(), NetMessage::Ping);
+    }
+
+    #[test]
+    fn test_send_with_retry_returns_last_error() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let mut client = NetworkNode::new(0).expect("Failed to bind client");
+        client.set_retry_backoff(Duration::from_millis(1), Duration::from_millis(2));
+        let err = client
+            .send_with_retry(&format!("127.0.0.1:{}", port), &NetMessage::Ping, 3)
+            .expect_err("No peer is listening");
+        assert!(matches!(err, NetError::Io(e) if e.kind() == io::ErrorKind::ConnectionRefused));
+    }
+
+    #[test]
+    fn test_send_with_zero_attempts_sends_nothing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = NetworkNode::new(0).expect("Failed to bind client");
+        let err = client
+            .send_with_retry(&listener.local_addr().unwrap().to_string(), &NetMessage::Ping, 0)
+            .expect_err("No attempt is made");
+        assert!(matches!(err, NetError::Io(e) if e.kind() == io::ErrorKind::InvalidInput));
+        listener.set_nonblocking(true).unwrap();
+        assert_eq!(listener.accept().map_err(|e| e.kind()).err(), Some(io::ErrorKind::WouldBlock));
+    }
+
+    #[test]
+    fn test_connections_beyond_limit_refused() {
+        let mut node = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind node");
+        node.set_connection_limits(2, 100, Duration::from_secs(60));
+        let port = node.listener.local_addr().unwrap().port();
+        let node = Arc::new(node);
+        let server = Arc::clone(&node);
+        thread::spawn(move || server.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let answered = |stream: &mut TcpStream| {
+            read_frame(stream, DEFAULT_MAX_MESSAGE_SIZE).is_ok_and(|m| matches!(m, NetMessage::Hello { .. }))
+                && read_frame(stream, DEFAULT_MAX_MESSAGE_SIZE).is_ok_and(|m| m == NetMessage::Pong)
+        };
+        let mut held: Vec<TcpStream> = (0..2).map(|_| handshake_and_ping(port, PROTOCOL_VERSION)).collect();
+        assert!(held.iter_mut().all(answered));
+        assert_eq!(node.active_connections(), 2);
+
+        for _ in 0..3 {
+            let mut refused = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            refused.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+            let _ = write_frame(&mut refused, &NodeServices::default().hello());
+            assert!(read_frame(&mut refused, DEFAULT_MAX_MESSAGE_SIZE).is_err());
+        }
+
+        // Closing a connection frees its slot for the next one.
+        held.pop();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while node.active_connections() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(answered(&mut handshake_and_ping(port, PROTOCOL_VERSION)));
+    }
+
+    #[test]
+    fn test_connection_rate_limited_per_ip() {
+        let services = NodeServices {
+            max_connections_per_ip: 2,
+            connection_rate_window: Duration::from_secs(60),
+            ..NodeServices::default()
+        };
+        let (a, b) = (IpAddr::from([10, 0, 0, 1]), IpAddr::from([10, 0, 0, 2]));
+        // Closed connections still count towards the rate.
+        drop(services.admit(a).unwrap());
+        drop(services.admit(a).unwrap());
+        assert_eq!(services.admit(a).err(), Some("connection rate limit exceeded"));
+        assert!(services.admit(b).is_ok());
+
+        let services = NodeServices { connection_rate_window: Duration::ZERO, ..services };
+        assert!(services.admit(a).is_ok());
+    }
+
+    /// Connects to `port`, sends a `Hello` claiming `version`, then a `Ping`.
+    fn handshake_and_ping(port: u16, version: u32) -> TcpStream {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        write_frame(&mut stream, &NetMessage::Hello { protocol_version: version, node_id: "peer".into() }).unwrap();
+        write_frame(&mut stream, &NetMessage::Ping).unwrap();
+        stream
+    }
+
+    #[test]
+    fn test_handshake_rejects_unsupported_version() {
+        let mut node = NetworkNode::new(0).expect("Failed to bind node");
+        node.set_protocol_version(1, 1..=1);
+        node.set_node_id("node-v1");
+        let port = node.listener.local_addr().unwrap().port();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        // A peer from the future learns the node's version, then is disconnected
+        // without its Ping being answered.
+        let mut rejected = handshake_and_ping(port, 99);
+        assert!(matches!(
+            read_frame(&mut rejected, DEFAULT_MAX_MESSAGE_SIZE).unwrap(),
+            NetMessage::Hello { protocol_version: 1, .. }
+        ));
+        let mut buf = [0u8; 1];
+        assert_eq!(rejected.read(&mut buf).unwrap(), 0);
+
+        // A compatible peer gets the node's Hello, then the Ping is answered.
+        let mut accepted = handshake_and_ping(port, 1);
+        assert_eq!(
+            read_frame(&mut accepted, DEFAULT_MAX_MESSAGE_SIZE).unwrap(),
+            NetMessage::Hello { protocol_version: 1, node_id: "node-v1".into() }
+        );
+        assert_eq!(read_frame(&mut accepted, DEFAULT_MAX_MESSAGE_SIZE).unwrap(), NetMessage::Pong);
+    }
+
+    #[test]
+    fn test_negotiate_picks_lower_version() {
+        let services = NodeServices { protocol_version: 3, supported_versions: 1..=3, ..NodeServices::default() };
+        let session = services.negotiate(&NetMessage::Hello { protocol_version: 2, node_id: "old".into() }).unwrap();
+        assert_eq!(session, PeerSession { node_id: "old".into(), protocol_version: 2 });
+        assert!(services.negotiate(&NetMessage::Ping).is_err());
+    }
+
+    /// Starts a node with a short keep-alive and completes the handshake with it.
+    fn keepalive_peer() -> TcpStream {
+        let mut node = NetworkNode::new(0).expect("Failed to bind node");
+        node.set_keepalive(Duration::from_millis(50), Duration::from_millis(150));
+        let port = node.listener.local_addr().unwrap().port();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        write_frame(&mut stream, &NodeServices::default().hello()).unwrap();
+        assert!(matches!(read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap(), NetMessage::Hello { .. }));
+        stream
+    }
+
+    #[test]
+    fn test_keepalive_answered_pings_keep_connection_open() {
+        let mut stream = keepalive_peer();
+        let mut pings = 0;
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_millis(500) {
+            assert_eq!(read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap(), NetMessage::Ping);
+            write_frame(&mut stream, &NetMessage::Pong).unwrap();
+            pings += 1;
+        }
+        assert!(pings >= 2, "only {} pings", pings);
+
+        // Still open: a request is answered (possibly after another keep-alive ping).
+        write_frame(&mut stream, &NetMessage::Ping).unwrap();
+        loop {
+            match read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap() {
+                NetMessage::Pong => break,
+                NetMessage::Ping => write_frame(&mut stream, &NetMessage::Pong).unwrap(),
+                other => panic!("unexpected message {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_keepalive_silent_peer_disconnected() {
+        let mut stream = keepalive_peer();
+        let started = Instant::now();
+        assert_eq!(read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).unwrap(), NetMessage::Ping);
+        // Never answer; the node closes the connection after the pong timeout.
+        let err = read_frame(&mut stream, DEFAULT_MAX_MESSAGE_SIZE).expect_err("connection should close");
+        assert!(matches!(err, NetError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+        assert!(started.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_read_timeout_closes_silent_connection() {
+        let mut node = NetworkNode::new(0).expect("Failed to bind node");
+        node.set_io_timeouts(Duration::from_millis(200), Duration::from_secs(1));
+        let port = node.listener.local_addr().unwrap().port();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        // Connect and send nothing, not even a Hello.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let started = Instant::now();
+        let mut buf = [0u8; 1];
+        assert_eq!(stream.read(&mut buf).expect("connection should close, not time out"), 0);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_bind_specific_address() {
+        let node = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind loopback");
+        let local = node.listener.local_addr().unwrap();
+        assert_eq!(local.ip(), std::net::IpAddr::from([127, 0, 0, 1]));
+        assert_ne!(local.port(), 0);
+
+        let any = NetworkNode::new(0).expect("Failed to bind all interfaces");
+        assert!(any.listener.local_addr().unwrap().ip().is_unspecified());
+
+        assert!(NetworkNode::bind("not an address").is_err());
+    }
+
+    #[test]
+    fn test_request_reports_protocol_mismatch() {
+        let mut node = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind node");
+        node.set_protocol_version(1, 1..=1);
+        let addr = node.listener.local_addr().unwrap().to_string();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let mut client = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind client");
+        client.set_protocol_version(99, 99..=99);
+        let err = client.request(&addr, &NetMessage::Ping, Duration::from_secs(2)).expect_err("versions differ");
+        assert!(matches!(err, NetError::ProtocolMismatch { theirs: 1, ours: 99 }), "{:?}", err);
+    }
+
+    #[test]
+    fn test_oversized_reply_is_message_too_large() {
+        let mut node = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind node");
+        node.serve_chain(Arc::new(Mutex::new(seeded_chain(20))));
+        let addr = node.listener.local_addr().unwrap().to_string();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let mut client = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind client");
+        client.set_max_message_size(256);
+        let request = NetMessage::GetBlocks { from_height: 1, count: 20 };
+        let err = client.request(&addr, &request, Duration::from_secs(2)).expect_err("reply exceeds limit");
+        assert!(matches!(err, NetError::MessageTooLarge { max: 256, .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn test_get_blocks_reply_is_capped() {
+        let mut node = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind node");
+        node.serve_chain(Arc::new(Mutex::new(seeded_chain(MAX_BLOCKS_PER_RESPONSE as u64 + 10))));
+        let addr = node.listener.local_addr().unwrap().to_string();
+        thread::spawn(move || node.run());
+        thread::sleep(Duration::from_millis(100));
+
+        let client = NetworkNode::bind("127.0.0.1:0").expect("Failed to bind client");
+        let request = NetMessage::GetBlocks { from_height: 1, count: u32::MAX };
+        match client.request(&addr, &request, Duration::from_secs(2)).expect("Request failed") {
+            NetMessage::Blocks(blocks) => assert_eq!(blocks.len(), MAX_BLOCKS_PER_RESPONSE as usize),
+            other => panic!("unexpected reply: {:?}", other),
+        }
+    }
+}
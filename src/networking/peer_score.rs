@@ -0,0 +1,180 @@
+// File: src/networking/peer_score.rs
+//! Misbehaviour scoring for peers.
+//!
+//! Every peer address starts at `DEFAULT_PEER_SCORE`. Protocol violations
+//! subtract a penalty and well-formed messages add a point back, up to the
+//! starting score. A peer whose score drops below the ban threshold is banned
+//! for the ban duration and starts afresh once the ban expires.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Score every peer starts with, and the most good behaviour can restore.
+pub const DEFAULT_PEER_SCORE: i32 = 100;
+
+/// A peer is banned once its score falls below this value.
+pub const DEFAULT_BAN_THRESHOLD: i32 = 0;
+
+/// Default time a banned address is refused.
+pub const DEFAULT_BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// A protocol violation that costs a peer score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// A frame failed its checksum or did not decode to a message.
+    MalformedFrame,
+    /// A frame declared a length above the maximum message size.
+    OversizedMessage,
+    /// A signed message carried a signature that does not verify.
+    InvalidSignature,
+    /// A block served during sync failed validation.
+    InvalidBlock,
+}
+
+impl Violation {
+    /// Points subtracted from the peer's score.
+    pub fn penalty(self) -> i32 {
+        match self {
+            Violation::MalformedFrame => 20,
+            Violation::OversizedMessage => 40,
+            Violation::InvalidSignature => 30,
+            Violation::InvalidBlock => 50,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::MalformedFrame => write!(f, "malformed frame"),
+            Violation::OversizedMessage => write!(f, "oversized message"),
+            Violation::InvalidSignature => write!(f, "invalid signature"),
+            Violation::InvalidBlock => write!(f, "invalid block"),
+        }
+    }
+}
+
+/// Scores and bans keyed by peer IP address, so reconnecting from a new port
+/// does not reset either.
+#[derive(Debug)]
+pub struct PeerScore {
+    scores: HashMap<IpAddr, i32>,
+    banned_until: HashMap<IpAddr, Instant>,
+    ban_threshold: i32,
+    ban_duration: Duration,
+}
+
+impl Default for PeerScore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PeerScore {
+    /// Creates an empty score table with the default ban threshold and duration.
+    pub fn new() -> Self {
+        Self {
+            scores: HashMap::new(),
+            banned_until: HashMap::new(),
+            ban_threshold: DEFAULT_BAN_THRESHOLD,
+            ban_duration: DEFAULT_BAN_DURATION,
+        }
+    }
+
+    /// Sets the score below which a peer is banned.
+    pub fn with_ban_threshold(mut self, ban_threshold: i32) -> Self {
+        self.ban_threshold = ban_threshold;
+        self
+    }
+
+    /// Sets how long a banned address is refused.
+    pub fn with_ban_duration(mut self, ban_duration: Duration) -> Self {
+        self.ban_duration = ban_duration;
+        self
+    }
+
+    /// Returns the current score of `peer`.
+    pub fn score(&self, peer: IpAddr) -> i32 {
+        self.scores.get(&peer).copied().unwrap_or(DEFAULT_PEER_SCORE)
+    }
+
+    /// Subtracts `violation`'s penalty from `peer`'s score at time `now`,
+    /// banning the peer if the score falls below the threshold. Returns true
+    /// if the peer is banned afterwards.
+    pub fn penalize(&mut self, peer: IpAddr, violation: Violation, now: Instant) -> bool {
+        self.banned_until.retain(|_, until| *until > now);
+        let score = self.scores.entry(peer).or_insert(DEFAULT_PEER_SCORE);
+        *score = score.saturating_sub(violation.penalty());
+        if *score < self.ban_threshold {
+            // The score resets so the peer starts afresh when the ban expires.
+            self.scores.remove(&peer);
+            self.banned_until.insert(peer, now + self.ban_duration);
+        }
+        self.is_banned(peer, now)
+    }
+
+    /// Adds a point to `peer`'s score for a well-formed message.
+    pub fn reward(&mut self, peer: IpAddr) {
+        if let Some(score) = self.scores.get_mut(&peer) {
+            *score = (*score + 1).min(DEFAULT_PEER_SCORE);
+            if *score == DEFAULT_PEER_SCORE {
+                self.scores.remove(&peer);
+            }
+        }
+    }
+
+    /// Returns true if `peer` is banned at time `now`.
+    pub fn is_banned(&self, peer: IpAddr, now: Instant) -> bool {
+        self.banned_until.get(&peer).is_some_and(|until| now < *until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const PEER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    #[test]
+    fn test_repeated_violations_ban_peer() {
+        let mut scores = PeerScore::new();
+        let now = Instant::now();
+        for _ in 0..5 {
+            assert!(!scores.penalize(PEER, Violation::MalformedFrame, now));
+        }
+        assert_eq!(scores.score(PEER), 0);
+        assert!(scores.penalize(PEER, Violation::MalformedFrame, now));
+        assert!(scores.is_banned(PEER, now));
+        assert!(!scores.is_banned(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), now));
+    }
+
+    #[test]
+    fn test_ban_expires_with_fresh_score() {
+        let mut scores = PeerScore::new().with_ban_duration(Duration::from_secs(30));
+        let now = Instant::now();
+        for _ in 0..3 {
+            scores.penalize(PEER, Violation::OversizedMessage, now);
+        }
+        assert!(scores.is_banned(PEER, now + Duration::from_secs(29)));
+        assert!(!scores.is_banned(PEER, now + Duration::from_secs(30)));
+        assert_eq!(scores.score(PEER), DEFAULT_PEER_SCORE);
+    }
+
+    #[test]
+    fn test_good_behaviour_recovers_score() {
+        let mut scores = PeerScore::new();
+        scores.penalize(PEER, Violation::InvalidSignature, Instant::now());
+        assert_eq!(scores.score(PEER), 70);
+        for _ in 0..10 {
+            scores.reward(PEER);
+        }
+        assert_eq!(scores.score(PEER), 80);
+        for _ in 0..100 {
+            scores.reward(PEER);
+        }
+        assert_eq!(scores.score(PEER), DEFAULT_PEER_SCORE);
+    }
+}
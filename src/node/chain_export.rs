@@ -0,0 +1,251 @@
+//! Flat-file chain export with a height index.
+//!
+//! `ChainExporter` writes blocks one after another, each as a
+//! Blake3-checksummed `Serializer` frame, then appends an index of
+//! (height, file offset) pairs and a fixed-size trailer locating it.
+//! `ChainReader` loads the index from the trailer and seeks straight to the
+//! requested block, so any block can be read without parsing those before it.
+//!
+//! Layout, all integers little-endian:
+//!
+//! ```text
+//! magic (8) | block frames ... | index: count × (height u64, offset u64)
+//!           | index offset u64 | count u64 | Blake3 of index (32) | magic (8)
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::consensus::block_producer::Block;
+use crate::utils::serialization::{Endianness, Serializer};
+
+/// Marks the start and end of an exported chain file.
+pub const EXPORT_MAGIC: [u8; 8] = *b"REINACHN";
+
+/// Bytes per index entry: height and offset.
+const INDEX_ENTRY_LEN: u64 = 16;
+
+/// Bytes of the trailer: index offset, entry count, index hash, and magic.
+const TRAILER_LEN: u64 = 8 + 8 + 32 + 8;
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Writes blocks to an export file. Call `finish` to write the index; a file
+/// without one cannot be opened by `ChainReader`.
+pub struct ChainExporter<W: Write> {
+    writer: W,
+    /// Bytes written so far, i.e. the offset of the next block.
+    offset: u64,
+    /// (height, offset) of every block written, in write order.
+    index: Vec<(u64, u64)>,
+}
+
+impl<W: Write> ChainExporter<W> {
+    /// Starts an export into `writer` by writing the file magic.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(&EXPORT_MAGIC)?;
+        Ok(Self { writer, offset: EXPORT_MAGIC.len() as u64, index: Vec::new() })
+    }
+
+    /// Appends `block` and records its offset under its block number.
+    pub fn write_block(&mut self, block: &Block) -> io::Result<()> {
+        let frame = Serializer::serialize(block, Endianness::Little).map_err(|e| invalid_data(e.to_string()))?;
+        self.writer.write_all(&frame)?;
+        self.index.push((block.block_number, self.offset));
+        self.offset += frame.len() as u64;
+        Ok(())
+    }
+
+    /// Writes the index and trailer, flushes, and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut index = Vec::with_capacity(self.index.len() * INDEX_ENTRY_LEN as usize);
+        for (height, offset) in &self.index {
+            index.extend_from_slice(&height.to_le_bytes());
+            index.extend_from_slice(&offset.to_le_bytes());
+        }
+        self.writer.write_all(&index)?;
+        self.writer.write_all(&self.offset.to_le_bytes())?;
+        self.writer.write_all(&(self.index.len() as u64).to_le_bytes())?;
+        self.writer.write_all(blake3::hash(&index).as_bytes())?;
+        self.writer.write_all(&EXPORT_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Random access to the blocks of an exported chain file.
+pub struct ChainReader<R: Read + Seek> {
+    reader: R,
+    /// Block number mapped to the offset of its frame.
+    index: HashMap<u64, u64>,
+    /// Offset where the block frames end and the index begins.
+    blocks_end: u64,
+}
+
+impl ChainReader<BufReader<File>> {
+    /// Opens the export file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> ChainReader<R> {
+    /// Reads and verifies the trailer and index of an export in `reader`.
+    /// A missing magic or an index that fails its checksum is reported as
+    /// `io::ErrorKind::InvalidData`.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < EXPORT_MAGIC.len() as u64 + TRAILER_LEN {
+            return Err(invalid_data("File too short for a chain export"));
+        }
+        let mut magic = [0u8; 8];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut magic)?;
+        let mut trailer = [0u8; TRAILER_LEN as usize];
+        reader.seek(SeekFrom::Start(len - TRAILER_LEN))?;
+        reader.read_exact(&mut trailer)?;
+        if magic != EXPORT_MAGIC || trailer[48..] != EXPORT_MAGIC {
+            return Err(invalid_data("Not a chain export"));
+        }
+        let blocks_end = u64::from_le_bytes(trailer[..8].try_into().expect("8-byte slice"));
+        let count = u64::from_le_bytes(trailer[8..16].try_into().expect("8-byte slice"));
+        let index_len = count
+            .checked_mul(INDEX_ENTRY_LEN)
+            .filter(|&index_len| blocks_end.checked_add(index_len) == Some(len - TRAILER_LEN))
+            .ok_or_else(|| invalid_data("Index does not fit between blocks and trailer"))?;
+        let mut index_bytes = vec![0u8; index_len as usize];
+        reader.seek(SeekFrom::Start(blocks_end))?;
+        reader.read_exact(&mut index_bytes)?;
+        if blake3::hash(&index_bytes).as_bytes() != &trailer[16..48] {
+            return Err(invalid_data("Index checksum mismatch"));
+        }
+        let index = index_bytes
+            .chunks_exact(INDEX_ENTRY_LEN as usize)
+            .map(|entry| {
+                let height = u64::from_le_bytes(entry[..8].try_into().expect("8-byte slice"));
+                let offset = u64::from_le_bytes(entry[8..].try_into().expect("8-byte slice"));
+                (height, offset)
+            })
+            .collect();
+        Ok(Self { reader, index, blocks_end })
+    }
+
+    /// Returns the number of blocks in the export.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the export holds no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Reads the block with block number `height`, verifying its checksum.
+    /// Returns `io::ErrorKind::NotFound` if the export has no such block and
+    /// `io::ErrorKind::InvalidData` if its frame is corrupt.
+    pub fn block_at(&mut self, height: u64) -> io::Result<Block> {
+        let offset = *self
+            .index
+            .get(&height)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No block at height {}", height)))?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut prefix = [0u8; 4];
+        self.reader.read_exact(&mut prefix)?;
+        let frame_len = 4 + u64::from(u32::from_le_bytes(prefix));
+        if offset.checked_add(frame_len).is_none_or(|end| end > self.blocks_end) {
+            return Err(invalid_data(format!("Block {} runs past the end of the block data", height)));
+        }
+        let mut frame = vec![0u8; frame_len as usize];
+        frame[..4].copy_from_slice(&prefix);
+        self.reader.read_exact(&mut frame[4..])?;
+        let block: Block =
+            Serializer::deserialize(&frame, Endianness::Little).map_err(|e| invalid_data(e.to_string()))?;
+        if block.block_number != height {
+            return Err(invalid_data(format!("Index entry {} points at block {}", height, block.block_number)));
+        }
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::serialization::TransactionBuilder;
+    use std::io::Cursor;
+
+    fn export(blocks: &[Block]) -> Vec<u8> {
+        let mut exporter = ChainExporter::new(Vec::new()).unwrap();
+        for block in blocks {
+            exporter.write_block(block).unwrap();
+        }
+        exporter.finish().unwrap()
+    }
+
+    fn blocks(count: u64) -> Vec<Block> {
+        (0..count)
+            .map(|height| Block {
+                block_number: height,
+                previous_hash: [height as u8; 32],
+                transactions: (0..height % 4)
+                    .map(|i| {
+                        TransactionBuilder::new()
+                            .id(height * 10 + i)
+                            .amount(100 + i)
+                            .fee(1.0)
+                            .sender("Alice")
+                            .recipient("Bob")
+                            .build()
+                            .unwrap()
+                    })
+                    .collect(),
+                tx_count: height % 4,
+                merkle_root: [height as u8; 32],
+                timestamp: 1_700_000_000 + height,
+                signature: vec![height as u8; 64],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_reads_blocks_by_height() {
+        let blocks = blocks(50);
+        let mut reader = ChainReader::new(Cursor::new(export(&blocks))).unwrap();
+        assert_eq!(reader.len(), 50);
+        for height in [37, 0, 49, 12, 3, 25, 12] {
+            assert_eq!(reader.block_at(height).unwrap(), blocks[height as usize]);
+        }
+        assert_eq!(reader.block_at(50).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_corrupted_block_fails_checksum() {
+        let blocks = blocks(50);
+        let mut bytes = export(&blocks);
+        let offset = {
+            let reader = ChainReader::new(Cursor::new(bytes.clone())).unwrap();
+            reader.index[&20] as usize
+        };
+        // Flip a byte inside block 20's payload, past its length prefix.
+        bytes[offset + 10] ^= 0xFF;
+        let mut reader = ChainReader::new(Cursor::new(bytes)).unwrap();
+        let err = reader.block_at(20).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        // Neighbouring blocks are unaffected.
+        assert_eq!(reader.block_at(19).unwrap(), blocks[19]);
+        assert_eq!(reader.block_at(21).unwrap(), blocks[21]);
+    }
+
+    #[test]
+    fn test_corrupted_index_rejected() {
+        let mut bytes = export(&blocks(5));
+        let index_start = bytes.len() - TRAILER_LEN as usize - 5 * INDEX_ENTRY_LEN as usize;
+        bytes[index_start + 8] ^= 1;
+        let err = ChainReader::new(Cursor::new(bytes)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
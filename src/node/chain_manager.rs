@@ -1,51 +1,1527 @@
 //! Minimal ChainManager for PoCUP.
-//! Manages a list of validators and runs PoCUP tasks on them.
+//! Manages a list of validators, runs PoCUP tasks on them, and holds the
+//! node's chain of accepted blocks, plus candidate branches for fork choice.
 
-use crate::pocup::pocup::{Validator, perform_useful_work, slash_if_needed};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::marker::PhantomData;
+use std::path::Path;
 
-/// ChainManager holds a list of PoCUP validators.
-pub struct ChainManager {
+use log::info;
+
+use crate::consensus::block_producer::Block;
+use crate::consensus::difficulty::MAX_DIFFICULTY;
+use crate::node::chain_export::ChainExporter;
+use crate::pocup::equivocation::{slash_for_equivocation, Evidence};
+use crate::pocup::pocup::{
+    perform_useful_work_with, slash_if_needed, verify_useful_work_with, Validator, DEFAULT_PUZZLE_DIFFICULTY,
+};
+use crate::roc::sentinel::{check_spam_with, SpamPolicy};
+use crate::utils::hasher::{merkle_root, Blake3Hasher, Hasher};
+use crate::utils::serialization::{
+    Decode, Encode, Endianness, SerializationError, SerializationResult, Serializer,
+};
+
+/// Default number of blocks per epoch.
+pub const DEFAULT_EPOCH_LENGTH: u64 = 100;
+
+/// Default minimum stake a validator needs to be in an epoch's active set.
+pub const DEFAULT_MIN_ACTIVE_STAKE: u64 = 1;
+
+/// Default reward minted for the proposer of each block, on top of fees.
+pub const DEFAULT_BLOCK_REWARD: u64 = 10;
+
+/// Default cap on a block's encoded size, signature included (1 MiB).
+pub const DEFAULT_MAX_BLOCK_BYTES: usize = 1024 * 1024;
+
+/// Previous hash recorded in the genesis block.
+pub const GENESIS_PREVIOUS_HASH: [u8; 32] = [0u8; 32];
+
+/// Reasons a block can be refused by `ChainManager::validate_block`.
+#[derive(Debug, PartialEq)]
+pub enum BlockValidationError {
+    /// The block number is not exactly one above the current head.
+    UnexpectedBlockNumber { expected: u64, found: u64 },
+    /// The block's previous hash does not match the hash of the current head.
+    PreviousHashMismatch,
+    /// The block's timestamp is earlier than the head's timestamp.
+    StaleTimestamp { head: u64, found: u64 },
+    /// A transaction in the block failed the sentinel spam checks.
+    SpamTransaction { tx_id: u64 },
+    /// A candidate block's parent is neither on the chain nor a known candidate.
+    UnknownParent,
+    /// The block's encoded size exceeds the configured maximum.
+    TooLarge { size: usize, max: usize },
+    /// The block's committed `tx_count` differs from its number of transactions.
+    TxCountMismatch { committed: u64, found: u64 },
+    /// The block's committed `merkle_root` does not match its transactions.
+    MerkleRootMismatch,
+}
+
+impl fmt::Display for BlockValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockValidationError::UnexpectedBlockNumber { expected, found } => {
+                write!(f, "Unexpected block number: expected {}, found {}", expected, found)
+            }
+            BlockValidationError::PreviousHashMismatch => write!(f, "Previous hash does not match chain head"),
+            BlockValidationError::StaleTimestamp { head, found } => {
+                write!(f, "Stale timestamp: head is at {}, block has {}", head, found)
+            }
+            BlockValidationError::SpamTransaction { tx_id } => {
+                write!(f, "Transaction {} failed spam checks", tx_id)
+            }
+            BlockValidationError::UnknownParent => write!(f, "Parent block is unknown"),
+            BlockValidationError::TooLarge { size, max } => {
+                write!(f, "Block is {} bytes, above the maximum of {}", size, max)
+            }
+            BlockValidationError::TxCountMismatch { committed, found } => {
+                write!(f, "Block commits to {} transactions but holds {}", committed, found)
+            }
+            BlockValidationError::MerkleRootMismatch => write!(f, "Merkle root does not match transactions"),
+        }
+    }
+}
+
+impl std::error::Error for BlockValidationError {}
+
+/// Reasons `ChainManager::distribute_rewards` can fail.
+#[derive(Debug, PartialEq)]
+pub enum RewardError {
+    /// No validator with the given id is registered.
+    UnknownProposer(String),
+    /// Crediting the reward would overflow the validator's stake.
+    Overflow,
+}
+
+impl fmt::Display for RewardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewardError::UnknownProposer(id) => write!(f, "Unknown proposer: {}", id),
+            RewardError::Overflow => write!(f, "Reward overflows validator stake"),
+        }
+    }
+}
+
+impl std::error::Error for RewardError {}
+
+/// Reasons `ChainManager` can refuse a change to the validator set or its
+/// settings.
+#[derive(Debug, PartialEq)]
+pub enum ChainError {
+    /// The validator's stake is below the chain's minimum validator stake.
+    StakeBelowMinimum { id: String, stake: u64, min: u64 },
+    /// The puzzle difficulty is above `MAX_DIFFICULTY`, which no solution can meet.
+    DifficultyTooHigh { difficulty: u32 },
+    /// Epochs must span at least one block.
+    ZeroEpochLength,
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::StakeBelowMinimum { id, stake, min } => {
+                write!(f, "Validator {} stakes {}, below the minimum of {}", id, stake, min)
+            }
+            ChainError::DifficultyTooHigh { difficulty } => {
+                write!(f, "Puzzle difficulty {} exceeds the maximum of {}", difficulty, MAX_DIFFICULTY)
+            }
+            ChainError::ZeroEpochLength => write!(f, "Epoch length must be at least one block"),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+/// Why a validator was slashed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlashReason {
+    /// The validator failed its PoCUP puzzle.
+    FailedPuzzle,
+    /// The validator signed two different blocks at one height.
+    Equivocation { first_hash: [u8; 32], second_hash: [u8; 32] },
+}
+
+/// A record of one slashing, kept for audits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlashEvent {
+    pub validator_id: String,
+    /// Chain height the offence was observed at.
+    pub height: u64,
+    pub reason: SlashReason,
+    /// Stake burned; zero for offences that are not yet penalized.
+    pub amount: u64,
+}
+
+/// ChainManager holds a list of PoCUP validators and the accepted chain.
+/// Blocks are linked, Merkle-committed and puzzled under hash function `H`.
+pub struct ChainManager<H: Hasher = Blake3Hasher> {
     /// Validators managed by the node.
     pub validators: Vec<Validator>,
+    /// Accepted blocks, ordered by block number, starting with genesis.
+    blocks: Vec<Block>,
+    /// Position in `blocks` of each accepted block, keyed by its hash.
+    block_index: HashMap<[u8; 32], usize>,
+    /// Number of blocks per epoch.
+    epoch_length: u64,
+    /// Minimum stake required to be in an active set.
+    min_active_stake: u64,
+    /// Minimum stake required to register as a validator at all.
+    min_validator_stake: u64,
+    /// Active validator ids fixed for an epoch, keyed by epoch number.
+    epoch_snapshot: Option<(u64, Vec<String>)>,
+    /// Reward credited to each block's proposer in addition to its fees.
+    reward_schedule: RewardSchedule,
+    /// Largest encoded block size `validate_block` accepts.
+    max_block_bytes: usize,
+    /// Off-chain candidate blocks keyed by their hash, forming competing
+    /// branches that `fork_choice` may switch to.
+    candidates: HashMap<[u8; 32], Block>,
+    /// Hashes of the candidates extending each block, keyed by the parent's
+    /// hash, in arrival order.
+    children: HashMap<[u8; 32], Vec<[u8; 32]>>,
+    /// Every slashing so far, oldest first.
+    slashing_events: Vec<SlashEvent>,
+    /// Leading zero bits each validator's PoCUP puzzle solution needs.
+    puzzle_difficulty: u32,
+    /// Spam rules every transaction in a block must pass. Node-local, so it
+    /// is not part of the snapshot.
+    spam_policy: SpamPolicy,
+    /// The chain's hash function.
+    hasher: PhantomData<fn() -> H>,
+}
+
+/// Block reward as a function of height: `initial_reward`, halved every
+/// `halving_interval` blocks with integer division until it reaches zero.
+/// A `halving_interval` of zero keeps the reward flat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardSchedule {
+    pub initial_reward: u64,
+    pub halving_interval: u64,
+}
+
+impl Default for RewardSchedule {
+    fn default() -> Self {
+        Self { initial_reward: DEFAULT_BLOCK_REWARD, halving_interval: 0 }
+    }
+}
+
+impl RewardSchedule {
+    /// Returns the reward for the block at `height`.
+    pub fn reward_at(&self, height: u64) -> u64 {
+        if self.halving_interval == 0 {
+            return self.initial_reward;
+        }
+        let halvings = height / self.halving_interval;
+        u32::try_from(halvings)
+            .ok()
+            .and_then(|shift| self.initial_reward.checked_shr(shift))
+            .unwrap_or(0)
+    }
+}
+
+/// A switch from the current tip to a heavier branch, as chosen by `fork_choice`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reorg {
+    /// Blocks to undo, from the current tip down to just above the fork point.
+    pub rollback: Vec<Block>,
+    /// Blocks to apply, in ascending order from the fork point to the new tip.
+    pub apply: Vec<Block>,
+}
+
+/// Chain parameters every node must agree on before the first block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenesisConfig {
+    /// Initial validators as `(id, stake)` pairs.
+    pub validators: Vec<(String, u64)>,
+    /// Minimum fee for mempool admission.
+    pub min_fee: f64,
+    /// Seconds between produced blocks.
+    pub block_interval_secs: u64,
+}
+
+impl GenesisConfig {
+    /// Decodes a genesis spec framed by `Serializer::serialize`.
+    pub fn from_bytes(bytes: &[u8]) -> SerializationResult<Self> {
+        Serializer::deserialize(bytes, Endianness::Little)
+    }
+
+    /// Encodes the spec with `Serializer::serialize`, for distribution to other nodes.
+    pub fn to_bytes(&self) -> SerializationResult<Vec<u8>> {
+        Serializer::serialize(self, Endianness::Little)
+    }
+
+    /// Builds a chain manager holding this spec's initial validator set.
+    pub fn chain_manager(&self) -> ChainManager {
+        ChainManager::from_genesis(&self.validators)
+    }
+}
+
+impl Encode for GenesisConfig {
+    fn encoded_size(&self) -> usize {
+        (self.validators.len() as u64).encoded_size() +
+        self.validators.iter().map(|(id, stake)| id.encoded_size() + stake.encoded_size()).sum::<usize>() +
+        self.min_fee.encoded_size() +
+        self.block_interval_secs.encoded_size()
+    }
+
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let mut offset = 0;
+        offset += (self.validators.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+        for (id, stake) in &self.validators {
+            offset += id.encode_to(&mut buffer[offset..], endianness)?;
+            offset += stake.encode_to(&mut buffer[offset..], endianness)?;
+        }
+        offset += self.min_fee.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.block_interval_secs.encode_to(&mut buffer[offset..], endianness)?;
+        Ok(offset)
+    }
+}
+
+impl Decode for GenesisConfig {
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let (count, mut offset) = u64::decode_from(buffer, endianness)?;
+        // Every validator takes at least one byte, so a count beyond the buffer is corrupt.
+        if count > buffer.len() as u64 {
+            return Err(SerializationError::InvalidData("Validator count exceeds buffer".into()));
+        }
+        let mut validators = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (id, consumed) = String::decode_from(&buffer[offset..], endianness)?;
+            offset += consumed;
+            let (stake, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+            offset += consumed;
+            validators.push((id, stake));
+        }
+        let (min_fee, consumed) = f64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        if !min_fee.is_finite() {
+            return Err(SerializationError::InvalidData("Genesis minimum fee is not finite".into()));
+        }
+        let (block_interval_secs, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        Ok((GenesisConfig { validators, min_fee, block_interval_secs }, offset))
+    }
+}
+
+/// Encoding: validator id, height, a reason tag (0 for a failed puzzle, 1 for
+/// equivocation followed by both block hashes), then the amount.
+impl Encode for SlashEvent {
+    fn encoded_size(&self) -> usize {
+        let reason_size = match &self.reason {
+            SlashReason::FailedPuzzle => 1,
+            SlashReason::Equivocation { first_hash, second_hash } => {
+                1 + first_hash.encoded_size() + second_hash.encoded_size()
+            }
+        };
+        self.validator_id.encoded_size() + self.height.encoded_size() + reason_size + self.amount.encoded_size()
+    }
+
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let mut offset = 0;
+        offset += self.validator_id.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.height.encode_to(&mut buffer[offset..], endianness)?;
+        match &self.reason {
+            SlashReason::FailedPuzzle => offset += 0u8.encode_to(&mut buffer[offset..], endianness)?,
+            SlashReason::Equivocation { first_hash, second_hash } => {
+                offset += 1u8.encode_to(&mut buffer[offset..], endianness)?;
+                offset += first_hash.encode_to(&mut buffer[offset..], endianness)?;
+                offset += second_hash.encode_to(&mut buffer[offset..], endianness)?;
+            }
+        }
+        offset += self.amount.encode_to(&mut buffer[offset..], endianness)?;
+        Ok(offset)
+    }
+}
+
+impl Decode for SlashEvent {
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let (validator_id, mut offset) = String::decode_from(buffer, endianness)?;
+        let (height, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (tag, consumed) = u8::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let reason = match tag {
+            0 => SlashReason::FailedPuzzle,
+            1 => {
+                let (first_hash, consumed) = <[u8; 32]>::decode_from(&buffer[offset..], endianness)?;
+                offset += consumed;
+                let (second_hash, consumed) = <[u8; 32]>::decode_from(&buffer[offset..], endianness)?;
+                offset += consumed;
+                SlashReason::Equivocation { first_hash, second_hash }
+            }
+            _ => return Err(SerializationError::InvalidData(format!("Unknown slash reason tag {}", tag))),
+        };
+        let (amount, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        Ok((SlashEvent { validator_id, height, reason, amount }, offset))
+    }
+}
+
+impl<H: Hasher> Default for ChainManager<H> {
+    fn default() -> Self {
+        Self::with_hasher()
+    }
 }
 
 impl ChainManager {
-    /// Creates a new, empty ChainManager.
+    /// Creates a new ChainManager with no validators and a chain holding only
+    /// the genesis block, hashing with Blake3.
     pub fn new() -> Self {
-        Self { validators: Vec::new() }
+        Self::with_hasher()
+    }
+}
+
+impl<H: Hasher> ChainManager<H> {
+    /// Like `new`, hashing with `H`.
+    pub fn with_hasher() -> Self {
+        let genesis = Self::genesis();
+        Self {
+            validators: Vec::new(),
+            block_index: HashMap::from([(genesis.hash_with::<H>(), 0)]),
+            blocks: vec![genesis],
+            epoch_length: DEFAULT_EPOCH_LENGTH,
+            min_active_stake: DEFAULT_MIN_ACTIVE_STAKE,
+            min_validator_stake: 0,
+            epoch_snapshot: None,
+            reward_schedule: RewardSchedule::default(),
+            max_block_bytes: DEFAULT_MAX_BLOCK_BYTES,
+            candidates: HashMap::new(),
+            children: HashMap::new(),
+            slashing_events: Vec::new(),
+            puzzle_difficulty: DEFAULT_PUZZLE_DIFFICULTY,
+            spam_policy: SpamPolicy::default(),
+            hasher: PhantomData,
+        }
+    }
+
+    /// Creates a ChainManager like `with_hasher`, but refusing validators that
+    /// stake less than `min_validator_stake`.
+    pub fn with_min_stake(min_validator_stake: u64) -> Self {
+        Self { min_validator_stake, ..Self::with_hasher() }
+    }
+
+    /// Creates a ChainManager at genesis with the given `(id, stake)` validators,
+    /// in order. Nodes built from the same list agree on the initial set.
+    pub fn from_genesis(validators: &[(String, u64)]) -> Self {
+        let mut manager = Self::with_hasher();
+        for (id, stake) in validators {
+            // `with_hasher` sets no minimum validator stake, so registration cannot fail.
+            manager.add_validator(id.clone(), *stake).expect("genesis validator");
+        }
+        manager
+    }
+
+    /// Sets a flat per-block reward paid to proposers, replacing any schedule.
+    pub fn set_block_reward(&mut self, block_reward: u64) {
+        self.reward_schedule = RewardSchedule { initial_reward: block_reward, halving_interval: 0 };
+    }
+
+    /// Sets the schedule the per-block reward follows as the chain grows.
+    pub fn set_reward_schedule(&mut self, reward_schedule: RewardSchedule) {
+        self.reward_schedule = reward_schedule;
+    }
+
+    /// Sets the largest encoded block size, in bytes, that blocks may have.
+    pub fn set_max_block_bytes(&mut self, max_block_bytes: usize) {
+        self.max_block_bytes = max_block_bytes;
+    }
+
+    /// Returns the largest encoded block size, in bytes, that blocks may have.
+    pub fn max_block_bytes(&self) -> usize {
+        self.max_block_bytes
+    }
+
+    /// Sets the leading zero bits PoCUP puzzle solutions need. Difficulties
+    /// above `MAX_DIFFICULTY` are refused, since no puzzle could be solved.
+    pub fn set_puzzle_difficulty(&mut self, puzzle_difficulty: u32) -> Result<(), ChainError> {
+        if puzzle_difficulty > MAX_DIFFICULTY {
+            return Err(ChainError::DifficultyTooHigh { difficulty: puzzle_difficulty });
+        }
+        self.puzzle_difficulty = puzzle_difficulty;
+        Ok(())
+    }
+
+    /// Returns the leading zero bits PoCUP puzzle solutions need.
+    pub fn puzzle_difficulty(&self) -> u32 {
+        self.puzzle_difficulty
+    }
+
+    /// Sets the spam rules block transactions must pass; use the policy the
+    /// mempool admits transactions under so produced blocks validate.
+    pub fn set_spam_policy(&mut self, spam_policy: SpamPolicy) {
+        self.spam_policy = spam_policy;
+    }
+
+    /// Returns the spam rules block transactions must pass.
+    pub fn spam_policy(&self) -> &SpamPolicy {
+        &self.spam_policy
+    }
+
+    /// Sets the number of blocks per epoch. Takes effect from the next
+    /// snapshot. A length of zero is refused.
+    pub fn set_epoch_length(&mut self, epoch_length: u64) -> Result<(), ChainError> {
+        if epoch_length == 0 {
+            return Err(ChainError::ZeroEpochLength);
+        }
+        self.epoch_length = epoch_length;
+        Ok(())
+    }
+
+    /// Sets the minimum stake for active-set eligibility.
+    pub fn set_min_active_stake(&mut self, min_active_stake: u64) {
+        self.min_active_stake = min_active_stake;
+    }
+
+    /// Returns the genesis block: block 0 with a fixed previous hash, no
+    /// transactions, and a zero timestamp, so every node derives the same one.
+    pub fn genesis() -> Block {
+        Block {
+            block_number: 0,
+            previous_hash: GENESIS_PREVIOUS_HASH,
+            transactions: Vec::new(),
+            tx_count: 0,
+            merkle_root: merkle_root::<H>(&[]),
+            timestamp: 0,
+            signature: Vec::new(),
+        }
     }
 
     /// Adds a new validator with the given id and stake.
     /// The validator's `puzzle_passed` is initially false.
     /// Logs the addition.
-    pub fn add_validator(&mut self, id: String, stake_amount: u64) {
-        println!("ChainManager: Adding validator {} with stake {}.", id, stake_amount);
-        self.validators.push(Validator { id, stake_amount, puzzle_passed: false });
+    ///
+    /// Returns `ChainError::StakeBelowMinimum` if `stake_amount` is below the
+    /// minimum validator stake.
+    pub fn add_validator(&mut self, id: String, stake_amount: u64) -> Result<(), ChainError> {
+        if stake_amount < self.min_validator_stake {
+            return Err(ChainError::StakeBelowMinimum { id, stake: stake_amount, min: self.min_validator_stake });
+        }
+        info!("ChainManager: Adding validator {} with stake {}.", id, stake_amount);
+        self.validators.push(Validator { id, stake_amount, puzzle_passed: false, jailed: false, last_solution: None });
+        Ok(())
+    }
+
+    /// Merges validators announced by a peer into the local set, matching by id.
+    /// Unknown validators are added unless they stake below the minimum
+    /// validator stake; whatever the peer claims, they start with no puzzle
+    /// passed or solution found, and unjailed. For known ones the higher of the
+    /// two stakes is kept, except that a validator jailed or slashed here keeps
+    /// its local stake. Returns how many validators were added or had their
+    /// stake raised.
+    pub fn merge_validators(&mut self, validators: Vec<Validator>) -> usize {
+        let mut changed = 0;
+        for incoming in validators {
+            let penalized = self.slashing_events.iter().any(|e| e.validator_id == incoming.id && e.amount > 0);
+            match self.validators.iter_mut().find(|v| v.id == incoming.id) {
+                Some(local) if local.jailed || penalized => {}
+                Some(local) if incoming.stake_amount > local.stake_amount => {
+                    local.stake_amount = incoming.stake_amount;
+                    changed += 1;
+                }
+                Some(_) => {}
+                None if incoming.stake_amount >= self.min_validator_stake => {
+                    info!("ChainManager: Learned validator {} with stake {}.", incoming.id, incoming.stake_amount);
+                    self.validators.push(Validator {
+                        puzzle_passed: false,
+                        jailed: false,
+                        last_solution: None,
+                        ..incoming
+                    });
+                    changed += 1;
+                }
+                None => {}
+            }
+        }
+        changed
+    }
+
+    /// Jails the validator with the given id. It stays in the current epoch's
+    /// active set but is excluded from the next one. Returns false if unknown.
+    pub fn jail_validator(&mut self, id: &str) -> bool {
+        match self.validators.iter_mut().find(|v| v.id == id) {
+            Some(v) => {
+                info!("ChainManager: Jailing validator {}.", id);
+                v.jailed = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Credits the proposer's stake with `total_fees` plus the block reward
+    /// the schedule pays at the current height.
+    /// Returns the amount credited; the stake is left unchanged on error.
+    pub fn distribute_rewards(&mut self, proposer_id: &str, total_fees: u64) -> Result<u64, RewardError> {
+        let block_reward = self.reward_schedule.reward_at(self.height());
+        let reward = total_fees.checked_add(block_reward).ok_or(RewardError::Overflow)?;
+        let proposer = self
+            .validators
+            .iter_mut()
+            .find(|v| v.id == proposer_id)
+            .ok_or_else(|| RewardError::UnknownProposer(proposer_id.to_string()))?;
+        proposer.stake_amount = proposer.stake_amount.checked_add(reward).ok_or(RewardError::Overflow)?;
+        info!("ChainManager: Rewarded {} with {} (stake now {}).", proposer_id, reward, proposer.stake_amount);
+        Ok(reward)
+    }
+
+    /// Serializes the validator set, the accepted chain, and configuration
+    /// into a Blake3-checksummed frame for fast restarts.
+    pub fn snapshot(&self) -> Vec<u8> {
+        Serializer::serialize(self, Endianness::Little).expect("snapshot buffer sized by encoded_size")
+    }
+
+    /// Rebuilds a ChainManager from bytes produced by `snapshot`.
+    pub fn restore(bytes: &[u8]) -> SerializationResult<Self> {
+        Serializer::deserialize(bytes, Endianness::Little)
+    }
+
+    /// Writes every accepted block, genesis included, to `path` as a
+    /// `ChainExporter` file that `ChainReader` can read by height.
+    pub fn export(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut exporter = ChainExporter::new(BufWriter::new(File::create(path)?))?;
+        for block in &self.blocks {
+            exporter.write_block(block)?;
+        }
+        exporter.finish()?;
+        Ok(())
+    }
+
+    /// Returns the epoch containing block `height`.
+    pub fn epoch_of(&self, height: u64) -> u64 {
+        height / self.epoch_length
+    }
+
+    /// Returns true if `validator_id`'s last puzzle solution is valid for the
+    /// current head. Solutions go stale as soon as a new block is appended.
+    pub fn verify_work(&self, validator_id: &str) -> bool {
+        let tip = self.head().hash_with::<H>();
+        self.validators
+            .iter()
+            .find(|v| v.id == validator_id)
+            .is_some_and(|v| verify_useful_work_with::<H>(v, &tip, self.puzzle_difficulty))
+    }
+
+    /// Returns the validators eligible for the epoch containing `height`.
+    ///
+    /// Eligibility (stake at least the minimum, not jailed) is snapshotted when
+    /// the first block of an epoch is appended, so the set is stable for the
+    /// rest of that epoch. For an epoch without a snapshot yet, the current
+    /// eligibility is returned.
+    pub fn active_set(&self, height: u64) -> Vec<&Validator> {
+        match &self.epoch_snapshot {
+            Some((epoch, ids)) if *epoch == self.epoch_of(height) => {
+                self.validators.iter().filter(|v| ids.contains(&v.id)).collect()
+            }
+            _ => self.validators.iter().filter(|v| self.is_eligible(v)).collect(),
+        }
+    }
+
+    fn is_eligible(&self, validator: &Validator) -> bool {
+        !validator.jailed && validator.stake_amount >= self.min_active_stake
+    }
+
+    /// Snapshots the active set if `height` opens an epoch without one.
+    fn snapshot_epoch(&mut self, height: u64) {
+        let epoch = self.epoch_of(height);
+        if self.epoch_snapshot.as_ref().is_some_and(|(current, _)| *current == epoch) {
+            return;
+        }
+        let ids = self
+            .validators
+            .iter()
+            .filter(|v| self.is_eligible(v))
+            .map(|v| v.id.clone())
+            .collect::<Vec<_>>();
+        info!("ChainManager: Epoch {} active set: {:?}", epoch, ids);
+        self.epoch_snapshot = Some((epoch, ids));
     }
 
     /// Runs PoCUP tasks on all validators.
-    /// For each validator, it calls `perform_useful_work` and then `slash_if_needed`.
+    /// For each validator, it solves the puzzle for the current head with
+    /// `perform_useful_work_with` and then calls `slash_if_needed`.
     pub fn run_pocup_tasks(&mut self) {
+        let tip = self.head().hash_with::<H>();
+        for v in &mut self.validators {
+            perform_useful_work_with::<H>(v, &tip, self.puzzle_difficulty);
+        }
+        self.slash_failed_puzzles();
+    }
+
+    /// Calls `slash_if_needed` on every validator, logging a `SlashEvent` for
+    /// each failed puzzle.
+    fn slash_failed_puzzles(&mut self) {
+        let height = self.height();
         for v in &mut self.validators {
-            crate::pocup::pocup::perform_useful_work(v);
-            crate::pocup::pocup::slash_if_needed(v);
+            if slash_if_needed(v) {
+                self.slashing_events.push(SlashEvent {
+                    validator_id: v.id.clone(),
+                    height,
+                    reason: SlashReason::FailedPuzzle,
+                    amount: 0,
+                });
+            }
+        }
+    }
+
+    /// Slashes the validator named in `evidence` with `slash_for_equivocation`
+    /// and logs the event. Returns the amount slashed; zero if the validator
+    /// is unknown.
+    pub fn slash_equivocation(&mut self, evidence: &Evidence) -> u64 {
+        let Some(v) = self.validators.iter_mut().find(|v| v.id == evidence.validator_id) else {
+            return 0;
+        };
+        let amount = slash_for_equivocation(v, evidence);
+        self.slashing_events.push(SlashEvent {
+            validator_id: v.id.clone(),
+            height: evidence.height,
+            reason: SlashReason::Equivocation { first_hash: evidence.first_hash, second_hash: evidence.second_hash },
+            amount,
+        });
+        amount
+    }
+
+    /// Returns the slashings recorded against `validator_id`, oldest first.
+    pub fn slashing_history(&self, validator_id: &str) -> Vec<&SlashEvent> {
+        self.slashing_events.iter().filter(|e| e.validator_id == validator_id).collect()
+    }
+
+    /// Returns the most recently accepted block.
+    pub fn head(&self) -> &Block {
+        self.blocks.last().expect("chain always contains genesis")
+    }
+
+    /// Returns the block number of the chain head (0 at genesis).
+    pub fn height(&self) -> u64 {
+        self.head().block_number
+    }
+
+    /// Returns up to `count` consecutive blocks starting at block number `from_height`.
+    pub fn blocks_from(&self, from_height: u64, count: usize) -> Vec<Block> {
+        self.blocks
+            .iter()
+            .skip_while(|b| b.block_number < from_height)
+            .take(count)
+            .cloned()
+            .collect()
+    }
+
+    /// Checks that `block` can extend the current head:
+    /// 1. Its block number is head + 1.
+    /// 2. Its previous hash equals the head's hash.
+    /// 3. Its timestamp is not earlier than the head's timestamp.
+    /// 4. Its committed `tx_count` matches its transactions.
+    /// 5. Its committed `merkle_root` matches its transactions.
+    /// 6. Every transaction passes `sentinel::check_spam_with` under the chain's
+    ///    spam policy.
+    pub fn validate_block(&self, block: &Block) -> Result<(), BlockValidationError> {
+        self.validate_child(self.head(), block)
+    }
+
+    /// Applies the `validate_block` rules with `parent` in place of the head.
+    fn validate_child(&self, parent: &Block, block: &Block) -> Result<(), BlockValidationError> {
+        let size = block.encoded_size();
+        if size > self.max_block_bytes {
+            return Err(BlockValidationError::TooLarge { size, max: self.max_block_bytes });
+        }
+        let expected = parent.block_number + 1;
+        if block.block_number != expected {
+            return Err(BlockValidationError::UnexpectedBlockNumber { expected, found: block.block_number });
         }
+        if block.previous_hash != parent.hash_with::<H>() {
+            return Err(BlockValidationError::PreviousHashMismatch);
+        }
+        if block.timestamp < parent.timestamp {
+            return Err(BlockValidationError::StaleTimestamp { head: parent.timestamp, found: block.timestamp });
+        }
+        if !block.verify_tx_count() {
+            return Err(BlockValidationError::TxCountMismatch {
+                committed: block.tx_count,
+                found: block.transactions.len() as u64,
+            });
+        }
+        if block.merkle_root != block.compute_merkle_root_with::<H>() {
+            return Err(BlockValidationError::MerkleRootMismatch);
+        }
+        if let Some(tx) = block.transactions.iter().find(|tx| !check_spam_with(tx, &self.spam_policy)) {
+            return Err(BlockValidationError::SpamTransaction { tx_id: tx.id });
+        }
+        Ok(())
+    }
+
+    /// Appends `block` to the chain after `validate_block` accepts it.
+    /// The first block appended in an epoch fixes that epoch's active set.
+    pub fn append_block(&mut self, block: Block) -> Result<(), BlockValidationError> {
+        self.validate_block(&block)?;
+        self.snapshot_epoch(block.block_number);
+        self.push_block(block);
+        self.prune_candidates();
+        Ok(())
+    }
+
+    /// Pushes `block` onto the chain and indexes its hash.
+    fn push_block(&mut self, block: Block) {
+        self.block_index.insert(block.hash_with::<H>(), self.blocks.len());
+        self.blocks.push(block);
+    }
+
+    /// Records `block` as a candidate on a competing branch. Its parent must be
+    /// an accepted block or another candidate, and it must pass the same
+    /// checks `validate_block` applies against the head.
+    pub fn add_candidate(&mut self, block: Block) -> Result<(), BlockValidationError> {
+        let parent = self
+            .block_index
+            .get(&block.previous_hash)
+            .map(|&index| &self.blocks[index])
+            .or_else(|| self.candidates.get(&block.previous_hash))
+            .ok_or(BlockValidationError::UnknownParent)?;
+        self.validate_child(parent, &block)?;
+        let hash = block.hash_with::<H>();
+        if self.block_index.contains_key(&hash) || self.candidates.contains_key(&hash) {
+            return Ok(());
+        }
+        self.children.entry(block.previous_hash).or_default().push(hash);
+        self.candidates.insert(hash, block);
+        Ok(())
+    }
+
+    /// Picks the longest chain among the current one and every branch of
+    /// candidates. Returns `None` if no branch is strictly longer than the
+    /// current chain; otherwise the blocks to roll back and to apply.
+    pub fn fork_choice(&self) -> Option<Reorg> {
+        let mut forks: Vec<(usize, [u8; 32])> = self
+            .children
+            .keys()
+            .filter_map(|hash| self.block_index.get(hash).map(|&index| (index, *hash)))
+            .collect();
+        forks.sort_unstable();
+        let mut best: Option<(usize, Vec<&Block>)> = None;
+        let mut best_height = self.height();
+        for (fork_index, fork_hash) in forks {
+            let branch = self.longest_branch(fork_hash);
+            let height = self.blocks[fork_index].block_number + branch.len() as u64;
+            if height > best_height {
+                best_height = height;
+                best = Some((fork_index, branch));
+            }
+        }
+        let (fork_index, branch) = best?;
+        Some(Reorg {
+            rollback: self.blocks[fork_index + 1..].iter().rev().cloned().collect(),
+            apply: branch.into_iter().cloned().collect(),
+        })
+    }
+
+    /// Returns the longest path of candidates descending from the block with
+    /// `hash`; of equally long paths, the one through the earliest-arriving
+    /// children. Walks the branches with an explicit stack, so depth is
+    /// bounded by memory rather than the call stack.
+    fn longest_branch(&self, hash: [u8; 32]) -> Vec<&Block> {
+        let mut deepest: Option<(usize, [u8; 32])> = None;
+        let mut stack = vec![(0, hash)];
+        while let Some((depth, hash)) = stack.pop() {
+            if depth > deepest.map_or(0, |(best, _)| best) {
+                deepest = Some((depth, hash));
+            }
+            for child in self.children.get(&hash).into_iter().flatten().rev() {
+                stack.push((depth + 1, *child));
+            }
+        }
+        let mut branch = Vec::new();
+        let mut cursor = deepest.map(|(_, hash)| hash);
+        while let Some(block) = cursor.and_then(|hash| self.candidates.get(&hash)) {
+            branch.push(block);
+            cursor = (block.previous_hash != hash).then_some(block.previous_hash);
+        }
+        branch.reverse();
+        branch
+    }
+
+    /// Drops candidates at or below the head's height; they can no longer
+    /// form a branch longer than the chain.
+    fn prune_candidates(&mut self) {
+        let height = self.height();
+        self.candidates.retain(|_, block| block.block_number > height);
+        let candidates = &self.candidates;
+        self.children.retain(|_, hashes| {
+            hashes.retain(|hash| candidates.contains_key(hash));
+            !hashes.is_empty()
+        });
+    }
+
+    /// Switches to the branch chosen by `fork_choice`, if any. Candidates at
+    /// or below the new head, including the rolled-back blocks, are dropped.
+    pub fn reorg_to_best(&mut self) -> Option<Reorg> {
+        let reorg = self.fork_choice()?;
+        let fork_height = self.blocks.len() - reorg.rollback.len();
+        for block in self.blocks.drain(fork_height..) {
+            self.block_index.remove(&block.hash_with::<H>());
+        }
+        for block in &reorg.apply {
+            self.candidates.remove(&block.hash_with::<H>());
+            self.snapshot_epoch(block.block_number);
+            self.push_block(block.clone());
+        }
+        self.prune_candidates();
+        info!(
+            "ChainManager: Reorg rolled back {} blocks and applied {}; head is now #{}.",
+            reorg.rollback.len(),
+            reorg.apply.len(),
+            self.height()
+        );
+        Some(reorg)
+    }
+}
+
+/// Snapshot encoding: validators, accepted blocks, then the epoch, stake, and reward
+/// configuration, the slashing log, and the current epoch's active set if one
+/// has been fixed. Decoding checks that the blocks form a single chain from
+/// genesis, numbered by height and linked under `H`.
+impl<H: Hasher> Encode for ChainManager<H> {
+    fn encoded_size(&self) -> usize {
+        (self.validators.len() as u64).encoded_size() +
+        self.validators.iter().map(|v| v.encoded_size()).sum::<usize>() +
+        (self.blocks.len() as u64).encoded_size() +
+        self.blocks.iter().map(|b| b.encoded_size()).sum::<usize>() +
+        self.epoch_length.encoded_size() +
+        self.min_active_stake.encoded_size() +
+        self.min_validator_stake.encoded_size() +
+        self.reward_schedule.initial_reward.encoded_size() +
+        self.reward_schedule.halving_interval.encoded_size() +
+        (self.max_block_bytes as u64).encoded_size() +
+        self.puzzle_difficulty.encoded_size() +
+        (self.slashing_events.len() as u64).encoded_size() +
+        self.slashing_events.iter().map(|e| e.encoded_size()).sum::<usize>() +
+        self.epoch_snapshot.as_ref().map(|(epoch, _)| *epoch).encoded_size() +
+        self.epoch_snapshot.as_ref().map_or(0, |(_, ids)| {
+            (ids.len() as u64).encoded_size() + ids.iter().map(|id| id.encoded_size()).sum::<usize>()
+        })
+    }
+
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let mut offset = 0;
+        offset += (self.validators.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+        for validator in &self.validators {
+            offset += validator.encode_to(&mut buffer[offset..], endianness)?;
+        }
+        offset += (self.blocks.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+        for block in &self.blocks {
+            offset += block.encode_to(&mut buffer[offset..], endianness)?;
+        }
+        offset += self.epoch_length.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.min_active_stake.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.min_validator_stake.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.reward_schedule.initial_reward.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.reward_schedule.halving_interval.encode_to(&mut buffer[offset..], endianness)?;
+        offset += (self.max_block_bytes as u64).encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.puzzle_difficulty.encode_to(&mut buffer[offset..], endianness)?;
+        offset += (self.slashing_events.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+        for event in &self.slashing_events {
+            offset += event.encode_to(&mut buffer[offset..], endianness)?;
+        }
+        offset += self.epoch_snapshot.as_ref().map(|(epoch, _)| *epoch).encode_to(&mut buffer[offset..], endianness)?;
+        if let Some((_, ids)) = &self.epoch_snapshot {
+            offset += (ids.len() as u64).encode_to(&mut buffer[offset..], endianness)?;
+            for id in ids {
+                offset += id.encode_to(&mut buffer[offset..], endianness)?;
+            }
+        }
+        Ok(offset)
     }
 }
 
+impl<H: Hasher> Decode for ChainManager<H> {
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let mut offset = 0;
+        let validators = decode_seq::<Validator>(buffer, &mut offset, endianness)?;
+        let blocks = decode_seq::<Block>(buffer, &mut offset, endianness)?;
+        if blocks.first() != Some(&Self::genesis()) {
+            return Err(SerializationError::InvalidData("Snapshot does not start at the genesis block".into()));
+        }
+        for (parent, block) in blocks.iter().zip(&blocks[1..]) {
+            if block.block_number != parent.block_number + 1 {
+                return Err(SerializationError::InvalidData("Snapshot blocks are not numbered by height".into()));
+            }
+            if block.previous_hash != parent.hash_with::<H>() {
+                return Err(SerializationError::InvalidData("Snapshot blocks are not linked".into()));
+            }
+        }
+        let (epoch_length, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        if epoch_length == 0 {
+            return Err(SerializationError::InvalidData("Snapshot has a zero epoch length".into()));
+        }
+        let (min_active_stake, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (min_validator_stake, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (initial_reward, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (halving_interval, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (max_block_bytes, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let max_block_bytes = usize::try_from(max_block_bytes)
+            .map_err(|_| SerializationError::InvalidData("Snapshot block size limit exceeds usize".into()))?;
+        let (puzzle_difficulty, consumed) = u32::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        if puzzle_difficulty > MAX_DIFFICULTY {
+            return Err(SerializationError::InvalidData("Snapshot puzzle difficulty exceeds the maximum".into()));
+        }
+        let slashing_events = decode_seq::<SlashEvent>(buffer, &mut offset, endianness)?;
+        let (epoch, consumed) = Option::<u64>::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let epoch_snapshot = match epoch {
+            Some(epoch) => Some((epoch, decode_seq::<String>(buffer, &mut offset, endianness)?)),
+            None => None,
+        };
+        let block_index = blocks.iter().enumerate().map(|(index, b)| (b.hash_with::<H>(), index)).collect();
+        let manager = ChainManager {
+            validators,
+            blocks,
+            block_index,
+            epoch_length,
+            min_active_stake,
+            min_validator_stake,
+            epoch_snapshot,
+            reward_schedule: RewardSchedule { initial_reward, halving_interval },
+            max_block_bytes,
+            candidates: HashMap::new(),
+            children: HashMap::new(),
+            slashing_events,
+            puzzle_difficulty,
+            spam_policy: SpamPolicy::default(),
+            hasher: PhantomData,
+        };
+        Ok((manager, offset))
+    }
+}
+
+/// Decodes a count-prefixed sequence starting at `*offset`, advancing it.
+fn decode_seq<T: Decode>(buffer: &[u8], offset: &mut usize, endianness: Endianness) -> SerializationResult<Vec<T>> {
+    let (count, consumed) = u64::decode_from(&buffer[*offset..], endianness)?;
+    *offset += consumed;
+    // Every item takes at least one byte, so a count beyond the buffer is corrupt.
+    if count > buffer.len() as u64 {
+        return Err(SerializationError::InvalidData("Item count exceeds buffer".into()));
+    }
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let (item, consumed) = T::decode_from(&buffer[*offset..], endianness)?;
+        *offset += consumed;
+        items.push(item);
+    }
+    Ok(items)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::serialization::Transaction;
     
     #[test]
     fn test_add_and_run() {
         let mut cm = ChainManager::new();
-        cm.add_validator("validator1".to_string(), 1000);
+        cm.add_validator("validator1".to_string(), 1000).unwrap();
         assert_eq!(cm.validators.len(), 1);
         // Initially, puzzle_passed is false.
         assert!(!cm.validators[0].puzzle_passed);
         cm.run_pocup_tasks();
-        // trivial_puzzle always returns true in Phase 1.
         assert!(cm.validators[0].puzzle_passed);
+        assert!(cm.verify_work("validator1"));
+        assert!(!cm.verify_work("unknown"));
+        // The solution is for genesis and goes stale with the next block.
+        cm.append_block(next_block(&cm)).unwrap();
+        assert!(!cm.verify_work("validator1"));
+    }
+
+    #[test]
+    fn test_min_validator_stake() {
+        let mut cm = <ChainManager>::with_min_stake(500);
+        assert_eq!(
+            cm.add_validator("Cheap".to_string(), 499),
+            Err(ChainError::StakeBelowMinimum { id: "Cheap".into(), stake: 499, min: 500 })
+        );
+        assert_eq!(cm.add_validator("Exact".to_string(), 500), Ok(()));
+        let ids: Vec<&str> = cm.validators.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, vec!["Exact"]);
+    }
+
+    #[test]
+    fn test_slashing_events_logged() {
+        let mut cm = ChainManager::new();
+        cm.add_validator("Lazy".to_string(), 100).unwrap();
+        cm.add_validator("Forker".to_string(), 300).unwrap();
+        cm.append_block(next_block(&cm)).unwrap();
+
+        cm.validators[0].puzzle_passed = false;
+        cm.validators[1].puzzle_passed = true;
+        cm.slash_failed_puzzles();
+        let evidence = Evidence { validator_id: "Forker".into(), height: 7, first_hash: [1; 32], second_hash: [2; 32] };
+        assert_eq!(cm.slash_equivocation(&evidence), 300);
+
+        assert_eq!(
+            cm.slashing_history("Lazy"),
+            vec![&SlashEvent { validator_id: "Lazy".into(), height: 1, reason: SlashReason::FailedPuzzle, amount: 0 }]
+        );
+        assert_eq!(
+            cm.slashing_history("Forker"),
+            vec![&SlashEvent {
+                validator_id: "Forker".into(),
+                height: 7,
+                reason: SlashReason::Equivocation { first_hash: [1; 32], second_hash: [2; 32] },
+                amount: 300,
+            }]
+        );
+        assert!(cm.slashing_history("Nobody").is_empty());
+    }
+
+    #[test]
+    fn test_merge_validator_set() {
+        let mut cm = <ChainManager>::with_min_stake(10);
+        cm.add_validator("A".to_string(), 100).unwrap();
+        cm.add_validator("B".to_string(), 300).unwrap();
+        let announced = |id: &str, stake_amount| Validator {
+            id: id.to_string(),
+            stake_amount,
+            puzzle_passed: false,
+            jailed: false,
+            last_solution: None,
+        };
+
+        let changed = cm.merge_validators(vec![
+            announced("A", 150),
+            announced("B", 200),
+            announced("C", 50),
+            announced("Dust", 5),
+        ]);
+        assert_eq!(changed, 2);
+        let stakes: Vec<(&str, u64)> = cm.validators.iter().map(|v| (v.id.as_str(), v.stake_amount)).collect();
+        assert_eq!(stakes, vec![("A", 150), ("B", 300), ("C", 50)]);
+    }
+
+    #[test]
+    fn test_merge_cannot_restore_slashed_or_jailed_stake() {
+        let mut cm = ChainManager::new();
+        cm.add_validator("Forker".to_string(), 500).unwrap();
+        cm.add_validator("Jailed".to_string(), 100).unwrap();
+        let evidence = Evidence { validator_id: "Forker".into(), height: 7, first_hash: [1; 32], second_hash: [2; 32] };
+        cm.slash_equivocation(&evidence);
+        cm.jail_validator("Jailed");
+        let claimed = |id: &str| Validator {
+            id: id.to_string(),
+            stake_amount: 1_000,
+            puzzle_passed: true,
+            jailed: false,
+            last_solution: Some(42),
+        };
+
+        assert_eq!(cm.merge_validators(vec![claimed("Forker"), claimed("Jailed"), claimed("New")]), 1);
+        let forker = &cm.validators[0];
+        assert_eq!((forker.stake_amount, forker.jailed), (0, true));
+        assert_eq!(cm.validators[1].stake_amount, 100);
+        // A learned validator keeps its stake but none of its claimed standing.
+        let learned = &cm.validators[2];
+        assert_eq!((learned.id.as_str(), learned.stake_amount), ("New", 1_000));
+        assert_eq!((learned.puzzle_passed, learned.jailed, learned.last_solution), (false, false, None));
+    }
+
+    fn next_block(cm: &ChainManager) -> Block {
+        let mut block = Block {
+            block_number: cm.height() + 1,
+            previous_hash: cm.head().hash(),
+            transactions: Vec::new(),
+            tx_count: 0,
+            merkle_root: [0u8; 32],
+            timestamp: cm.head().timestamp + 10,
+            signature: Vec::new(),
+        };
+        block.commit_transactions();
+        block
+    }
+
+    fn tx(id: u64, fee: f64, sender: &str, recipient: &str) -> Transaction {
+        Transaction {
+            id,
+            amount: 1000,
+            fee,
+            version: 1,
+            nonce: 0,
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            signature: vec![1, 2, 3, 4].into(),
+        }
+    }
+
+    #[test]
+    fn test_new_chain_starts_at_genesis() {
+        let cm = ChainManager::new();
+        assert_eq!(cm.height(), 0);
+        assert_eq!(cm.head(), &<ChainManager>::genesis());
+        assert_eq!(cm.head().previous_hash, GENESIS_PREVIOUS_HASH);
+        assert!(cm.head().transactions.is_empty());
+    }
+
+    #[test]
+    fn test_validate_valid_next_block() {
+        let cm = ChainManager::new();
+        let mut block = next_block(&cm);
+        block.transactions.push(tx(1, 5.0, "Alice", "Bob"));
+        block.commit_transactions();
+        assert_eq!(cm.validate_block(&block), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_stale_timestamp() {
+        let mut cm = ChainManager::new();
+        cm.append_block(next_block(&cm)).unwrap();
+        let mut block = next_block(&cm);
+        block.timestamp = cm.head().timestamp - 1;
+        assert_eq!(
+            cm.validate_block(&block),
+            Err(BlockValidationError::StaleTimestamp { head: 10, found: 9 })
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate_rejects_spam_transaction() {
+        let mut cm = ChainManager::new();
+        let mut block = next_block(&cm);
+        block.transactions.push(tx(1, 5.0, "Alice", "Bob"));
+        block.transactions.push(tx(2, 5.0, "Mallory", "Mallory"));
+        block.commit_transactions();
+        assert_eq!(cm.append_block(block), Err(BlockValidationError::SpamTransaction { tx_id: 2 }));
+        assert_eq!(cm.height(), 0);
+    }
+
+    #[test]
+    fn test_validate_applies_chain_spam_policy() {
+        let mut cm = ChainManager::new();
+        let mut block = next_block(&cm);
+        block.transactions.push(tx(1, 5.0, "Mallory", "Mallory"));
+        block.commit_transactions();
+        cm.set_spam_policy(SpamPolicy { allow_self_transfer: true, low_fee_threshold: 10.0, ..SpamPolicy::default() });
+        assert_eq!(cm.validate_block(&block), Err(BlockValidationError::SpamTransaction { tx_id: 1 }));
+        cm.set_spam_policy(SpamPolicy { allow_self_transfer: true, ..SpamPolicy::default() });
+        assert_eq!(cm.append_block(block), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_enforces_max_block_bytes() {
+        let mut cm = ChainManager::new();
+        let mut block = next_block(&cm);
+        block.transactions.push(tx(1, 5.0, "Alice", "Bob"));
+        block.commit_transactions();
+        let size = block.encoded_size();
+        cm.set_max_block_bytes(size);
+        assert_eq!(cm.validate_block(&block), Ok(()));
+        cm.set_max_block_bytes(size - 1);
+        assert_eq!(cm.append_block(block), Err(BlockValidationError::TooLarge { size, max: size - 1 }));
+        assert_eq!(cm.height(), 0);
+    }
+
+    #[test]
+    fn test_append_block_links_chain() {
+        let mut cm = ChainManager::new();
+        for _ in 0..3 {
+            let block = next_block(&cm);
+            cm.append_block(block).expect("valid block");
+        }
+        assert_eq!(cm.height(), 3);
+        assert_eq!(cm.blocks_from(2, 10).len(), 2);
+        assert_eq!(cm.blocks_from(0, 10).len(), 4);
+    }
+
+    #[test]
+    fn test_append_block_rejects_bad_link() {
+        let mut cm = ChainManager::new();
+        cm.append_block(next_block(&cm)).unwrap();
+        let mut bad = next_block(&cm);
+        bad.previous_hash = [7u8; 32];
+        assert_eq!(cm.append_block(bad), Err(BlockValidationError::PreviousHashMismatch));
+        let mut skipped = next_block(&cm);
+        skipped.block_number = 5;
+        assert_eq!(
+            cm.append_block(skipped),
+            Err(BlockValidationError::UnexpectedBlockNumber { expected: 2, found: 5 })
+        );
+    }
+
+    fn active_ids(cm: &ChainManager, height: u64) -> Vec<&str> {
+        cm.active_set(height).iter().map(|v| v.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_jailed_validator_stays_active_until_next_epoch() {
+        let mut cm = ChainManager::new();
+        assert_eq!(cm.set_epoch_length(0), Err(ChainError::ZeroEpochLength));
+        cm.set_epoch_length(3).unwrap();
+        cm.add_validator("A".to_string(), 100).unwrap();
+        cm.add_validator("B".to_string(), 100).unwrap();
+        cm.add_validator("Poor".to_string(), 0).unwrap();
+        cm.append_block(next_block(&cm)).unwrap(); // height 1 fixes epoch 0
+        assert_eq!(active_ids(&cm, 1), vec!["A", "B"]);
+
+        assert!(cm.jail_validator("B"));
+        cm.append_block(next_block(&cm)).unwrap(); // height 2, still epoch 0
+        assert_eq!(active_ids(&cm, 2), vec!["A", "B"]);
+
+        cm.append_block(next_block(&cm)).unwrap(); // height 3 opens epoch 1
+        assert_eq!(active_ids(&cm, 3), vec!["A"]);
+        assert!(!cm.jail_validator("unknown"));
+    }
+
+    #[test]
+    fn test_distribute_rewards() {
+        let mut cm = ChainManager::new();
+        cm.set_block_reward(5);
+        cm.add_validator("A".to_string(), 100).unwrap();
+        assert_eq!(cm.distribute_rewards("A", 20), Ok(25));
+        assert_eq!(cm.validators[0].stake_amount, 125);
+        assert_eq!(cm.distribute_rewards("Z", 20), Err(RewardError::UnknownProposer("Z".into())));
+        assert_eq!(cm.distribute_rewards("A", u64::MAX), Err(RewardError::Overflow));
+        assert_eq!(cm.validators[0].stake_amount, 125);
+    }
+
+    #[test]
+    fn test_reward_schedule_halves() {
+        let schedule = RewardSchedule { initial_reward: 50, halving_interval: 210 };
+        assert_eq!(schedule.reward_at(0), 50);
+        assert_eq!(schedule.reward_at(209), 50);
+        assert_eq!(schedule.reward_at(210), 25);
+        assert_eq!(schedule.reward_at(420), 12);
+        assert_eq!(schedule.reward_at(210 * 6), 0);
+        // Far past the last halving, including shifts wider than u64.
+        assert_eq!(schedule.reward_at(210 * 64), 0);
+        assert_eq!(schedule.reward_at(u64::MAX), 0);
+        let max = RewardSchedule { initial_reward: u64::MAX, halving_interval: 1 };
+        assert_eq!(max.reward_at(63), 1);
+        assert_eq!(max.reward_at(64), 0);
+    }
+
+    #[test]
+    fn test_distribute_rewards_follows_schedule() {
+        let mut cm = ChainManager::new();
+        cm.add_validator("A".to_string(), 100).unwrap();
+        cm.set_reward_schedule(RewardSchedule { initial_reward: 8, halving_interval: 2 });
+        assert_eq!(cm.distribute_rewards("A", 0), Ok(8));
+        cm.append_block(next_block(&cm)).unwrap();
+        cm.append_block(next_block(&cm)).unwrap();
+        assert_eq!(cm.distribute_rewards("A", 1), Ok(5));
+    }
+
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let mut cm = ChainManager::new();
+        cm.add_validator("A".to_string(), 100).unwrap();
+        cm.add_validator("B".to_string(), 250).unwrap();
+        cm.run_pocup_tasks();
+        cm.add_validator("C".to_string(), 5).unwrap();
+        cm.jail_validator("B");
+        cm.set_block_reward(3);
+        cm.set_max_block_bytes(4096);
+        cm.set_puzzle_difficulty(12).unwrap();
+        cm.append_block(next_block(&cm)).unwrap();
+        let evidence = Evidence { validator_id: "A".into(), height: 1, first_hash: [1; 32], second_hash: [2; 32] };
+        cm.slash_equivocation(&evidence);
+        cm.slash_failed_puzzles();
+
+        let restored = <ChainManager>::restore(&cm.snapshot()).expect("restore");
+        assert_eq!(restored.validators, cm.validators);
+        assert!(restored.validators[0].puzzle_passed && !restored.validators[2].puzzle_passed);
+        assert_eq!(restored.height(), 1);
+        assert_eq!(restored.head(), cm.head());
+        assert_eq!(restored.reward_schedule, RewardSchedule { initial_reward: 3, halving_interval: 0 });
+        assert_eq!(restored.max_block_bytes(), 4096);
+        assert_eq!(restored.puzzle_difficulty(), 12);
+        assert_eq!(restored.slashing_events, cm.slashing_events);
+        assert!(matches!(restored.slashing_history("A")[0].reason, SlashReason::Equivocation { .. }));
+        assert_eq!(restored.slashing_history("C")[0].reason, SlashReason::FailedPuzzle);
+        assert_eq!(restored.epoch_snapshot, cm.epoch_snapshot);
+        assert!(restored.epoch_snapshot.is_some());
+
+        let mut corrupt = cm.snapshot();
+        corrupt[6] ^= 0xff;
+        assert!(<ChainManager>::restore(&corrupt).is_err());
+    }
+
+    #[test]
+    fn test_difficulty_above_max_rejected() {
+        let mut cm = ChainManager::new();
+        assert_eq!(cm.set_puzzle_difficulty(257), Err(ChainError::DifficultyTooHigh { difficulty: 257 }));
+        assert_eq!(cm.puzzle_difficulty(), DEFAULT_PUZZLE_DIFFICULTY);
+        assert_eq!(cm.set_puzzle_difficulty(MAX_DIFFICULTY), Ok(()));
+
+        // A snapshot carrying an impossible difficulty does not restore.
+        cm.puzzle_difficulty = 257;
+        assert!(<ChainManager>::restore(&cm.snapshot()).is_err());
+    }
+
+    #[test]
+    fn test_restore_rejects_broken_chain() {
+        let mut cm = ChainManager::new();
+        cm.append_block(next_block(&cm)).unwrap();
+        let unlinked = Block { previous_hash: [7u8; 32], ..next_block(&cm) };
+        cm.blocks.push(unlinked);
+        assert!(matches!(
+            <ChainManager>::restore(&cm.snapshot()),
+            Err(SerializationError::InvalidData(msg)) if msg.contains("linked")
+        ));
+
+        cm.blocks.pop();
+        let skipped = Block { block_number: cm.height() + 2, ..next_block(&cm) };
+        cm.blocks.push(skipped);
+        assert!(matches!(
+            <ChainManager>::restore(&cm.snapshot()),
+            Err(SerializationError::InvalidData(msg)) if msg.contains("numbered")
+        ));
+
+        cm.blocks.remove(0);
+        assert!(<ChainManager>::restore(&cm.snapshot()).is_err());
+    }
+
+    #[test]
+    fn test_export_to_file_reads_back() {
+        let mut cm = ChainManager::new();
+        for _ in 0..5 {
+            cm.append_block(next_block(&cm)).unwrap();
+        }
+        let path = std::env::temp_dir().join(format!("reina-chain-export-{}", std::process::id()));
+        cm.export(&path).unwrap();
+        let mut reader = crate::node::chain_export::ChainReader::open(&path).unwrap();
+        assert_eq!(reader.len(), 6);
+        assert_eq!(&reader.block_at(3).unwrap(), &cm.blocks_from(3, 1)[0]);
+        assert_eq!(&reader.block_at(5).unwrap(), cm.head());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_reorg_to_longer_branch() {
+        let mut cm = ChainManager::new();
+        let genesis = cm.head().clone();
+        cm.append_block(next_block(&cm)).unwrap();
+        cm.append_block(next_block(&cm)).unwrap();
+        let branch_a: Vec<Block> = cm.blocks_from(1, 2);
+
+        // Branch B forks from genesis and is one block longer.
+        let mut branch_b = Vec::new();
+        let mut parent = genesis;
+        for _ in 0..3 {
+            let block = Block {
+                block_number: parent.block_number + 1,
+                previous_hash: parent.hash(),
+                signature: vec![0xb],
+                timestamp: parent.timestamp + 7,
+                ..next_block(&cm)
+            };
+            cm.add_candidate(block.clone()).unwrap();
+            parent = block.clone();
+            branch_b.push(block);
+        }
+
+        let reorg = cm.fork_choice().expect("branch B is longer");
+        assert_eq!(reorg.rollback, vec![branch_a[1].clone(), branch_a[0].clone()]);
+        assert_eq!(reorg.apply, branch_b);
+
+        assert_eq!(cm.reorg_to_best(), Some(reorg));
+        assert_eq!(cm.height(), 3);
+        assert_eq!(cm.head(), &branch_b[2]);
+        assert_eq!(cm.fork_choice(), None);
+        assert_eq!(
+            cm.add_candidate(Block { previous_hash: [9u8; 32], ..branch_b[0].clone() }),
+            Err(BlockValidationError::UnknownParent)
+        );
+    }
+
+    /// Returns `len` linked candidates forking from `parent`.
+    fn fork_from(cm: &ChainManager, mut parent: Block, len: usize) -> Vec<Block> {
+        let mut branch = Vec::with_capacity(len);
+        for _ in 0..len {
+            let block = Block {
+                block_number: parent.block_number + 1,
+                previous_hash: parent.hash(),
+                signature: vec![0xc],
+                timestamp: parent.timestamp + 3,
+                ..next_block(cm)
+            };
+            parent = block.clone();
+            branch.push(block);
+        }
+        branch
+    }
+
+    #[test]
+    fn test_reorg_prunes_candidates_at_or_below_head() {
+        let mut cm = ChainManager::new();
+        let genesis = cm.head().clone();
+        cm.append_block(next_block(&cm)).unwrap();
+        let short = fork_from(&cm, genesis.clone(), 1);
+        let long = fork_from(&cm, genesis, 3);
+        for block in short.iter().chain(&long) {
+            cm.add_candidate(block.clone()).unwrap();
+        }
+
+        cm.reorg_to_best().expect("the long branch wins");
+        assert_eq!(cm.head(), &long[2]);
+        assert!(cm.candidates.is_empty());
+        assert!(cm.children.is_empty());
+        assert_eq!(cm.block_index.len(), 4);
+        assert_eq!(cm.block_index[&long[1].hash()], 2);
+
+        // A stale block at the head's height is dropped as soon as the head moves on.
+        cm.add_candidate(fork_from(&cm, long[1].clone(), 1).remove(0)).unwrap();
+        cm.append_block(next_block(&cm)).unwrap();
+        assert!(cm.candidates.is_empty());
+    }
+
+    #[test]
+    fn test_fork_choice_handles_deep_branch() {
+        let mut cm = ChainManager::new();
+        let genesis = cm.head().clone();
+        let branch = fork_from(&cm, genesis, 50_000);
+        for block in &branch {
+            cm.add_candidate(block.clone()).unwrap();
+        }
+        let reorg = cm.fork_choice().expect("the branch is longer");
+        assert_eq!(reorg.apply.len(), branch.len());
+        cm.reorg_to_best();
+        assert_eq!(cm.head(), branch.last().unwrap());
+    }
+
+    #[test]
+    fn test_tx_count_mismatch_rejected() {
+        let mut cm = ChainManager::new();
+        let block = Block { tx_count: 1, ..next_block(&cm) };
+        assert_eq!(cm.append_block(block), Err(BlockValidationError::TxCountMismatch { committed: 1, found: 0 }));
+        assert_eq!(cm.append_block(next_block(&cm)), Ok(()));
+    }
+
+    #[test]
+    fn test_merkle_root_mismatch_rejected() {
+        let mut cm = ChainManager::new();
+        let mut block = next_block(&cm);
+        block.transactions.push(tx(1, 5.0, "Alice", "Bob"));
+        block.tx_count = 1;
+        assert_eq!(cm.validate_block(&block), Err(BlockValidationError::MerkleRootMismatch));
+        block.commit_transactions();
+        assert_eq!(cm.append_block(block), Ok(()));
+    }
+
+    #[test]
+    fn test_from_genesis_config() {
+        let genesis = GenesisConfig {
+            validators: vec![("Validator_A".into(), 100), ("Validator_B".into(), 200), ("Validator_C".into(), 150)],
+            min_fee: 1.5,
+            block_interval_secs: 5,
+        };
+        let decoded = GenesisConfig::from_bytes(&genesis.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, genesis);
+
+        let cm = decoded.chain_manager();
+        let stakes: Vec<(String, u64)> = cm.validators.iter().map(|v| (v.id.clone(), v.stake_amount)).collect();
+        assert_eq!(stakes, genesis.validators);
+        assert_eq!(cm.height(), 0);
+    }
+}
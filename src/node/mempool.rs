@@ -1,54 +1,742 @@
 //! Minimal Mempool for Reina Phase 1.
 //!
-//! This module stores unconfirmed transactions in a simple FIFO Vec.
-//! In future phases, we may switch to a priority queue (e.g., BinaryHeap or BTreeMap)
-//! and add concurrency via Mutex/RwLock. For now, transactions are validated
-//! with a basic fee check and stored in memory.
+//! This module stores unconfirmed transactions keyed by id, with a FIFO queue of
+//! ids (indexed by position, so any id can be dropped from it directly) for
+//! ordering and sender/recipient indices for account queries.
+//! In future phases, we may switch to a priority queue (e.g., BinaryHeap or BTreeMap).
+//! Transactions are checked by a configurable `ValidationPipeline` of `TxRule`s
+//! (by default, a minimum fee), deduplicated by id (or by content hash via
+//! `submit`), and stored in memory. `ConcurrentMempool` wraps a mempool in a
+//! Mutex so it can be shared with network handlers.
 
-use crate::utils::serialization::Transaction;
+use std::any::Any;
+use std::borrow::Borrow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use log::warn;
+
+use crate::roc::sentinel::{check_spam_with, SpamPolicy};
+use crate::utils::bloom::Bloom;
+use crate::utils::serialization::{Endianness, Serializer, Transaction};
+
+/// Minimum fee required by `Mempool::new`.
+pub const DEFAULT_MIN_FEE: f64 = 1.0;
+
+/// Number of ids the seen-filter is sized for by default.
+pub const DEFAULT_BLOOM_CAPACITY: usize = 10_000;
+
+/// Target false-positive rate of the default seen-filter.
+pub const DEFAULT_BLOOM_FP_RATE: f64 = 0.01;
+
+/// Why a transaction was not admitted to the mempool.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RejectReason {
+    /// The fee is NaN or infinite.
+    NonFiniteFee,
+    /// The fee is below the mempool's configured minimum.
+    FeeTooLow { fee: f64, min_fee: f64 },
+    /// A transaction with the same id is already pending.
+    Duplicate,
+    /// The transaction failed the sentinel spam checks.
+    Spam,
+    /// Block production is behind: `pending` transactions are already waiting.
+    Backpressure { pending: usize },
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::NonFiniteFee => write!(f, "Fee is not a finite number"),
+            RejectReason::FeeTooLow { fee, min_fee } => write!(f, "Fee {} is below the minimum {}", fee, min_fee),
+            RejectReason::Duplicate => write!(f, "Transaction is already pending"),
+            RejectReason::Spam => write!(f, "Transaction failed spam checks"),
+            RejectReason::Backpressure { pending } => {
+                write!(f, "Block production is behind with {} transactions pending", pending)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RejectReason {}
+
+/// Outcome of `Mempool::submit`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubmitResult {
+    /// The transaction was admitted.
+    Accepted,
+    /// A transaction with the same content hash is already pending, so a
+    /// resubmission succeeded without adding a second copy.
+    AlreadyPending,
+    /// The transaction was not admitted.
+    Rejected(RejectReason),
+}
+
+/// A single admission check run by a `ValidationPipeline`.
+pub trait TxRule: Any + Send + Sync {
+    /// Returns why `tx` must be rejected, or `Ok(())` if the rule passes.
+    fn check(&self, tx: &Transaction) -> Result<(), RejectReason>;
+}
+
+/// Rejects transactions whose fee is non-finite or below `min_fee`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeRule {
+    pub min_fee: f64,
+}
+
+impl TxRule for FeeRule {
+    fn check(&self, tx: &Transaction) -> Result<(), RejectReason> {
+        if tx.validate().is_err() {
+            return Err(RejectReason::NonFiniteFee);
+        }
+        if tx.fee < self.min_fee {
+            return Err(RejectReason::FeeTooLow { fee: tx.fee, min_fee: self.min_fee });
+        }
+        Ok(())
+    }
+}
+
+/// Rejects transactions that fail `sentinel::check_spam_with` under `policy`.
+#[derive(Debug, Clone, Default)]
+pub struct SentinelRule {
+    pub policy: SpamPolicy,
+}
+
+impl TxRule for SentinelRule {
+    fn check(&self, tx: &Transaction) -> Result<(), RejectReason> {
+        if check_spam_with(tx, &self.policy) {
+            Ok(())
+        } else {
+            Err(RejectReason::Spam)
+        }
+    }
+}
+
+/// An ordered list of rules; a transaction is admitted only if every rule passes.
+#[derive(Default)]
+pub struct ValidationPipeline {
+    rules: Vec<Box<dyn TxRule>>,
+}
+
+impl ValidationPipeline {
+    /// Creates a pipeline with no rules, which admits every transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `rule`, to be run after the rules already present.
+    pub fn with_rule<R: TxRule>(mut self, rule: R) -> Self {
+        self.push(rule);
+        self
+    }
+
+    /// Appends `rule`, to be run after the rules already present.
+    pub fn push<R: TxRule>(&mut self, rule: R) {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Returns the number of rules in the pipeline.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns true if the pipeline has no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Runs the rules in order, returning the first rejection.
+    pub fn check(&self, tx: &Transaction) -> Result<(), RejectReason> {
+        self.rules.iter().try_for_each(|rule| rule.check(tx))
+    }
+
+    /// Returns the first rule of type `R`, if any.
+    pub fn rule<R: TxRule>(&self) -> Option<&R> {
+        self.rules.iter().find_map(|rule| (rule.as_ref() as &dyn Any).downcast_ref())
+    }
+
+    /// Returns the first rule of type `R` mutably, if any.
+    pub fn rule_mut<R: TxRule>(&mut self) -> Option<&mut R> {
+        self.rules.iter_mut().find_map(|rule| (rule.as_mut() as &mut dyn Any).downcast_mut())
+    }
+}
+
+/// Raises the admission fee floor as the mempool fills:
+/// `effective_min_fee = min_fee * (1 + occupancy * factor)`, where occupancy is
+/// the pending count over `capacity`, clamped to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CongestionPricing {
+    /// Pending count at which the floor reaches its maximum.
+    pub capacity: usize,
+    /// How steeply the floor rises; at full occupancy it is `1 + factor` times the base.
+    pub factor: f64,
+}
+
+impl CongestionPricing {
+    /// Returns `base_fee` scaled for `pending` transactions.
+    pub fn floor(&self, base_fee: f64, pending: usize) -> f64 {
+        let occupancy = (pending as f64 / self.capacity.max(1) as f64).min(1.0);
+        base_fee * (1.0 + occupancy * self.factor)
+    }
+}
+
+/// How the mempool prioritises transactions when assembling a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeOrdering {
+    /// Highest absolute `fee` first (see `canonical_order`).
+    #[default]
+    Absolute,
+    /// Highest `fee_per_byte` first, ties broken by ascending `id`. Packs more
+    /// fee into a block of limited size than ordering by absolute fee.
+    PerByte,
+}
+
+impl FeeOrdering {
+    /// Returns the value `tx` is ranked by under this ordering, higher first.
+    pub fn priority(self, tx: &Transaction) -> f64 {
+        match self {
+            FeeOrdering::Absolute => tx.fee,
+            FeeOrdering::PerByte => tx.fee_per_byte(),
+        }
+    }
+
+    /// Sorts `transactions` highest priority first, ties broken by ascending `id`.
+    pub fn sort(self, transactions: &mut [Transaction]) {
+        self.sort_by_priority(transactions);
+    }
+
+    /// Like `sort`, for owned or borrowed transactions. Each priority is
+    /// computed once, so `PerByte` does not re-encode a transaction per comparison.
+    fn sort_by_priority<T: Borrow<Transaction>>(self, transactions: &mut [T]) {
+        transactions.sort_by_cached_key(|tx| {
+            let tx = tx.borrow();
+            (Reverse(TotalF64(self.priority(tx))), tx.id)
+        });
+    }
+}
+
+/// An `f64` ordered by `total_cmp`, so it can serve as a sort key.
+struct TotalF64(f64);
+
+impl PartialEq for TotalF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TotalF64 {}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
 
 /// A minimal mempool to hold unconfirmed transactions.
 pub struct Mempool {
-    transactions: Vec<Transaction>,
+    /// Pending transactions keyed by id.
+    transactions: HashMap<u64, Transaction>,
+    /// Transaction ids keyed by queue position, in arrival (FIFO) order.
+    /// `reinsert` takes positions below the front, so they may be negative.
+    order: BTreeMap<i64, u64>,
+    /// Queue position of each pending id, for removal from `order`.
+    positions: HashMap<u64, i64>,
+    /// Pending transaction ids per sender, in arrival order.
+    by_sender: HashMap<String, Vec<u64>>,
+    /// Pending transaction ids per recipient, in arrival order.
+    by_recipient: HashMap<String, Vec<u64>>,
+    /// Pending transaction ids per content hash, in arrival order.
+    by_content: HashMap<[u8; 32], Vec<u64>>,
+    /// Admission rules run by `try_add_transaction`.
+    pipeline: ValidationPipeline,
+    /// Ids added since the pool was last empty or the filter last rebuilt;
+    /// screens `contains` lookups.
+    seen: Bloom,
+    /// Ids and false-positive rate the seen-filter is configured for.
+    seen_capacity: usize,
+    seen_fp_rate: f64,
+    /// Inserts into `seen` since it was last cleared or rebuilt.
+    seen_inserts: usize,
+    /// Insert count at which `seen` is rebuilt from the pending ids: the
+    /// configured capacity, or twice the pending count if that is larger.
+    seen_limit: usize,
+    /// Occupancy-based fee floor on top of the pipeline's `FeeRule`, if enabled.
+    congestion: Option<CongestionPricing>,
+    /// Priority order used by `select_for_block` and `reinsert`.
+    ordering: FeeOrdering,
+}
+
+impl Default for Mempool {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mempool {
-    /// Creates a new, empty mempool.
+    /// Creates a new, empty mempool with the default minimum fee.
     pub fn new() -> Self {
-        Self { transactions: Vec::new() }
+        Self::with_min_fee(DEFAULT_MIN_FEE)
+    }
+
+    /// Creates a new, empty mempool that rejects transactions paying less than `min_fee`.
+    pub fn with_min_fee(min_fee: f64) -> Self {
+        Self::with_pipeline(ValidationPipeline::new().with_rule(FeeRule { min_fee }))
+    }
+
+    /// Creates a new, empty mempool that admits transactions passing every rule in `pipeline`.
+    pub fn with_pipeline(pipeline: ValidationPipeline) -> Self {
+        Self {
+            transactions: HashMap::new(),
+            order: BTreeMap::new(),
+            positions: HashMap::new(),
+            by_sender: HashMap::new(),
+            by_recipient: HashMap::new(),
+            by_content: HashMap::new(),
+            pipeline,
+            seen: Bloom::new(DEFAULT_BLOOM_CAPACITY, DEFAULT_BLOOM_FP_RATE),
+            seen_capacity: DEFAULT_BLOOM_CAPACITY,
+            seen_fp_rate: DEFAULT_BLOOM_FP_RATE,
+            seen_inserts: 0,
+            seen_limit: DEFAULT_BLOOM_CAPACITY,
+            congestion: None,
+            ordering: FeeOrdering::default(),
+        }
+    }
+
+    /// Sets the priority order used when selecting transactions for a block.
+    pub fn with_ordering(mut self, ordering: FeeOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Returns the priority order used when selecting transactions for a block.
+    pub fn ordering(&self) -> FeeOrdering {
+        self.ordering
+    }
+
+    /// Writes the pending transactions to `path` in FIFO order, as a
+    /// `Serializer::serialize_batch` buffer. The file is written under a
+    /// temporary name and renamed into place, so a crash mid-save leaves the
+    /// previous file intact.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let pending: Vec<Transaction> = self.iter().cloned().collect();
+        let bytes = Serializer::serialize_batch(&pending, Endianness::Little)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let staging = path.with_extension("tmp");
+        fs::write(&staging, bytes)?;
+        fs::rename(staging, path)
+    }
+
+    /// Restores a mempool saved by `save`, re-admitting each transaction
+    /// through the default pipeline with `min_fee`. Transactions that no
+    /// longer pass are logged and dropped; a corrupt file is reported as
+    /// `io::ErrorKind::InvalidData`.
+    pub fn load(path: impl AsRef<Path>, min_fee: f64) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let saved: Vec<Transaction> = Serializer::deserialize_batch(&bytes, Endianness::Little)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut mempool = Self::with_min_fee(min_fee);
+        for tx in saved {
+            let id = tx.id;
+            if let Err(reason) = mempool.try_add_transaction(tx) {
+                warn!("Dropping saved transaction {}: {}", id, reason);
+            }
+        }
+        Ok(mempool)
+    }
+
+    /// Resizes the seen-filter for `expected_capacity` ids at roughly
+    /// `false_positive_rate`, re-adding every pending id.
+    pub fn configure_bloom(&mut self, expected_capacity: usize, false_positive_rate: f64) {
+        self.seen_capacity = expected_capacity;
+        self.seen_fp_rate = false_positive_rate;
+        self.rebuild_seen();
+    }
+
+    /// Records `id` in the seen-filter. Removed ids stay in the filter, so
+    /// once it has taken more inserts than it is sized for it is rebuilt from
+    /// the pending ids to keep its false-positive rate near the target.
+    fn mark_seen(&mut self, id: u64) {
+        self.seen.insert(id);
+        self.seen_inserts += 1;
+        if self.seen_inserts > self.seen_limit {
+            self.rebuild_seen();
+        }
+    }
+
+    /// Replaces the seen-filter with one holding only the pending ids.
+    fn rebuild_seen(&mut self) {
+        self.seen_limit = self.seen_capacity.max(self.transactions.len() * 2);
+        self.seen = Bloom::new(self.seen_limit, self.seen_fp_rate);
+        for &id in self.transactions.keys() {
+            self.seen.insert(id);
+        }
+        self.seen_inserts = self.transactions.len();
+    }
+
+    /// Returns false if `id` is not pending and has not been added since the
+    /// pool was last empty or the seen-filter last rebuilt. True means `id`
+    /// may be pending; `contains` confirms it.
+    pub fn probably_contains(&self, id: u64) -> bool {
+        self.seen.contains(id)
+    }
+
+    /// Returns the admission rules.
+    pub fn pipeline(&self) -> &ValidationPipeline {
+        &self.pipeline
+    }
+
+    /// Returns the admission rules for reconfiguration. Already-pending
+    /// transactions are not re-checked.
+    pub fn pipeline_mut(&mut self) -> &mut ValidationPipeline {
+        &mut self.pipeline
+    }
+
+    /// Returns the minimum fee enforced by the pipeline's `FeeRule`, or 0.0 if it has none.
+    pub fn min_fee(&self) -> f64 {
+        self.pipeline.rule::<FeeRule>().map_or(0.0, |rule| rule.min_fee)
+    }
+
+    /// Adjusts the minimum fee, adding a `FeeRule` to the pipeline if it has none.
+    /// Already-pending transactions are not re-checked.
+    pub fn set_min_fee(&mut self, min_fee: f64) {
+        match self.pipeline.rule_mut::<FeeRule>() {
+            Some(rule) => rule.min_fee = min_fee,
+            None => self.pipeline.push(FeeRule { min_fee }),
+        }
+    }
+
+    /// Enables congestion pricing, so the fee floor rises with occupancy
+    /// (see `CongestionPricing`). Already-pending transactions are not re-checked.
+    pub fn set_congestion_pricing(&mut self, pricing: CongestionPricing) {
+        self.congestion = Some(pricing);
+    }
+
+    /// Returns the fee a new transaction must pay right now: `min_fee` scaled
+    /// by congestion pricing for the current occupancy, or `min_fee` alone
+    /// when congestion pricing is off.
+    pub fn effective_min_fee(&self) -> f64 {
+        let base = self.min_fee();
+        self.congestion.as_ref().map_or(base, |pricing| pricing.floor(base, self.transactions.len()))
     }
 
     /// Validates a transaction.
-    /// Currently, a transaction is valid if its fee is at least 1.0.
-    /// Future enhancements will integrate advanced spam detection.
+    /// A transaction is valid if it passes every rule in the pipeline and pays
+    /// at least `effective_min_fee`.
     pub fn validate_transaction(&self, tx: &Transaction) -> bool {
-        tx.fee >= 1.0
+        self.check_admission(tx).is_ok()
+    }
+
+    /// Runs the pipeline, then the congestion floor.
+    fn check_admission(&self, tx: &Transaction) -> Result<(), RejectReason> {
+        // Checked first: NaN compares false against the congestion floor, and
+        // the pipeline may have no `FeeRule` to catch it.
+        if !tx.fee.is_finite() {
+            return Err(RejectReason::NonFiniteFee);
+        }
+        self.pipeline.check(tx)?;
+        let min_fee = self.effective_min_fee();
+        if tx.fee < min_fee {
+            return Err(RejectReason::FeeTooLow { fee: tx.fee, min_fee });
+        }
+        Ok(())
+    }
+
+    /// Returns true if a transaction with the given id is pending. The exact
+    /// lookup only runs when the seen-filter reports a possible hit.
+    pub fn contains(&self, id: u64) -> bool {
+        self.probably_contains(id) && self.transactions.contains_key(&id)
     }
 
     /// Adds a transaction to the mempool.
-    /// Returns true if the transaction is valid and inserted.
+    /// Returns true if the transaction is valid, not already pending, and inserted.
     pub fn add_transaction(&mut self, tx: Transaction) -> bool {
-        if self.validate_transaction(&tx) {
-            self.transactions.push(tx);
-            true
-        } else {
-            false
+        self.try_add_transaction(tx).is_ok()
+    }
+
+    /// Adds a transaction to the mempool, reporting why it was rejected if it was.
+    pub fn try_add_transaction(&mut self, tx: Transaction) -> Result<(), RejectReason> {
+        self.check_admission(&tx)?;
+        if self.contains(tx.id) {
+            return Err(RejectReason::Duplicate);
+        }
+        self.by_sender.entry(tx.sender.clone()).or_default().push(tx.id);
+        self.by_recipient.entry(tx.recipient.clone()).or_default().push(tx.id);
+        self.by_content.entry(tx.content_hash()).or_default().push(tx.id);
+        self.enqueue_back(tx.id);
+        let id = tx.id;
+        self.transactions.insert(id, tx);
+        self.mark_seen(id);
+        Ok(())
+    }
+
+    /// Submits a transaction so that retries are safe: if a transaction with
+    /// the same content hash (see `Transaction::content_hash`) is already
+    /// pending, the submission reports `AlreadyPending` instead of adding a
+    /// copy or rejecting it as a duplicate.
+    pub fn submit(&mut self, tx: Transaction) -> SubmitResult {
+        if self.by_content.contains_key(&tx.content_hash()) {
+            return SubmitResult::AlreadyPending;
         }
+        match self.try_add_transaction(tx) {
+            Ok(()) => SubmitResult::Accepted,
+            Err(reason) => SubmitResult::Rejected(reason),
+        }
+    }
+
+    /// Adds each transaction in turn. The result at index `i` is the outcome
+    /// for `txs[i]`; earlier items in the batch count as pending for duplicate checks.
+    pub fn add_transactions(&mut self, txs: Vec<Transaction>) -> Vec<Result<(), RejectReason>> {
+        txs.into_iter().map(|tx| self.try_add_transaction(tx)).collect()
     }
 
     /// Removes and returns the earliest transaction (FIFO) from the mempool.
     pub fn remove_transaction(&mut self) -> Option<Transaction> {
-        if !self.transactions.is_empty() {
-            Some(self.transactions.remove(0))
-        } else {
-            None
+        let (_, id) = self.order.pop_first()?;
+        self.positions.remove(&id);
+        let tx = self.transactions.remove(&id).expect("queued id is pending");
+        unindex(&mut self.by_sender, &tx.sender, id);
+        unindex(&mut self.by_recipient, &tx.recipient, id);
+        unindex(&mut self.by_content, &tx.content_hash(), id);
+        if self.transactions.is_empty() {
+            // Removed ids stay in the filter; start fresh whenever the pool drains.
+            self.seen.clear();
+            self.seen_inserts = 0;
         }
+        Some(tx)
+    }
+
+    /// Removes and returns up to `max` transactions in FIFO order, taking at
+    /// most `max_per_sender` from any one sender (`None` for no limit).
+    /// Skipped transactions stay pending, in their original order.
+    pub fn take_fifo(&mut self, max: usize, max_per_sender: Option<usize>) -> Vec<Transaction> {
+        let ids: Vec<u64> = limit_per_sender(self.iter(), max_per_sender).take(max).map(|tx| tx.id).collect();
+        ids.into_iter().filter_map(|id| self.remove_by_id(id)).collect()
+    }
+
+    /// Removes and returns up to `max` transactions in the mempool's
+    /// `FeeOrdering`, for assembling a block.
+    pub fn select_for_block(&mut self, max: usize) -> Vec<Transaction> {
+        self.select_for_block_capped(max, None)
+    }
+
+    /// Like `select_for_block`, but takes at most `max_per_sender` transactions
+    /// from any one sender; the rest stay pending for later blocks.
+    pub fn select_for_block_capped(&mut self, max: usize, max_per_sender: Option<usize>) -> Vec<Transaction> {
+        let selected = self.peek_for_block(max, max_per_sender);
+        for tx in &selected {
+            self.remove_by_id(tx.id);
+        }
+        selected
+    }
+
+    /// Returns copies of the transactions `select_for_block_capped` would
+    /// take, in the same order, without removing them.
+    pub fn peek_for_block(&self, max: usize, max_per_sender: Option<usize>) -> Vec<Transaction> {
+        let mut candidates: Vec<&Transaction> = self.transactions.values().collect();
+        self.ordering.sort_by_priority(&mut candidates);
+        limit_per_sender(candidates.into_iter(), max_per_sender).take(max).cloned().collect()
+    }
+
+    /// Returns previously pulled transactions to the pending set, e.g. after the
+    /// block that included them was rejected. They skip the validation pipeline,
+    /// since they were admitted once, and go to the front of the FIFO queue in
+    /// fee-priority order. A transaction whose id has meanwhile been re-added is
+    /// left out in favour of the pending copy.
+    pub fn reinsert(&mut self, mut txs: Vec<Transaction>) {
+        self.ordering.sort(&mut txs);
+        for tx in txs.into_iter().rev() {
+            if self.contains(tx.id) {
+                continue;
+            }
+            self.by_sender.entry(tx.sender.clone()).or_default().insert(0, tx.id);
+            self.by_recipient.entry(tx.recipient.clone()).or_default().insert(0, tx.id);
+            self.by_content.entry(tx.content_hash()).or_default().insert(0, tx.id);
+            self.enqueue_front(tx.id);
+            let id = tx.id;
+            self.transactions.insert(id, tx);
+            self.mark_seen(id);
+        }
+    }
+
+    fn remove_by_id(&mut self, id: u64) -> Option<Transaction> {
+        let tx = self.transactions.remove(&id)?;
+        if let Some(position) = self.positions.remove(&id) {
+            self.order.remove(&position);
+        }
+        unindex(&mut self.by_sender, &tx.sender, id);
+        unindex(&mut self.by_recipient, &tx.recipient, id);
+        unindex(&mut self.by_content, &tx.content_hash(), id);
+        if self.transactions.is_empty() {
+            self.seen.clear();
+            self.seen_inserts = 0;
+        }
+        Some(tx)
+    }
+
+    /// Queues `id` behind every pending transaction.
+    fn enqueue_back(&mut self, id: u64) {
+        let position = self.order.last_key_value().map_or(0, |(&last, _)| last + 1);
+        self.order.insert(position, id);
+        self.positions.insert(id, position);
+    }
+
+    /// Queues `id` ahead of every pending transaction.
+    fn enqueue_front(&mut self, id: u64) {
+        let position = self.order.first_key_value().map_or(0, |(&first, _)| first - 1);
+        self.order.insert(position, id);
+        self.positions.insert(id, position);
+    }
+
+    /// Returns the earliest transaction without removing it.
+    pub fn peek(&self) -> Option<&Transaction> {
+        self.order.values().next().map(|id| &self.transactions[id])
+    }
+
+    /// Iterates over pending transactions in FIFO order without removing them.
+    pub fn iter(&self) -> impl Iterator<Item = &Transaction> {
+        self.order.values().map(|id| &self.transactions[id])
+    }
+
+    /// Returns pending transactions sent by `sender`, in arrival order.
+    pub fn transactions_from(&self, sender: &str) -> Vec<&Transaction> {
+        self.lookup(&self.by_sender, sender)
+    }
+
+    /// Returns pending transactions paying `recipient`, in arrival order.
+    pub fn transactions_to(&self, recipient: &str) -> Vec<&Transaction> {
+        self.lookup(&self.by_recipient, recipient)
+    }
+
+    fn lookup(&self, index: &HashMap<String, Vec<u64>>, address: &str) -> Vec<&Transaction> {
+        index
+            .get(address)
+            .map(|ids| ids.iter().map(|id| &self.transactions[id]).collect())
+            .unwrap_or_default()
     }
 
     /// Returns the current number of transactions in the mempool.
     pub fn size(&self) -> usize {
         self.transactions.len()
     }
+
+    /// Counts pending transactions per fee bucket. `buckets` holds ascending
+    /// lower bounds: bucket `i` covers `[buckets[i], buckets[i + 1])` and the
+    /// last bucket is open-ended. Fees below `buckets[0]` are not counted.
+    pub fn fee_histogram(&self, buckets: &[f64]) -> Vec<usize> {
+        let mut counts = vec![0; buckets.len()];
+        for tx in self.transactions.values() {
+            // Number of lower bounds at or below the fee; zero means below every bucket.
+            let above = buckets.partition_point(|&bound| bound <= tx.fee);
+            if above > 0 {
+                counts[above - 1] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Estimates the fee needed to be included within `target_blocks` blocks of
+    /// `txs_per_block` transactions each, assuming blocks take the highest-fee
+    /// transactions first. Returns the fee of the last pending transaction that
+    /// would still fit, or the effective minimum fee when everything pending fits.
+    pub fn estimate_fee(&self, target_blocks: u32, txs_per_block: usize) -> f64 {
+        let capacity = (target_blocks as usize).saturating_mul(txs_per_block).max(1);
+        if self.transactions.len() < capacity {
+            return self.effective_min_fee();
+        }
+        let mut fees: Vec<f64> = self.transactions.values().map(|tx| tx.fee).collect();
+        fees.sort_by(|a, b| b.total_cmp(a));
+        fees[capacity - 1].max(self.effective_min_fee())
+    }
+}
+
+/// Removes `id` from `key`'s entry in `index`, dropping the entry once empty.
+fn unindex<K, Q>(index: &mut HashMap<K, Vec<u64>>, key: &Q, id: u64)
+where
+    K: Borrow<Q> + Hash + Eq,
+    Q: Hash + Eq + ?Sized,
+{
+    if let Some(ids) = index.get_mut(key) {
+        ids.retain(|&pending| pending != id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+/// Filters `txs` down to the first `max_per_sender` transactions from each
+/// sender, keeping their relative order. `None` passes everything through.
+pub fn limit_per_sender<'a>(
+    txs: impl Iterator<Item = &'a Transaction>,
+    max_per_sender: Option<usize>,
+) -> impl Iterator<Item = &'a Transaction> {
+    let mut taken: HashMap<&'a str, usize> = HashMap::new();
+    txs.filter(move |tx| {
+        let count = taken.entry(tx.sender.as_str()).or_default();
+        *count += 1;
+        max_per_sender.is_none_or(|cap| *count <= cap)
+    })
+}
+
+/// A thread-safe mempool shared between the node and its network handlers.
+#[derive(Default)]
+pub struct ConcurrentMempool {
+    inner: Mutex<Mempool>,
+}
+
+impl ConcurrentMempool {
+    /// Wraps an existing mempool.
+    pub fn new(mempool: Mempool) -> Self {
+        Self { inner: Mutex::new(mempool) }
+    }
+
+    /// Adds a transaction; see `Mempool::add_transaction`.
+    pub fn add_transaction(&self, tx: Transaction) -> bool {
+        self.inner.lock().unwrap().add_transaction(tx)
+    }
+
+    /// Submits a transaction; see `Mempool::submit`.
+    pub fn submit(&self, tx: Transaction) -> SubmitResult {
+        self.inner.lock().unwrap().submit(tx)
+    }
+
+    /// Adds a batch under a single lock; see `Mempool::add_transactions`.
+    pub fn add_transactions(&self, txs: Vec<Transaction>) -> Vec<Result<(), RejectReason>> {
+        self.inner.lock().unwrap().add_transactions(txs)
+    }
+
+    /// Removes and returns the earliest transaction; see `Mempool::remove_transaction`.
+    pub fn remove_transaction(&self) -> Option<Transaction> {
+        self.inner.lock().unwrap().remove_transaction()
+    }
+
+    /// Returns true if a transaction with the given id is pending.
+    pub fn contains(&self, id: u64) -> bool {
+        self.inner.lock().unwrap().contains(id)
+    }
+
+    /// Returns the current number of pending transactions.
+    pub fn size(&self) -> usize {
+        self.inner.lock().unwrap().size()
+    }
+
+    /// Returns the fee floor new transactions must meet; see `Mempool::effective_min_fee`.
+    pub fn effective_min_fee(&self) -> f64 {
+        self.inner.lock().unwrap().effective_min_fee()
+    }
 }
 
 #[cfg(test)]
@@ -62,9 +750,10 @@ mod tests {
             amount: 1000,
             fee,
             version: 1,
+            nonce: 0,
             sender: "Alice".to_string(),
             recipient: "Bob".to_string(),
-            signature: vec![1, 2, 3, 4],
+            signature: vec![1, 2, 3, 4].into(),
         }
     }
 
@@ -96,4 +785,349 @@ mod tests {
         assert!(!mempool.add_transaction(tx));
         assert_eq!(mempool.size(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_duplicate_id_rejected() {
+        let mut mempool = Mempool::new();
+        assert!(mempool.add_transaction(dummy_tx(1, 5.0)));
+        assert!(!mempool.add_transaction(dummy_tx(1, 7.0)));
+        assert_eq!(mempool.size(), 1);
+        assert!(mempool.contains(1));
+    }
+
+    #[test]
+    fn test_submit_accepts_first_submission() {
+        let mut mempool = Mempool::new();
+        assert_eq!(mempool.submit(dummy_tx(1, 5.0)), SubmitResult::Accepted);
+        assert!(mempool.contains(1));
+        assert_eq!(
+            mempool.submit(dummy_tx(2, 0.5)),
+            SubmitResult::Rejected(RejectReason::FeeTooLow { fee: 0.5, min_fee: DEFAULT_MIN_FEE })
+        );
+    }
+
+    #[test]
+    fn test_submit_reports_identical_resubmission_pending() {
+        let mut mempool = Mempool::new();
+        let tx = dummy_tx(1, 5.0);
+        assert_eq!(mempool.submit(tx.clone()), SubmitResult::Accepted);
+        assert_eq!(mempool.submit(tx.clone()), SubmitResult::AlreadyPending);
+        // A wallet that re-signs under a new id is still resubmitting the same transfer.
+        let reissued = Transaction { id: 9, signature: vec![9; 4].into(), ..tx };
+        assert_eq!(mempool.submit(reissued), SubmitResult::AlreadyPending);
+        assert_eq!(mempool.size(), 1);
+        // Once the original is taken for a block, the content is free again.
+        mempool.remove_transaction();
+        assert_eq!(mempool.submit(dummy_tx(1, 5.0)), SubmitResult::Accepted);
+    }
+
+    #[test]
+    fn test_submit_accepts_content_different_transaction() {
+        let mut mempool = Mempool::new();
+        let tx = dummy_tx(1, 5.0);
+        assert_eq!(mempool.submit(tx.clone()), SubmitResult::Accepted);
+        let next = Transaction { id: 2, nonce: 1, ..tx };
+        assert_eq!(mempool.submit(next), SubmitResult::Accepted);
+        assert_eq!(mempool.size(), 2);
+    }
+
+    #[test]
+    fn test_infinite_fee_rejected() {
+        let mut mempool = Mempool::new();
+        assert!(!mempool.add_transaction(dummy_tx(1, f64::INFINITY)));
+        assert!(!mempool.add_transaction(dummy_tx(2, f64::NAN)));
+    }
+
+    #[test]
+    fn test_configured_min_fee() {
+        let mut mempool = Mempool::with_min_fee(5.0);
+        assert!(!mempool.add_transaction(dummy_tx(1, 3.0)));
+        assert!(mempool.add_transaction(dummy_tx(2, 5.0)));
+        mempool.set_min_fee(2.0);
+        assert!(mempool.add_transaction(dummy_tx(3, 3.0)));
+        assert_eq!(mempool.size(), 2);
+    }
+
+    fn ids(txs: Vec<&Transaction>) -> Vec<u64> {
+        txs.iter().map(|tx| tx.id).collect()
+    }
+
+    #[test]
+    fn test_address_indices() {
+        let mut mempool = Mempool::new();
+        for (id, sender, recipient) in [(1, "Alice", "Bob"), (2, "Carol", "Alice"), (3, "Alice", "Carol"), (4, "Bob", "Bob")] {
+            let mut tx = dummy_tx(id, 5.0);
+            tx.sender = sender.to_string();
+            tx.recipient = recipient.to_string();
+            assert!(mempool.add_transaction(tx));
+        }
+        assert_eq!(ids(mempool.transactions_from("Alice")), vec![1, 3]);
+        assert_eq!(ids(mempool.transactions_to("Alice")), vec![2]);
+        assert_eq!(ids(mempool.transactions_to("Bob")), vec![1, 4]);
+        assert_eq!(ids(mempool.transactions_from("Bob")), vec![4]);
+        assert!(mempool.transactions_from("Dave").is_empty());
+
+        assert_eq!(mempool.remove_transaction().map(|tx| tx.id), Some(1));
+        assert_eq!(ids(mempool.transactions_from("Alice")), vec![3]);
+        assert_eq!(ids(mempool.transactions_to("Bob")), vec![4]);
+        assert_eq!(mempool.peek().map(|tx| tx.id), Some(2));
+    }
+
+    #[test]
+    fn test_batch_add_reports_each_outcome() {
+        let mut mempool = Mempool::new();
+        let results = mempool.add_transactions(vec![dummy_tx(1, 5.0), dummy_tx(2, 0.5), dummy_tx(1, 6.0)]);
+        assert_eq!(
+            results,
+            vec![Ok(()), Err(RejectReason::FeeTooLow { fee: 0.5, min_fee: 1.0 }), Err(RejectReason::Duplicate)]
+        );
+        assert_eq!(mempool.size(), 1);
+    }
+
+    #[test]
+    fn test_pipeline_reports_failing_rule() {
+        let pipeline = ValidationPipeline::new().with_rule(FeeRule { min_fee: 1.0 }).with_rule(SentinelRule::default());
+        let mut mempool = Mempool::with_pipeline(pipeline);
+        assert!(mempool.add_transaction(dummy_tx(1, 5.0)));
+
+        // Passes the fee rule but fails the sentinel rule.
+        let mut self_transfer = dummy_tx(2, 5.0);
+        self_transfer.recipient = self_transfer.sender.clone();
+        assert_eq!(mempool.try_add_transaction(self_transfer), Err(RejectReason::Spam));
+
+        // The fee rule runs first, so its reason wins when both fail.
+        let mut both = dummy_tx(3, 0.5);
+        both.recipient = both.sender.clone();
+        assert_eq!(mempool.try_add_transaction(both), Err(RejectReason::FeeTooLow { fee: 0.5, min_fee: 1.0 }));
+        assert_eq!(mempool.size(), 1);
+    }
+
+    #[test]
+    fn test_set_min_fee_updates_pipeline_rule() {
+        let mut mempool = Mempool::with_pipeline(ValidationPipeline::new());
+        assert_eq!(mempool.min_fee(), 0.0);
+        assert!(mempool.add_transaction(dummy_tx(1, 0.5)));
+        mempool.set_min_fee(2.0);
+        assert_eq!(mempool.pipeline().len(), 1);
+        assert!(!mempool.add_transaction(dummy_tx(2, 1.0)));
+        mempool.set_min_fee(0.5);
+        assert_eq!(mempool.pipeline().len(), 1);
+        assert!(mempool.add_transaction(dummy_tx(3, 1.0)));
+    }
+
+    #[test]
+    fn test_fee_histogram_and_estimate() {
+        let mut mempool = Mempool::new();
+        for (id, fee) in [(1, 1.0), (2, 1.5), (3, 2.0), (4, 5.0), (5, 5.0), (6, 9.0), (7, 20.0), (8, 50.0)] {
+            assert!(mempool.add_transaction(dummy_tx(id, fee)));
+        }
+        assert_eq!(mempool.fee_histogram(&[1.0, 2.0, 5.0, 10.0]), vec![2, 1, 3, 2]);
+        // Fees below the first bound are left out.
+        assert_eq!(mempool.fee_histogram(&[5.0, 10.0]), vec![3, 2]);
+        assert!(mempool.fee_histogram(&[]).is_empty());
+
+        // Two blocks of two: the four highest fees are 50, 20, 9 and 5.
+        assert_eq!(mempool.estimate_fee(2, 2), 5.0);
+        assert_eq!(mempool.estimate_fee(1, 1), 50.0);
+        // A longer horizon never needs a higher fee.
+        assert!(mempool.estimate_fee(3, 2) <= mempool.estimate_fee(2, 2));
+        // Room for everything pending: the minimum fee suffices.
+        assert_eq!(mempool.estimate_fee(5, 2), mempool.min_fee());
+    }
+
+    #[test]
+    fn test_congestion_pricing_raises_floor() {
+        let mut mempool = Mempool::with_min_fee(10.0);
+        mempool.set_congestion_pricing(CongestionPricing { capacity: 10, factor: 2.0 });
+        assert_eq!(mempool.effective_min_fee(), 10.0);
+        assert!(mempool.add_transaction(dummy_tx(1, 10.0)));
+        // One of ten slots taken: 10 * (1 + 0.1 * 2).
+        assert!((mempool.effective_min_fee() - 12.0).abs() < 1e-9);
+
+        for id in 2..=8 {
+            assert!(mempool.add_transaction(dummy_tx(id, 100.0)));
+        }
+        // At 80% occupancy the floor is 26; a fee clearing the base no longer does.
+        let floor = mempool.effective_min_fee();
+        assert!((floor - 26.0).abs() < 1e-9);
+        assert!(!mempool.validate_transaction(&dummy_tx(9, 20.0)));
+        assert_eq!(
+            mempool.try_add_transaction(dummy_tx(9, 20.0)),
+            Err(RejectReason::FeeTooLow { fee: 20.0, min_fee: floor })
+        );
+        assert!(mempool.add_transaction(dummy_tx(9, 30.0)));
+
+        // Past capacity the floor stops rising.
+        for id in 10..=15 {
+            assert!(mempool.add_transaction(dummy_tx(id, 100.0)));
+        }
+        assert_eq!(mempool.effective_min_fee(), 30.0);
+
+        // A NaN fee is refused even when no pipeline rule looks at fees.
+        let mut open = Mempool::with_pipeline(ValidationPipeline::new());
+        open.set_congestion_pricing(CongestionPricing { capacity: 10, factor: 2.0 });
+        assert_eq!(open.try_add_transaction(dummy_tx(1, f64::NAN)), Err(RejectReason::NonFiniteFee));
+        assert_eq!(open.try_add_transaction(dummy_tx(2, f64::INFINITY)), Err(RejectReason::NonFiniteFee));
+        assert_eq!(open.size(), 0);
+    }
+
+    #[test]
+    fn test_bloom_has_no_false_negatives() {
+        let mut mempool = Mempool::new();
+        mempool.configure_bloom(1_000, 0.01);
+        for id in 0..1_000 {
+            assert!(mempool.add_transaction(dummy_tx(id * 7919, 5.0)));
+        }
+        assert!((0..1_000).all(|id| mempool.probably_contains(id * 7919)));
+        assert!((0..1_000).all(|id| mempool.contains(id * 7919)));
+
+        // Ids never inserted are rarely reported, and never confirmed.
+        let probes = 100_000u64;
+        let false_positives = (0..probes).map(|i| 1_000_000_007 + i * 3).filter(|&id| mempool.probably_contains(id)).count();
+        assert!((false_positives as f64 / probes as f64) < 0.02, "{} false positives", false_positives);
+        assert!(!mempool.contains(1_000_000_007));
+    }
+
+    #[test]
+    fn test_bloom_resets_when_pool_drains() {
+        let mut mempool = Mempool::new();
+        assert!(mempool.add_transaction(dummy_tx(42, 5.0)));
+        assert!(mempool.remove_transaction().is_some());
+        assert!(!mempool.probably_contains(42));
+        assert!(mempool.add_transaction(dummy_tx(42, 5.0)));
+        assert!(mempool.contains(42));
+    }
+
+    #[test]
+    fn test_bloom_rebuilds_past_capacity_without_draining() {
+        let mut mempool = Mempool::new();
+        mempool.configure_bloom(100, 0.01);
+        // Churn far past capacity while keeping a few transactions pending.
+        for id in 0..5_000u64 {
+            assert!(mempool.add_transaction(dummy_tx(id, 5.0)));
+            if id >= 10 {
+                assert!(mempool.remove_transaction().is_some());
+            }
+        }
+        assert_eq!(mempool.size(), 10);
+        assert!((4_990..5_000).all(|id| mempool.contains(id)));
+
+        let false_positives = (0..4_990).filter(|&id| mempool.probably_contains(id)).count();
+        assert!(false_positives < 100, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn test_reinsert_restores_pulled_transactions() {
+        let mut mempool = Mempool::new();
+        for (id, fee) in [(1, 5.0), (2, 30.0), (3, 10.0), (4, 20.0)] {
+            assert!(mempool.add_transaction(dummy_tx(id, fee)));
+        }
+        let before: Vec<Transaction> = mempool.iter().cloned().collect();
+
+        let pulled = mempool.select_for_block(3);
+        assert_eq!(pulled.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![2, 4, 3]);
+        assert_eq!(mempool.size(), 1);
+
+        // Raising the fee floor does not keep the rejected block's transactions out.
+        mempool.set_min_fee(15.0);
+        mempool.reinsert(pulled.clone());
+        assert_eq!(mempool.size(), before.len());
+        let mut restored: Vec<Transaction> = mempool.iter().cloned().collect();
+        restored.sort_by_key(|tx| tx.id);
+        assert_eq!(restored, before);
+        assert_eq!(ids(mempool.transactions_from("Alice")).len(), 4);
+
+        // The same block is selected again.
+        assert_eq!(mempool.select_for_block(3), pulled);
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("reina-mempool-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = temp_path("round-trip");
+        let mut mempool = Mempool::new();
+        for id in 1..=5 {
+            assert!(mempool.add_transaction(dummy_tx(id, id as f64 * 10.0)));
+        }
+        mempool.save(&path).unwrap();
+
+        let loaded = Mempool::load(&path, DEFAULT_MIN_FEE).unwrap();
+        assert_eq!(loaded.iter().collect::<Vec<_>>(), mempool.iter().collect::<Vec<_>>());
+        assert_eq!(ids(loaded.transactions_from("Alice")), vec![1, 2, 3, 4, 5]);
+
+        // Revalidation drops entries below a higher fee floor.
+        assert_eq!(Mempool::load(&path, 25.0).unwrap().size(), 3);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_rejects_corrupt_file() {
+        let path = temp_path("corrupt");
+        let mut mempool = Mempool::new();
+        assert!(mempool.add_transaction(dummy_tx(1, 10.0)));
+        mempool.save(&path).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[6] ^= 0xFF;
+        fs::write(&path, &bytes).unwrap();
+
+        let err = Mempool::load(&path, DEFAULT_MIN_FEE).err().expect("corrupt file loaded");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        fs::write(&path, [1, 2]).unwrap();
+        assert_eq!(Mempool::load(&path, DEFAULT_MIN_FEE).err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_select_for_block_caps_each_sender() {
+        let mut mempool = Mempool::new();
+        for id in 1..=5 {
+            assert!(mempool.add_transaction(dummy_tx(id, 100.0 + id as f64)));
+        }
+        let mut carol = dummy_tx(6, 2.0);
+        carol.sender = "Carol".to_string();
+        assert!(mempool.add_transaction(carol));
+
+        let pulled = mempool.select_for_block_capped(4, Some(2));
+        assert_eq!(pulled.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![5, 4, 6]);
+        assert_eq!(ids(mempool.transactions_from("Alice")), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_fee_ordering_modes() {
+        let small = dummy_tx(1, 5.0);
+        let mut large = dummy_tx(2, 20.0);
+        large.signature = vec![7; 4096].into();
+        assert!(small.fee_per_byte() > large.fee_per_byte());
+
+        for (ordering, first) in [(FeeOrdering::Absolute, 2), (FeeOrdering::PerByte, 1)] {
+            let mut mempool = Mempool::new().with_ordering(ordering);
+            assert_eq!(mempool.ordering(), ordering);
+            assert!(mempool.add_transaction(large.clone()));
+            assert!(mempool.add_transaction(small.clone()));
+            let pulled = mempool.select_for_block(1);
+            assert_eq!(pulled.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![first], "{:?}", ordering);
+        }
+    }
+
+    #[test]
+    fn test_fifo_order_survives_removal_from_the_middle() {
+        let mut mempool = Mempool::new();
+        for (id, fee) in [(1, 5.0), (2, 30.0), (3, 10.0), (4, 20.0), (5, 2.0)] {
+            assert!(mempool.add_transaction(dummy_tx(id, fee)));
+        }
+        let peeked = mempool.peek_for_block(2, None);
+        let pulled = mempool.select_for_block(2);
+        assert_eq!(pulled, peeked);
+        assert_eq!(ids(mempool.iter().collect()), vec![1, 3, 5]);
+
+        mempool.reinsert(pulled);
+        assert_eq!(ids(mempool.iter().collect()), vec![2, 4, 1, 3, 5]);
+        assert_eq!(mempool.remove_transaction().map(|tx| tx.id), Some(2));
+        assert!(mempool.add_transaction(dummy_tx(6, 3.0)));
+        assert_eq!(ids(mempool.iter().collect()), vec![4, 1, 3, 5, 6]);
+        assert_eq!(mempool.peek().map(|tx| tx.id), Some(4));
+    }
+}
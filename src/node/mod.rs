@@ -1,2 +1,3 @@
+pub mod chain_export;
 pub mod chain_manager;
 pub mod mempool;
\ No newline at end of file
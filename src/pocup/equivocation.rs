@@ -0,0 +1,96 @@
+//! Double-sign (equivocation) detection for PoCUP.
+//!
+//! A validator that signs two different blocks at the same height is
+//! equivocating. `EquivocationTracker` remembers the first block hash each
+//! validator signed per height and produces `Evidence` on a conflict.
+
+use std::collections::HashMap;
+
+use log::warn;
+
+use super::pocup::{slash, SlashSeverity, Validator};
+
+/// Proof that a validator signed two conflicting blocks at one height.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evidence {
+    pub validator_id: String,
+    pub height: u64,
+    /// Hash of the block signed first.
+    pub first_hash: [u8; 32],
+    /// Hash of the conflicting block.
+    pub second_hash: [u8; 32],
+}
+
+/// Records `(validator_id, height) -> block_hash` for every observed signature.
+#[derive(Debug, Default)]
+pub struct EquivocationTracker {
+    signed: HashMap<(String, u64), [u8; 32]>,
+}
+
+impl EquivocationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `validator_id` signed `block_hash` at `height`.
+    /// Returns evidence if it already signed a different hash at that height;
+    /// re-reporting the same hash is not an offence.
+    pub fn report(&mut self, validator_id: &str, height: u64, block_hash: [u8; 32]) -> Option<Evidence> {
+        let first_hash = *self.signed.entry((validator_id.to_string(), height)).or_insert(block_hash);
+        if first_hash == block_hash {
+            return None;
+        }
+        Some(Evidence { validator_id: validator_id.to_string(), height, first_hash, second_hash: block_hash })
+    }
+
+    /// Forgets signatures below `height`, which can no longer conflict with new blocks.
+    pub fn prune_below(&mut self, height: u64) {
+        self.signed.retain(|(_, h), _| *h >= height);
+    }
+}
+
+/// Slashes an equivocating validator: its entire stake is burned and it is jailed.
+/// Evidence for a different validator is ignored. Returns the amount slashed.
+pub fn slash_for_equivocation(validator: &mut Validator, evidence: &Evidence) -> u64 {
+    if validator.id != evidence.validator_id {
+        return 0;
+    }
+    let slashed = slash(validator, SlashSeverity::Equivocation);
+    validator.jailed = true;
+    warn!(
+        "Validator {} equivocated at height {}; slashed {} tokens and jailed.",
+        validator.id, evidence.height, slashed
+    );
+    slashed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator() -> Validator {
+        Validator { id: "validator1".to_string(), stake_amount: 500, puzzle_passed: true, jailed: false, last_solution: None }
+    }
+
+    #[test]
+    fn test_one_block_per_height_is_not_evidence() {
+        let mut tracker = EquivocationTracker::new();
+        assert_eq!(tracker.report("validator1", 1, [1u8; 32]), None);
+        assert_eq!(tracker.report("validator1", 1, [1u8; 32]), None);
+        assert_eq!(tracker.report("validator1", 2, [2u8; 32]), None);
+        assert_eq!(tracker.report("validator2", 1, [9u8; 32]), None);
+    }
+
+    #[test]
+    fn test_conflicting_signatures_slash() {
+        let mut tracker = EquivocationTracker::new();
+        let mut v = validator();
+        assert_eq!(tracker.report(&v.id, 5, [1u8; 32]), None);
+        let evidence = tracker.report(&v.id, 5, [2u8; 32]).expect("conflict detected");
+        assert_eq!(evidence.first_hash, [1u8; 32]);
+        assert_eq!(evidence.second_hash, [2u8; 32]);
+        assert_eq!(slash_for_equivocation(&mut v, &evidence), 500);
+        assert_eq!(v.stake_amount, 0);
+        assert!(v.jailed);
+    }
+}
@@ -0,0 +1,215 @@
+//! Validator liveness from signed heartbeats.
+//!
+//! Online validators periodically broadcast a heartbeat: their id, the chain
+//! height they are at, and a timestamp, signed with their Ed25519 key.
+//! `LivenessTracker` verifies each heartbeat against the validator's
+//! registered key and remembers the latest timestamp per validator, so
+//! callers can ask whether a validator has been heard from recently.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use crate::utils::serialization::{Encode, Endianness, FieldWriter};
+
+/// Default tolerance for heartbeats stamped ahead of the receiver's clock.
+pub const DEFAULT_MAX_CLOCK_SKEW: Duration = Duration::from_secs(30);
+
+/// Why a heartbeat was ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeartbeatError {
+    /// No key is registered for the validator.
+    UnknownValidator(String),
+    /// The signature does not verify against the validator's key.
+    InvalidSignature,
+    /// The timestamp is not newer than the last heartbeat recorded.
+    Stale { timestamp: u64, latest: u64 },
+    /// The timestamp lies further in the future than the allowed clock skew.
+    FromFuture { timestamp: u64, now: u64 },
+}
+
+impl fmt::Display for HeartbeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeartbeatError::UnknownValidator(id) => write!(f, "No key registered for validator {}", id),
+            HeartbeatError::InvalidSignature => write!(f, "Heartbeat signature is invalid"),
+            HeartbeatError::Stale { timestamp, latest } => {
+                write!(f, "Heartbeat at {} is not newer than the last one at {}", timestamp, latest)
+            }
+            HeartbeatError::FromFuture { timestamp, now } => {
+                write!(f, "Heartbeat at {} is too far ahead of the local time {}", timestamp, now)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeartbeatError {}
+
+/// Returns the bytes a heartbeat signature covers: the little-endian encoding
+/// of `validator_id`, `height`, and `timestamp`.
+pub fn heartbeat_signing_bytes(validator_id: &str, height: u64, timestamp: u64) -> Vec<u8> {
+    let id = validator_id.to_string();
+    let mut buffer = vec![0u8; id.encoded_size() + height.encoded_size() + timestamp.encoded_size()];
+    FieldWriter::new(&mut buffer, Endianness::Little)
+        .field(&id)
+        .and_then(|w| w.field(&height))
+        .and_then(|w| w.field(&timestamp))
+        .expect("buffer sized from encoded_size");
+    buffer
+}
+
+/// Signs a heartbeat with `key`, returning the signature bytes.
+pub fn sign_heartbeat(key: &SigningKey, validator_id: &str, height: u64, timestamp: u64) -> Vec<u8> {
+    key.sign(&heartbeat_signing_bytes(validator_id, height, timestamp)).to_bytes().to_vec()
+}
+
+/// Last heartbeat accepted from a validator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LastHeartbeat {
+    pub height: u64,
+    /// Timestamp the validator signed, in seconds since UNIX_EPOCH.
+    pub timestamp: u64,
+}
+
+/// Tracks the most recent valid heartbeat from each registered validator.
+#[derive(Debug)]
+pub struct LivenessTracker {
+    keys: HashMap<String, VerifyingKey>,
+    last_seen: HashMap<String, LastHeartbeat>,
+    max_clock_skew: Duration,
+}
+
+impl Default for LivenessTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LivenessTracker {
+    pub fn new() -> Self {
+        Self { keys: HashMap::new(), last_seen: HashMap::new(), max_clock_skew: DEFAULT_MAX_CLOCK_SKEW }
+    }
+
+    /// Sets how far ahead of the local clock a heartbeat may be stamped.
+    pub fn with_max_clock_skew(mut self, max_clock_skew: Duration) -> Self {
+        self.max_clock_skew = max_clock_skew;
+        self
+    }
+
+    /// Registers the key heartbeats from `validator_id` must be signed with.
+    pub fn register(&mut self, validator_id: &str, key: VerifyingKey) {
+        self.keys.insert(validator_id.to_string(), key);
+    }
+
+    /// Verifies and records a heartbeat received at local time `now` (seconds
+    /// since UNIX_EPOCH). Rejected heartbeats leave the tracker unchanged.
+    pub fn record(
+        &mut self,
+        validator_id: &str,
+        height: u64,
+        timestamp: u64,
+        signature: &[u8],
+        now: u64,
+    ) -> Result<(), HeartbeatError> {
+        let key = self
+            .keys
+            .get(validator_id)
+            .ok_or_else(|| HeartbeatError::UnknownValidator(validator_id.to_string()))?;
+        let message = heartbeat_signing_bytes(validator_id, height, timestamp);
+        if !Signature::from_slice(signature).is_ok_and(|sig| key.verify(&message, &sig).is_ok()) {
+            return Err(HeartbeatError::InvalidSignature);
+        }
+        if timestamp > now.saturating_add(self.max_clock_skew.as_secs()) {
+            return Err(HeartbeatError::FromFuture { timestamp, now });
+        }
+        if let Some(last) = self.last_seen.get(validator_id) {
+            if timestamp <= last.timestamp {
+                return Err(HeartbeatError::Stale { timestamp, latest: last.timestamp });
+            }
+        }
+        self.last_seen.insert(validator_id.to_string(), LastHeartbeat { height, timestamp });
+        Ok(())
+    }
+
+    /// Returns the last heartbeat accepted from `validator_id`, if any.
+    pub fn last_heartbeat(&self, validator_id: &str) -> Option<LastHeartbeat> {
+        self.last_seen.get(validator_id).copied()
+    }
+
+    /// Returns true if `validator_id` sent a valid heartbeat no more than
+    /// `max_silence` before `now`.
+    pub fn is_live(&self, validator_id: &str, now: u64, max_silence: Duration) -> bool {
+        self.last_seen
+            .get(validator_id)
+            .is_some_and(|last| now.saturating_sub(last.timestamp) <= max_silence.as_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn tracker_with(id: &str, seed: u8) -> (LivenessTracker, SigningKey) {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let mut tracker = LivenessTracker::new();
+        tracker.register(id, key.verifying_key());
+        (tracker, key)
+    }
+
+    #[test]
+    fn test_recent_heartbeat_marks_live() {
+        let (mut tracker, key) = tracker_with("validator1", 7);
+        assert!(!tracker.is_live("validator1", NOW, Duration::from_secs(60)));
+
+        let signature = sign_heartbeat(&key, "validator1", 12, NOW - 5);
+        assert_eq!(tracker.record("validator1", 12, NOW - 5, &signature, NOW), Ok(()));
+        assert!(tracker.is_live("validator1", NOW, Duration::from_secs(60)));
+        assert_eq!(tracker.last_heartbeat("validator1"), Some(LastHeartbeat { height: 12, timestamp: NOW - 5 }));
+    }
+
+    #[test]
+    fn test_silence_past_threshold_marks_not_live() {
+        let (mut tracker, key) = tracker_with("validator1", 7);
+        let signature = sign_heartbeat(&key, "validator1", 12, NOW);
+        tracker.record("validator1", 12, NOW, &signature, NOW).unwrap();
+
+        let max_silence = Duration::from_secs(60);
+        assert!(tracker.is_live("validator1", NOW + 60, max_silence));
+        assert!(!tracker.is_live("validator1", NOW + 61, max_silence));
+    }
+
+    #[test]
+    fn test_invalid_and_stale_heartbeats_ignored() {
+        let (mut tracker, key) = tracker_with("validator1", 7);
+        let impostor = SigningKey::from_bytes(&[8; 32]);
+        let forged = sign_heartbeat(&impostor, "validator1", 12, NOW);
+        assert_eq!(tracker.record("validator1", 12, NOW, &forged, NOW), Err(HeartbeatError::InvalidSignature));
+        // A valid signature over different fields does not carry over.
+        let signature = sign_heartbeat(&key, "validator1", 12, NOW);
+        assert_eq!(tracker.record("validator1", 13, NOW, &signature, NOW), Err(HeartbeatError::InvalidSignature));
+        assert_eq!(
+            tracker.record("other", 12, NOW, &signature, NOW),
+            Err(HeartbeatError::UnknownValidator("other".into()))
+        );
+        assert!(!tracker.is_live("validator1", NOW, Duration::from_secs(60)));
+
+        tracker.record("validator1", 12, NOW, &signature, NOW).unwrap();
+        // Replaying the same heartbeat, or an older one, is stale.
+        assert_eq!(
+            tracker.record("validator1", 12, NOW, &signature, NOW + 100),
+            Err(HeartbeatError::Stale { timestamp: NOW, latest: NOW })
+        );
+        let future = NOW + 3600;
+        let signature = sign_heartbeat(&key, "validator1", 13, future);
+        assert_eq!(
+            tracker.record("validator1", 13, future, &signature, NOW),
+            Err(HeartbeatError::FromFuture { timestamp: future, now: NOW })
+        );
+        // A replay cannot refresh liveness once the validator has gone quiet.
+        assert!(!tracker.is_live("validator1", NOW + 100, Duration::from_secs(60)));
+    }
+}
@@ -1 +1,4 @@
-pub mod pocup;
\ No newline at end of file
+pub mod equivocation;
+pub mod liveness;
+#[allow(clippy::module_inception)]
+pub mod pocup;
@@ -1,12 +1,81 @@
 //! Arbiter Module for ROC.
 //!
-//! Placeholder for eventual AI-based governance or risk analysis.
-//! Phase 1 includes minimal functionality only.
+//! Placeholder for eventual AI-based governance or risk analysis, plus quorum
+//! certificate aggregation used to mark blocks final.
 
-#![allow(unused)]
+use std::collections::{HashMap, HashSet};
 
 /// Stub function to assess a governance proposal.
 /// Returns true, indicating the proposal is acceptable.
 pub fn assess_governance_proposal() -> bool {
     true
-}
\ No newline at end of file
+}
+
+/// Signatures from a stake supermajority over one block hash.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuorumCert {
+    pub block_hash: [u8; 32],
+    /// `(validator_id, signature)` pairs that make up the quorum.
+    pub signers: Vec<(String, Vec<u8>)>,
+}
+
+/// Aggregates `votes` for `block_hash` into a certificate once the signers'
+/// combined stake is strictly more than two-thirds of `total_stake`.
+///
+/// Votes from validators missing in `stakes` are ignored, as are repeat votes
+/// from the same validator. Returns `None` while quorum is not reached.
+pub fn build_quorum(
+    block_hash: [u8; 32],
+    votes: &[(String, Vec<u8>)],
+    stakes: &HashMap<String, u64>,
+    total_stake: u64,
+) -> Option<QuorumCert> {
+    let mut seen = HashSet::new();
+    let mut signed_stake: u128 = 0;
+    let mut signers = Vec::new();
+    for (validator_id, signature) in votes {
+        let Some(&stake) = stakes.get(validator_id) else { continue };
+        if !seen.insert(validator_id.as_str()) {
+            continue;
+        }
+        signed_stake += u128::from(stake);
+        signers.push((validator_id.clone(), signature.clone()));
+    }
+    // signed / total > 2/3, computed without division.
+    if signed_stake * 3 > u128::from(total_stake) * 2 {
+        Some(QuorumCert { block_hash, signers })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stakes() -> HashMap<String, u64> {
+        [("A", 40), ("B", 30), ("C", 20), ("D", 10)]
+            .into_iter()
+            .map(|(id, stake)| (id.to_string(), stake))
+            .collect()
+    }
+
+    fn vote(id: &str) -> (String, Vec<u8>) {
+        (id.to_string(), vec![1, 2, 3])
+    }
+
+    #[test]
+    fn test_quorum_reached() {
+        let cert = build_quorum([7u8; 32], &[vote("A"), vote("B"), vote("D")], &stakes(), 100)
+            .expect("80% of stake signed");
+        assert_eq!(cert.block_hash, [7u8; 32]);
+        assert_eq!(cert.signers.len(), 3);
+    }
+
+    #[test]
+    fn test_quorum_just_short() {
+        // 40 + 20 + 10 = 70 of 105: 2/3 exactly is not a supermajority.
+        let votes = [vote("A"), vote("C"), vote("D"), vote("A"), vote("Mallory")];
+        assert_eq!(build_quorum([7u8; 32], &votes, &stakes(), 105), None);
+    }
+}
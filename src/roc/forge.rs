@@ -1,12 +1,133 @@
 //! Forge Module for ROC.
 //!
-//! Placeholder for verifying HPC tasks or aggregator-based proofs.
-//! In Phase 1, this module provides no real logic.
+//! Models HPC jobs: a task carries an input and a difficulty, and a result
+//! claims an output together with a nonce. The nonce must solve the PoCUP
+//! puzzle over a challenge committing to the task and the claimed output.
 
-#![allow(unused)]
+use crate::consensus::difficulty::MAX_DIFFICULTY;
+use crate::pocup::pocup::verify_puzzle;
+use crate::utils::serialization::{
+    Decode, Encode, Endianness, FieldReader, FieldWriter, SerializationError, SerializationResult,
+};
 
-/// Stub function that always returns true.
-/// Future versions will verify actual HPC results.
-pub fn verify_hpc_result() -> bool {
-    true
-}
\ No newline at end of file
+/// An HPC job submitted for validators to work on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HpcTask {
+    pub id: u64,
+    pub input: Vec<u8>,
+    /// Required leading zero bits in the puzzle hash.
+    pub difficulty: u32,
+}
+
+/// A validator's claimed answer to an `HpcTask`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HpcResult {
+    pub task_id: u64,
+    pub output: Vec<u8>,
+    pub nonce: u64,
+}
+
+/// Returns the puzzle challenge for `result` on `task`: the task id, input,
+/// and claimed output, so a nonce cannot be reused for a different output.
+pub fn challenge(task: &HpcTask, output: &[u8]) -> Vec<u8> {
+    let mut challenge = Vec::with_capacity(8 + task.input.len() + output.len());
+    challenge.extend_from_slice(&task.id.to_le_bytes());
+    challenge.extend_from_slice(&task.input);
+    challenge.extend_from_slice(output);
+    challenge
+}
+
+/// Returns true if `result` answers `task` and its nonce meets the task's difficulty.
+pub fn verify(task: &HpcTask, result: &HpcResult) -> bool {
+    result.task_id == task.id && verify_puzzle(&challenge(task, &result.output), result.nonce, task.difficulty)
+}
+
+impl Encode for HpcTask {
+    fn encoded_size(&self) -> usize {
+        self.id.encoded_size() + self.input.encoded_size() + self.difficulty.encoded_size()
+    }
+
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        Ok(FieldWriter::new(buffer, endianness)
+            .field(&self.id)?
+            .field(&self.input)?
+            .field(&self.difficulty)?
+            .finish())
+    }
+}
+
+impl Decode for HpcTask {
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let mut reader = FieldReader::new(buffer, endianness);
+        let task = HpcTask { id: reader.field()?, input: reader.field()?, difficulty: reader.field()? };
+        if task.difficulty > MAX_DIFFICULTY {
+            return Err(SerializationError::InvalidData("Task difficulty exceeds the maximum".into()));
+        }
+        Ok((task, reader.finish()))
+    }
+}
+
+impl Encode for HpcResult {
+    fn encoded_size(&self) -> usize {
+        self.task_id.encoded_size() + self.output.encoded_size() + self.nonce.encoded_size()
+    }
+
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        Ok(FieldWriter::new(buffer, endianness)
+            .field(&self.task_id)?
+            .field(&self.output)?
+            .field(&self.nonce)?
+            .finish())
+    }
+}
+
+impl Decode for HpcResult {
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let mut reader = FieldReader::new(buffer, endianness);
+        let result = HpcResult { task_id: reader.field()?, output: reader.field()?, nonce: reader.field()? };
+        Ok((result, reader.finish()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pocup::pocup::solve_puzzle;
+    use crate::utils::serialization::Serializer;
+
+    fn solved(task: &HpcTask) -> HpcResult {
+        let output = vec![42u8; 4];
+        let nonce = solve_puzzle(&challenge(task, &output), task.difficulty).unwrap();
+        HpcResult { task_id: task.id, output, nonce }
+    }
+
+    #[test]
+    fn test_valid_result_accepted() {
+        let task = HpcTask { id: 7, input: b"matrix".to_vec(), difficulty: 6 };
+        let result = solved(&task);
+        assert!(verify(&task, &result));
+        let tampered = HpcResult { output: vec![0u8; 4], ..result };
+        assert!(!verify(&task, &tampered));
+    }
+
+    #[test]
+    fn test_result_for_wrong_task_rejected() {
+        let task = HpcTask { id: 7, input: b"matrix".to_vec(), difficulty: 0 };
+        let result = HpcResult { task_id: 8, ..solved(&task) };
+        assert!(!verify(&task, &result));
+    }
+
+    #[test]
+    fn test_task_and_result_round_trip() {
+        let task = HpcTask { id: 1, input: vec![1, 2, 3], difficulty: 12 };
+        let result = HpcResult { task_id: 1, output: vec![4, 5], nonce: 99 };
+        let ser = Serializer::serialize(&task, Endianness::Little).unwrap();
+        assert_eq!(Serializer::deserialize::<HpcTask>(&ser, Endianness::Little).unwrap(), task);
+        let ser = Serializer::serialize(&result, Endianness::Little).unwrap();
+        assert_eq!(Serializer::deserialize::<HpcResult>(&ser, Endianness::Little).unwrap(), result);
+
+        let impossible = HpcTask { difficulty: MAX_DIFFICULTY + 1, ..task };
+        let ser = Serializer::serialize(&impossible, Endianness::Little).unwrap();
+        assert!(Serializer::deserialize::<HpcTask>(&ser, Endianness::Little).is_err());
+    }
+}
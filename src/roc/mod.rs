@@ -3,8 +3,8 @@
 //! This module lays the foundation for ROC, which will integrate on-chain AI logic
 //! with future HPC and governance tasks. In Phase 1, we include three submodules:
 //! - `sentinel`: minimal spam detection for transactions,
-//! - `forge`: placeholder for HPC task verification,
-//! - `arbiter`: placeholder for AI-based governance analysis.
+//! - `forge`: HPC task and result types with puzzle-based verification,
+//! - `arbiter`: governance placeholder and quorum certificate aggregation.
 //!
 //! Future phases will expand these capabilities.
 
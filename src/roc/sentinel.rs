@@ -1,7 +1,11 @@
 //! Sentinel Module for ROC.
 //!
 //! Provides minimal spam detection for transactions in Phase 1.
-//! Rules: reject if fee < 1.0 or if sender equals recipient.
+//! Rules: reject if the fee is not finite or below the policy's
+//! `low_fee_threshold` (1.0 by default), or if sender equals recipient; the
+//! self-transfer rule can be waived with `SpamPolicy::allow_self_transfer`.
+//! `spam_score` grades transactions 0–100 for review rather than rejection;
+//! `check_spam` remains the hard gate.
 //! Future versions will implement advanced AI spam detection.
 
 use crate::utils::serialization::Transaction;
@@ -9,31 +13,90 @@ use crate::utils::serialization::Transaction;
 /// Returns true if the transaction passes spam checks; false otherwise.
 #[inline(always)]
 pub fn check_spam(tx: &Transaction) -> bool {
-    if tx.fee < 1.0 {
+    check_spam_with(tx, &SpamPolicy::default())
+}
+
+/// Like `check_spam`, but under `policy`: fees below its `low_fee_threshold`
+/// fail, and the self-transfer rule is skipped when it allows self-transfers.
+/// Non-finite fees always fail.
+#[inline(always)]
+pub fn check_spam_with(tx: &Transaction, policy: &SpamPolicy) -> bool {
+    if !tx.fee.is_finite() || tx.fee < policy.low_fee_threshold {
         return false;
     }
-    if tx.sender == tx.recipient {
+    if tx.sender == tx.recipient && !policy.allow_self_transfer {
         return false;
     }
     true
 }
 
+/// Partial penalties used by `spam_score`, and the rule switches used by
+/// `check_spam_with`.
+#[derive(Debug, Clone)]
+pub struct SpamPolicy {
+    /// Fees below this value are penalized by `spam_score` and rejected by
+    /// `check_spam_with`.
+    pub low_fee_threshold: f64,
+    /// Added when the fee is below `low_fee_threshold`.
+    pub low_fee_penalty: u8,
+    /// Added when sender and recipient are identical.
+    pub self_transfer_penalty: u8,
+    /// Added when sender and recipient differ only by case or surrounding whitespace.
+    pub near_self_transfer_penalty: u8,
+    /// Added when the signature is empty.
+    pub empty_signature_penalty: u8,
+    /// Lets `check_spam_with` accept transfers whose sender equals the recipient.
+    pub allow_self_transfer: bool,
+}
+
+impl Default for SpamPolicy {
+    fn default() -> Self {
+        Self {
+            low_fee_threshold: 1.0,
+            low_fee_penalty: 40,
+            self_transfer_penalty: 50,
+            near_self_transfer_penalty: 30,
+            empty_signature_penalty: 30,
+            allow_self_transfer: false,
+        }
+    }
+}
+
+/// Returns a spam score from 0 (clean) to 100 by summing the policy's
+/// penalties for each pattern the transaction matches, clamped at 100. A NaN
+/// or infinite fee cannot be honest and scores 100 outright.
+pub fn spam_score(tx: &Transaction, policy: &SpamPolicy) -> u8 {
+    if !tx.fee.is_finite() {
+        return 100;
+    }
+    let mut score = 0u32;
+    if tx.fee < policy.low_fee_threshold {
+        score += u32::from(policy.low_fee_penalty);
+    }
+    if tx.sender == tx.recipient {
+        score += u32::from(policy.self_transfer_penalty);
+    } else if tx.sender.trim().eq_ignore_ascii_case(tx.recipient.trim()) {
+        score += u32::from(policy.near_self_transfer_penalty);
+    }
+    if tx.signature.is_empty() {
+        score += u32::from(policy.empty_signature_penalty);
+    }
+    score.min(100) as u8
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::serialization::Transaction;
+    use crate::utils::serialization::{SignatureBytes, Transaction, TransactionBuilder};
+
+    fn transfer(sender: &str, recipient: &str) -> Transaction {
+        let builder = TransactionBuilder::new().id(1).amount(1000).fee(5.0).signature(vec![1, 2, 3, 4]);
+        builder.sender(sender).recipient(recipient).build().unwrap()
+    }
 
     #[test]
     fn test_check_spam() {
-        let tx_valid = Transaction {
-            id: 1,
-            amount: 1000,
-            fee: 5.0,
-            version: 1,
-            sender: "Alice".to_string(),
-            recipient: "Bob".to_string(),
-            signature: vec![1, 2, 3, 4],
-        };
+        let tx_valid = transfer("Alice", "Bob");
         let tx_low_fee = Transaction { fee: 0.5, ..tx_valid.clone() };
         let tx_same = Transaction { sender: "Alice".to_string(), recipient: "Alice".to_string(), ..tx_valid.clone() };
 
@@ -41,4 +104,50 @@ mod tests {
         assert!(!check_spam(&tx_low_fee));
         assert!(!check_spam(&tx_same));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_self_transfer_allowance() {
+        let self_transfer = transfer("hot-wallet", "hot-wallet");
+        let cheap_self_transfer = Transaction { fee: 0.5, ..self_transfer.clone() };
+
+        let strict = SpamPolicy::default();
+        assert!(!check_spam_with(&self_transfer, &strict));
+        assert!(!check_spam_with(&cheap_self_transfer, &strict));
+
+        let lenient = SpamPolicy { allow_self_transfer: true, ..SpamPolicy::default() };
+        assert!(check_spam_with(&self_transfer, &lenient));
+        assert!(!check_spam_with(&cheap_self_transfer, &lenient));
+    }
+
+    #[test]
+    fn test_fee_rule_follows_policy_and_rejects_non_finite_fees() {
+        let tx = transfer("Alice", "Bob");
+        let cheap = SpamPolicy { low_fee_threshold: 0.1, ..SpamPolicy::default() };
+        let pricey = SpamPolicy { low_fee_threshold: 10.0, ..SpamPolicy::default() };
+        assert!(check_spam_with(&Transaction { fee: 0.5, ..tx.clone() }, &cheap));
+        assert!(!check_spam_with(&tx, &pricey));
+        for fee in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(!check_spam_with(&Transaction { fee, ..tx.clone() }, &cheap));
+        }
+    }
+
+    #[test]
+    fn test_spam_score() {
+        let policy = SpamPolicy::default();
+        let clean = transfer("Alice", "Bob");
+        assert_eq!(spam_score(&clean, &policy), 0);
+
+        let near_self = Transaction { recipient: " alice".to_string(), ..clean.clone() };
+        assert_eq!(spam_score(&near_self, &policy), 30);
+
+        let low_fee_self = Transaction { fee: 0.1, recipient: "Alice".to_string(), ..clean.clone() };
+        assert_eq!(spam_score(&low_fee_self, &policy), 90);
+
+        let worst = Transaction { signature: SignatureBytes::new(), ..low_fee_self };
+        assert_eq!(spam_score(&worst, &policy), 100);
+
+        for fee in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(spam_score(&Transaction { fee, ..clean.clone() }, &policy), 100);
+        }
+    }
+}
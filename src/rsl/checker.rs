@@ -0,0 +1,503 @@
+//! Semantic checks for parsed RSL contracts.
+//!
+//! Runs after parsing and before execution, rejecting contracts that are
+//! syntactically valid but break language rules.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::{BinOp, Contract, Expr, Function, Statement};
+
+/// Type given to an untyped `let` whose value is an integer literal.
+const DEFAULT_INTEGER_TYPE: &str = "u64";
+
+/// A rule violation found by `check_contract`.
+#[derive(Debug, PartialEq)]
+pub enum SemanticError {
+    /// A function assigns to a constant.
+    AssignToConstant { function: String, constant: String },
+    /// A function emits an event that is not declared.
+    UnknownEvent { function: String, event: String },
+    /// An emit passes a different number of arguments than the event declares.
+    EventArgumentCount { event: String, expected: usize, found: usize },
+    /// An integer literal does not fit the declared type of what it initializes.
+    LiteralOutOfRange { target: String, ty: String, value: i128 },
+    /// Two functions share a name; RSL has no overloading.
+    DuplicateFunction(String),
+    /// A field and a function share a name.
+    FieldFunctionCollision(String),
+    /// An identifier does not name a parameter, local, field, or constant.
+    UnresolvedIdentifier { function: String, name: String },
+    /// A `let` whose value has no single type.
+    UninferableType { function: String, name: String },
+    /// A `let` or assignment whose value has a different type than its target.
+    TypeMismatch { target: String, expected: String, found: String },
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemanticError::AssignToConstant { function, constant } => {
+                write!(f, "function '{}' assigns to constant '{}'", function, constant)
+            }
+            SemanticError::UnknownEvent { function, event } => {
+                write!(f, "function '{}' emits undeclared event '{}'", function, event)
+            }
+            SemanticError::EventArgumentCount { event, expected, found } => {
+                write!(f, "event '{}' takes {} arguments, emit passes {}", event, expected, found)
+            }
+            SemanticError::LiteralOutOfRange { target, ty, value } => {
+                write!(f, "literal {} assigned to '{}' does not fit {}", value, target, ty)
+            }
+            SemanticError::DuplicateFunction(name) => {
+                write!(f, "function '{}' is declared more than once", name)
+            }
+            SemanticError::FieldFunctionCollision(name) => {
+                write!(f, "'{}' is declared as both a field and a function", name)
+            }
+            SemanticError::UnresolvedIdentifier { function, name } => {
+                write!(f, "function '{}' refers to unknown identifier '{}'", function, name)
+            }
+            SemanticError::UninferableType { function, name } => {
+                write!(f, "cannot infer the type of '{}' in function '{}'", name, function)
+            }
+            SemanticError::TypeMismatch { target, expected, found } => {
+                write!(f, "'{}' is declared {} but assigned a {} value", target, expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+/// Checks `contract` against RSL's semantic rules, returning the first violation.
+pub fn check_contract(contract: &Contract) -> Result<(), SemanticError> {
+    for constant in &contract.constants {
+        check_literal(&constant.name, &constant.ty, &constant.value)?;
+    }
+    let mut function_names = HashSet::new();
+    for function in &contract.functions {
+        if !function_names.insert(function.name.as_str()) {
+            return Err(SemanticError::DuplicateFunction(function.name.clone()));
+        }
+    }
+    if let Some(field) = contract.fields.iter().find(|f| function_names.contains(f.name.as_str())) {
+        return Err(SemanticError::FieldFunctionCollision(field.name.clone()));
+    }
+    let scope = ContractScope {
+        constants: contract.constants.iter().map(|c| (c.name.as_str(), c.ty.as_str())).collect(),
+        event_arity: contract.events.iter().map(|e| (e.name.as_str(), e.params.len())).collect(),
+        field_types: contract.fields.iter().map(|f| (f.name.as_str(), f.field_type.as_str())).collect(),
+    };
+    for function in &contract.functions {
+        check_function(function, &scope)?;
+    }
+    Ok(())
+}
+
+/// Contract-level declarations visible to every function.
+struct ContractScope<'a> {
+    /// Declared constants mapped to their type.
+    constants: HashMap<&'a str, &'a str>,
+    /// Declared events mapped to their parameter count.
+    event_arity: HashMap<&'a str, usize>,
+    /// Declared fields mapped to their type.
+    field_types: HashMap<&'a str, &'a str>,
+}
+
+fn check_function(function: &Function, scope: &ContractScope) -> Result<(), SemanticError> {
+    // Parameters and locals shadow constants and fields of the same name.
+    let mut locals: HashMap<&str, &str> =
+        function.params.iter().map(|p| (p.name.as_str(), p.param_type.as_str())).collect();
+    for statement in &function.statements {
+        match statement {
+            Statement::Assign { target, value } => {
+                let ty = match locals.get(target.as_str()) {
+                    Some(ty) => *ty,
+                    None if scope.constants.contains_key(target.as_str()) => {
+                        return Err(SemanticError::AssignToConstant {
+                            function: function.name.clone(),
+                            constant: target.clone(),
+                        });
+                    }
+                    None => *scope.field_types.get(target.as_str()).ok_or_else(|| {
+                        SemanticError::UnresolvedIdentifier { function: function.name.clone(), name: target.clone() }
+                    })?,
+                };
+                check_value_type(function, target, ty, value, &locals, scope)?;
+                check_literal(target, ty, value)?;
+            }
+            Statement::Let { name, ty, value } => {
+                let ty = match ty {
+                    Some(ty) => {
+                        check_value_type(function, name, ty, value, &locals, scope)?;
+                        ty.as_str()
+                    }
+                    None => {
+                        check_resolves(function, value, &locals, scope)?;
+                        match infer_type(value, &locals, scope) {
+                            Some(Inferred::Named(found)) => found,
+                            Some(Inferred::IntLiteral) => DEFAULT_INTEGER_TYPE,
+                            None => {
+                                return Err(SemanticError::UninferableType {
+                                    function: function.name.clone(),
+                                    name: name.clone(),
+                                })
+                            }
+                        }
+                    }
+                };
+                check_literal(name, ty, value)?;
+                locals.insert(name, ty);
+            }
+            Statement::Emit { name, args } => {
+                let expected = *scope.event_arity.get(name.as_str()).ok_or_else(|| SemanticError::UnknownEvent {
+                    function: function.name.clone(),
+                    event: name.clone(),
+                })?;
+                if args.len() != expected {
+                    return Err(SemanticError::EventArgumentCount {
+                        event: name.clone(),
+                        expected,
+                        found: args.len(),
+                    });
+                }
+            }
+            // The target contract is not known locally, so only the arguments are checked.
+            Statement::Call { args, .. } => {
+                for arg in args {
+                    check_resolves(function, arg, &locals, scope)?;
+                }
+            }
+            Statement::Require(expr) | Statement::Return(Some(expr)) | Statement::Expr(expr) => {
+                check_resolves(function, expr, &locals, scope)?;
+            }
+            Statement::Return(None) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a `value` bound to `target`, declared as `ty`, whose type differs
+/// from `ty` or cannot be inferred. An integer literal fits any integer type.
+fn check_value_type(
+    function: &Function,
+    target: &str,
+    ty: &str,
+    value: &Expr,
+    locals: &HashMap<&str, &str>,
+    scope: &ContractScope,
+) -> Result<(), SemanticError> {
+    check_resolves(function, value, locals, scope)?;
+    let found = match infer_type(value, locals, scope) {
+        Some(Inferred::Named(found)) => found,
+        Some(Inferred::IntLiteral) if integer_range(ty).is_some() => return Ok(()),
+        Some(Inferred::IntLiteral) => "integer",
+        None => {
+            return Err(SemanticError::UninferableType { function: function.name.clone(), name: target.to_string() })
+        }
+    };
+    if found != ty {
+        return Err(SemanticError::TypeMismatch {
+            target: target.to_string(),
+            expected: ty.to_string(),
+            found: found.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Rejects an identifier in `expr` that is not a parameter, local, field, or constant.
+fn check_resolves(
+    function: &Function,
+    expr: &Expr,
+    locals: &HashMap<&str, &str>,
+    scope: &ContractScope,
+) -> Result<(), SemanticError> {
+    match expr {
+        Expr::Ident(name)
+            if !locals.contains_key(name.as_str())
+                && !scope.field_types.contains_key(name.as_str())
+                && !scope.constants.contains_key(name.as_str()) =>
+        {
+            Err(SemanticError::UnresolvedIdentifier { function: function.name.clone(), name: name.clone() })
+        }
+        Expr::Binary { left, right, .. } => {
+            check_resolves(function, left, locals, scope)?;
+            check_resolves(function, right, locals, scope)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Type of an expression as far as `infer_type` can tell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Inferred<'a> {
+    /// Integer literals, and arithmetic on them alone, fit whichever integer
+    /// type the context demands.
+    IntLiteral,
+    Named(&'a str),
+}
+
+/// Infers the type of `expr` from literals and the declared types of the
+/// identifiers it uses. Returns `None` if an identifier is unknown or the
+/// operands of an operator disagree.
+fn infer_type<'a>(expr: &Expr, locals: &HashMap<&'a str, &'a str>, scope: &ContractScope<'a>) -> Option<Inferred<'a>> {
+    match expr {
+        Expr::Int(_) => Some(Inferred::IntLiteral),
+        Expr::Bool(_) => Some(Inferred::Named("bool")),
+        Expr::Str(_) => Some(Inferred::Named("String")),
+        Expr::Ident(name) => {
+            let name = name.as_str();
+            let ty = locals.get(name).or_else(|| scope.field_types.get(name)).or_else(|| scope.constants.get(name));
+            ty.map(|ty| Inferred::Named(ty))
+        }
+        Expr::Binary { op, left, right } => {
+            let operands = unify(infer_type(left, locals, scope)?, infer_type(right, locals, scope)?)?;
+            match op {
+                BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => match operands {
+                    Inferred::Named(ty) if integer_range(ty).is_none() => None,
+                    _ => Some(operands),
+                },
+                BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge => Some(Inferred::Named("bool")),
+            }
+        }
+    }
+}
+
+/// Returns the common type of two operands: an integer literal adopts the
+/// other side's integer type.
+fn unify<'a>(left: Inferred<'a>, right: Inferred<'a>) -> Option<Inferred<'a>> {
+    match (left, right) {
+        _ if left == right => Some(left),
+        (Inferred::IntLiteral, Inferred::Named(ty)) | (Inferred::Named(ty), Inferred::IntLiteral)
+            if integer_range(ty).is_some() =>
+        {
+            Some(Inferred::Named(ty))
+        }
+        _ => None,
+    }
+}
+
+/// Rejects an integer literal `value` that falls outside the range of `ty`.
+/// Other expressions, and types without a known integer range, are not checked.
+fn check_literal(target: &str, ty: &str, value: &Expr) -> Result<(), SemanticError> {
+    match (value, integer_range(ty)) {
+        (Expr::Int(literal), Some(range)) if !range.contains(literal) => Err(SemanticError::LiteralOutOfRange {
+            target: target.to_string(),
+            ty: ty.to_string(),
+            value: *literal,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the values representable by the integer type `ty`.
+fn integer_range(ty: &str) -> Option<RangeInclusive<i128>> {
+    match ty {
+        "u8" => Some(0..=u8::MAX as i128),
+        "u16" => Some(0..=u16::MAX as i128),
+        "u32" => Some(0..=u32::MAX as i128),
+        "u64" => Some(0..=u64::MAX as i128),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsl::parse_rsl;
+
+    #[test]
+    fn test_assignment_to_constant_rejected() {
+        let contract = parse_rsl(r#"
+            contract Token {
+                const MAX_SUPPLY: u64 = 1000000;
+                fn inflate() {
+                    MAX_SUPPLY = 2000000;
+                }
+            }
+        "#).unwrap();
+        assert_eq!(
+            check_contract(&contract),
+            Err(SemanticError::AssignToConstant { function: "inflate".into(), constant: "MAX_SUPPLY".into() })
+        );
+    }
+
+    #[test]
+    fn test_reading_constant_allowed() {
+        let contract = parse_rsl(r#"
+            contract Token {
+                const MAX_SUPPLY: u64 = 1000000;
+                let supply: u64;
+                fn mint(amount: u64) {
+                    require(supply + amount <= MAX_SUPPLY);
+                    supply = supply + amount;
+                }
+            }
+        "#).unwrap();
+        assert_eq!(check_contract(&contract), Ok(()));
+    }
+
+    const TRANSFER_EVENT: &str = "event Transfer(from: address, to: address, amount: u64);";
+
+    #[test]
+    fn test_emit_matching_event_allowed() {
+        let source = format!(
+            "contract Token {{\n{}\nfn send(to: address, amount: u64) {{\nemit Transfer(to, to, amount);\n}}\n}}",
+            TRANSFER_EVENT
+        );
+        let contract = parse_rsl(&source).unwrap();
+        assert_eq!(check_contract(&contract), Ok(()));
+    }
+
+    #[test]
+    fn test_emit_argument_count_mismatch_rejected() {
+        let source = format!(
+            "contract Token {{\n{}\nfn send(to: address) {{\nemit Transfer(to, to);\n}}\n}}",
+            TRANSFER_EVENT
+        );
+        let contract = parse_rsl(&source).unwrap();
+        assert_eq!(
+            check_contract(&contract),
+            Err(SemanticError::EventArgumentCount { event: "Transfer".into(), expected: 3, found: 2 })
+        );
+    }
+
+    #[test]
+    fn test_emit_unknown_event_rejected() {
+        let contract = parse_rsl("contract Token {\nfn f() {\nemit Missing();\n}\n}").unwrap();
+        assert_eq!(
+            check_contract(&contract),
+            Err(SemanticError::UnknownEvent { function: "f".into(), event: "Missing".into() })
+        );
+    }
+
+    fn check_source(source: &str) -> Result<(), SemanticError> {
+        check_contract(&parse_rsl(source).unwrap())
+    }
+
+    #[test]
+    fn test_literal_over_u32_rejected() {
+        assert_eq!(
+            check_source("contract C {\nlet small: u32;\nfn f() {\nsmall = 4294967296;\n}\n}"),
+            Err(SemanticError::LiteralOutOfRange { target: "small".into(), ty: "u32".into(), value: 4294967296 })
+        );
+    }
+
+    #[test]
+    fn test_negative_literal_for_u64_rejected() {
+        assert_eq!(
+            check_source("contract C {\nlet total: u64;\nfn f() {\ntotal = -1;\n}\n}"),
+            Err(SemanticError::LiteralOutOfRange { target: "total".into(), ty: "u64".into(), value: -1 })
+        );
+    }
+
+    #[test]
+    fn test_duplicate_function_rejected() {
+        assert_eq!(
+            check_source("contract C {\nfn f() {\n}\nfn g() {\n}\nfn f(x: u64) {\n}\n}"),
+            Err(SemanticError::DuplicateFunction("f".into()))
+        );
+    }
+
+    #[test]
+    fn test_field_function_collision_rejected() {
+        assert_eq!(
+            check_source("contract C {\nlet total: u64;\nfn total(): u64 {\nreturn total;\n}\n}"),
+            Err(SemanticError::FieldFunctionCollision("total".into()))
+        );
+    }
+
+    #[test]
+    fn test_external_call_arguments_must_resolve() {
+        let source = "contract C {\nconst FEE: u64 = 1;\nlet total: u64;\nfn f(a: address) {\ncall Other.transfer(a, total + FEE, 100);\n}\n}";
+        assert_eq!(check_source(source), Ok(()));
+        assert_eq!(
+            check_source("contract C {\nfn f() {\nlet x: u64 = 1;\ncall Other.transfer(x + missing);\n}\n}"),
+            Err(SemanticError::UnresolvedIdentifier { function: "f".into(), name: "missing".into() })
+        );
+    }
+
+    #[test]
+    fn test_untyped_let_infers_u64() {
+        let source = "contract C {\nconst FEE: u64 = 1;\nfn f(amount: u64) {\nlet total = amount + FEE;\nlet doubled = total * 2;\nlet count = 2 + 3;\n}\n}";
+        assert_eq!(check_source(source), Ok(()));
+        // The literal-only binding is u64, so a negative literal does not fit it.
+        assert_eq!(
+            check_source("contract C {\nfn f() {\nlet count = 2 + 3;\ncount = -1;\n}\n}"),
+            Err(SemanticError::LiteralOutOfRange { target: "count".into(), ty: "u64".into(), value: -1 })
+        );
+        // An inferred local can initialize an annotated one of the same type.
+        assert_eq!(check_source("contract C {\nfn f(a: u64) {\nlet t = a;\nlet u: u64 = t + 1;\n}\n}"), Ok(()));
+    }
+
+    #[test]
+    fn test_untyped_let_without_single_type_rejected() {
+        assert_eq!(
+            check_source("contract C {\nlet flag: bool;\nfn f(amount: u64) {\nlet x = flag + amount;\n}\n}"),
+            Err(SemanticError::UninferableType { function: "f".into(), name: "x".into() })
+        );
+        assert_eq!(
+            check_source("contract C {\nfn f() {\nlet x = missing + 1;\n}\n}"),
+            Err(SemanticError::UnresolvedIdentifier { function: "f".into(), name: "missing".into() })
+        );
+    }
+
+    #[test]
+    fn test_annotated_let_must_match_value_type() {
+        assert_eq!(check_source("contract C {\nfn f(a: u32) {\nlet x: u32 = a + 1;\n}\n}"), Ok(()));
+        assert_eq!(
+            check_source("contract C {\nlet total: u64;\nfn f() {\nlet x: u32 = total;\n}\n}"),
+            Err(SemanticError::TypeMismatch { target: "x".into(), expected: "u32".into(), found: "u64".into() })
+        );
+        assert_eq!(
+            check_source("contract C {\nfn f() {\nlet b: bool = 1;\n}\n}"),
+            Err(SemanticError::TypeMismatch { target: "b".into(), expected: "bool".into(), found: "integer".into() })
+        );
+        assert_eq!(
+            check_source("contract C {\nfn f() {\nlet x: u64 = missing;\n}\n}"),
+            Err(SemanticError::UnresolvedIdentifier { function: "f".into(), name: "missing".into() })
+        );
+        assert_eq!(
+            check_source("contract C {\nlet flag: bool;\nfn f(a: u64) {\nlet x: u64 = flag + a;\n}\n}"),
+            Err(SemanticError::UninferableType { function: "f".into(), name: "x".into() })
+        );
+    }
+
+    #[test]
+    fn test_assignment_target_and_value_checked() {
+        assert_eq!(
+            check_source("contract C {\nfn f() {\nmissing = 1;\n}\n}"),
+            Err(SemanticError::UnresolvedIdentifier { function: "f".into(), name: "missing".into() })
+        );
+        assert_eq!(
+            check_source("contract C {\nlet flag: bool;\nfn f() {\nflag = 5;\n}\n}"),
+            Err(SemanticError::TypeMismatch { target: "flag".into(), expected: "bool".into(), found: "integer".into() })
+        );
+        assert_eq!(
+            check_source("contract C {\nlet total: u64;\nfn f() {\ntotal = total + missing;\n}\n}"),
+            Err(SemanticError::UnresolvedIdentifier { function: "f".into(), name: "missing".into() })
+        );
+        assert_eq!(check_source("contract C {\nlet flag: bool;\nfn f() {\nflag = true;\n}\n}"), Ok(()));
+    }
+
+    #[test]
+    fn test_require_return_and_expression_identifiers_must_resolve() {
+        assert_eq!(
+            check_source("contract C {\nfn f() {\nrequire(missing == 1);\n}\n}"),
+            Err(SemanticError::UnresolvedIdentifier { function: "f".into(), name: "missing".into() })
+        );
+        assert_eq!(
+            check_source("contract C {\nfn f(): u64 {\nreturn missing;\n}\n}"),
+            Err(SemanticError::UnresolvedIdentifier { function: "f".into(), name: "missing".into() })
+        );
+        assert_eq!(check_source("contract C {\nfn f(a: u64): u64 {\nrequire(a > 1);\nreturn a;\n}\n}"), Ok(()));
+    }
+
+    #[test]
+    fn test_in_range_literal_allowed() {
+        assert_eq!(check_source("contract C {\nlet small: u32;\nfn f() {\nsmall = 4294967295;\n}\n}"), Ok(()));
+    }
+}
@@ -0,0 +1,415 @@
+//! Tree-walking interpreter for RSL contracts.
+//!
+//! Executes parsed function bodies against an in-memory contract state. A call
+//! runs on a scratch copy of the state and commits only if it completes, so a
+//! failed `require` or any other error leaves the contract untouched.
+//! Integer `+`, `-`, and `*` are checked by default; see `OverflowMode`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{BinOp, Contract, Expr, Function, Statement};
+
+/// A runtime value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    U64(u64),
+    Bool(bool),
+    Str(String),
+}
+
+impl Value {
+    /// Returns the zero value used to initialize a field of type `ty`.
+    fn default_for(ty: &str) -> Option<Value> {
+        match ty {
+            "u64" | "u32" => Some(Value::U64(0)),
+            "bool" => Some(Value::Bool(false)),
+            "String" | "address" => Some(Value::Str(String::new())),
+            _ => None,
+        }
+    }
+}
+
+/// Errors raised while executing a contract function.
+#[derive(Debug, PartialEq)]
+pub enum InterpError {
+    /// A `require(cond)` evaluated to false.
+    RequireFailed,
+    /// No function with this name exists on the contract.
+    UnknownFunction(String),
+    /// An identifier does not name a field, parameter, or local.
+    UnknownVariable(String),
+    /// A field has a type the interpreter cannot represent.
+    UnsupportedType(String),
+    /// The call passed the wrong number of arguments.
+    ArgumentCount { expected: usize, found: usize },
+    /// An operator or condition was applied to values of the wrong type.
+    TypeMismatch(String),
+    /// Integer division by zero.
+    DivisionByZero,
+    /// `+`, `-`, or `*` overflowed or underflowed u64 in checked mode.
+    ArithmeticOverflow { op: BinOp, left: u64, right: u64 },
+    /// A `call` to another contract; cross-contract execution is not supported yet.
+    ExternalCall { contract: String, function: String },
+}
+
+impl fmt::Display for InterpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpError::RequireFailed => write!(f, "require condition failed"),
+            InterpError::UnknownFunction(name) => write!(f, "unknown function '{}'", name),
+            InterpError::UnknownVariable(name) => write!(f, "unknown variable '{}'", name),
+            InterpError::UnsupportedType(ty) => write!(f, "unsupported type '{}'", ty),
+            InterpError::ArgumentCount { expected, found } => {
+                write!(f, "expected {} arguments, found {}", expected, found)
+            }
+            InterpError::TypeMismatch(msg) => write!(f, "type mismatch: {}", msg),
+            InterpError::DivisionByZero => write!(f, "division by zero"),
+            InterpError::ArithmeticOverflow { op, left, right } => {
+                write!(f, "arithmetic overflow: {} {:?} {}", left, op, right)
+            }
+            InterpError::ExternalCall { contract, function } => {
+                write!(f, "cannot call {}.{}: external calls are not supported", contract, function)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InterpError {}
+
+/// An event emitted by a successful call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmittedEvent {
+    pub name: String,
+    pub args: Vec<Value>,
+}
+
+/// How integer `+`, `-`, and `*` behave when the result does not fit u64.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowMode {
+    /// Fail the call with `InterpError::ArithmeticOverflow`.
+    #[default]
+    Checked,
+    /// Wrap around modulo 2^64, for contracts that rely on it.
+    Wrapping,
+}
+
+/// A deployed contract instance: its definition plus current field values.
+pub struct Interpreter<'a> {
+    contract: &'a Contract,
+    constants: HashMap<String, Value>,
+    state: HashMap<String, Value>,
+    events: Vec<EmittedEvent>,
+    overflow: OverflowMode,
+}
+
+/// Control flow result of executing a statement.
+enum Flow {
+    Continue,
+    Return(Option<Value>),
+}
+
+impl<'a> Interpreter<'a> {
+    /// Creates an instance of `contract` with every field set to its zero value.
+    /// Constants are evaluated once, in declaration order. Arithmetic is checked.
+    pub fn new(contract: &'a Contract) -> Result<Self, InterpError> {
+        Self::with_overflow_mode(contract, OverflowMode::default())
+    }
+
+    /// Like `new`, but with `overflow` governing arithmetic in constants and calls.
+    pub fn with_overflow_mode(contract: &'a Contract, overflow: OverflowMode) -> Result<Self, InterpError> {
+        let mut constants = HashMap::new();
+        for constant in &contract.constants {
+            let frame = Frame {
+                constants: &constants,
+                state: HashMap::new(),
+                locals: HashMap::new(),
+                events: Vec::new(),
+                overflow,
+            };
+            let value = frame.eval(&constant.value)?;
+            constants.insert(constant.name.clone(), value);
+        }
+        let mut state = HashMap::new();
+        for field in &contract.fields {
+            let value = Value::default_for(&field.field_type)
+                .ok_or_else(|| InterpError::UnsupportedType(field.field_type.clone()))?;
+            state.insert(field.name.clone(), value);
+        }
+        Ok(Self { contract, constants, state, events: Vec::new(), overflow })
+    }
+
+    /// Returns the current value of a contract field.
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.state.get(field)
+    }
+
+    /// Returns the events emitted by all successful calls so far, in order.
+    pub fn events(&self) -> &[EmittedEvent] {
+        &self.events
+    }
+
+    /// Calls `function` with `args`, returning the function's return value.
+    ///
+    /// State changes and emitted events are committed only if the call succeeds.
+    pub fn call(&mut self, function: &str, args: Vec<Value>) -> Result<Option<Value>, InterpError> {
+        let func = self.find_function(function)?;
+        if func.params.len() != args.len() {
+            return Err(InterpError::ArgumentCount { expected: func.params.len(), found: args.len() });
+        }
+        let mut frame = Frame {
+            constants: &self.constants,
+            state: self.state.clone(),
+            locals: func.params.iter().map(|p| p.name.clone()).zip(args).collect(),
+            events: Vec::new(),
+            overflow: self.overflow,
+        };
+        let mut result = None;
+        for statement in &func.statements {
+            if let Flow::Return(value) = frame.execute(statement)? {
+                result = value;
+                break;
+            }
+        }
+        self.state = frame.state;
+        self.events.extend(frame.events);
+        Ok(result)
+    }
+
+    fn find_function(&self, name: &str) -> Result<&'a Function, InterpError> {
+        self.contract
+            .functions
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| InterpError::UnknownFunction(name.to_string()))
+    }
+}
+
+/// Execution context of a single call.
+struct Frame<'c> {
+    /// Contract constants; read-only.
+    constants: &'c HashMap<String, Value>,
+    /// Scratch copy of the contract state, committed on success.
+    state: HashMap<String, Value>,
+    /// Parameters and `let` locals.
+    locals: HashMap<String, Value>,
+    /// Events emitted during the call, committed on success.
+    events: Vec<EmittedEvent>,
+    overflow: OverflowMode,
+}
+
+impl Frame<'_> {
+    fn execute(&mut self, statement: &Statement) -> Result<Flow, InterpError> {
+        match statement {
+            Statement::Assign { target, value } => {
+                let value = self.eval(value)?;
+                if let Some(slot) = self.locals.get_mut(target) {
+                    *slot = value;
+                } else if let Some(slot) = self.state.get_mut(target) {
+                    *slot = value;
+                } else {
+                    return Err(InterpError::UnknownVariable(target.clone()));
+                }
+            }
+            Statement::Let { name, value, .. } => {
+                let value = self.eval(value)?;
+                self.locals.insert(name.clone(), value);
+            }
+            Statement::Require(condition) => match self.eval(condition)? {
+                Value::Bool(true) => {}
+                Value::Bool(false) => return Err(InterpError::RequireFailed),
+                other => return Err(InterpError::TypeMismatch(format!("require expects bool, got {:?}", other))),
+            },
+            Statement::Emit { name, args } => {
+                let args = args.iter().map(|arg| self.eval(arg)).collect::<Result<Vec<_>, _>>()?;
+                self.events.push(EmittedEvent { name: name.clone(), args });
+            }
+            Statement::Call { contract, function, .. } => {
+                return Err(InterpError::ExternalCall { contract: contract.clone(), function: function.clone() });
+            }
+            Statement::Return(value) => {
+                let value = value.as_ref().map(|v| self.eval(v)).transpose()?;
+                return Ok(Flow::Return(value));
+            }
+            Statement::Expr(expr) => {
+                self.eval(expr)?;
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Value, InterpError> {
+        match expr {
+            Expr::Int(v) => u64::try_from(*v)
+                .map(Value::U64)
+                .map_err(|_| InterpError::TypeMismatch(format!("integer literal {} does not fit u64", v))),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Str(s) => Ok(Value::Str(s.clone())),
+            Expr::Ident(name) => self
+                .locals
+                .get(name)
+                .or_else(|| self.state.get(name))
+                .or_else(|| self.constants.get(name))
+                .cloned()
+                .ok_or_else(|| InterpError::UnknownVariable(name.clone())),
+            Expr::Binary { op, left, right } => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                binary(*op, left, right, self.overflow)
+            }
+        }
+    }
+}
+
+fn binary(op: BinOp, left: Value, right: Value, overflow: OverflowMode) -> Result<Value, InterpError> {
+    match (op, left, right) {
+        (BinOp::Add | BinOp::Sub | BinOp::Mul, Value::U64(a), Value::U64(b)) => arithmetic(op, a, b, overflow),
+        (BinOp::Div, Value::U64(_), Value::U64(0)) => Err(InterpError::DivisionByZero),
+        (BinOp::Div, Value::U64(a), Value::U64(b)) => Ok(Value::U64(a / b)),
+        (BinOp::Lt, Value::U64(a), Value::U64(b)) => Ok(Value::Bool(a < b)),
+        (BinOp::Gt, Value::U64(a), Value::U64(b)) => Ok(Value::Bool(a > b)),
+        (BinOp::Le, Value::U64(a), Value::U64(b)) => Ok(Value::Bool(a <= b)),
+        (BinOp::Ge, Value::U64(a), Value::U64(b)) => Ok(Value::Bool(a >= b)),
+        (BinOp::Eq, a, b) if same_type(&a, &b) => Ok(Value::Bool(a == b)),
+        (BinOp::Ne, a, b) if same_type(&a, &b) => Ok(Value::Bool(a != b)),
+        (op, a, b) => Err(InterpError::TypeMismatch(format!("cannot apply {:?} to {:?} and {:?}", op, a, b))),
+    }
+}
+
+/// Applies `+`, `-`, or `*` under `overflow`.
+fn arithmetic(op: BinOp, a: u64, b: u64, overflow: OverflowMode) -> Result<Value, InterpError> {
+    let (wrapped, overflowed) = match op {
+        BinOp::Add => a.overflowing_add(b),
+        BinOp::Sub => a.overflowing_sub(b),
+        BinOp::Mul => a.overflowing_mul(b),
+        _ => unreachable!("only called for +, -, and *"),
+    };
+    if overflowed && overflow == OverflowMode::Checked {
+        return Err(InterpError::ArithmeticOverflow { op, left: a, right: b });
+    }
+    Ok(Value::U64(wrapped))
+}
+
+fn same_type(a: &Value, b: &Value) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsl::parse_rsl;
+
+    const VAULT: &str = r#"
+        contract Vault {
+            let balance: u64;
+            let spent: u64;
+            fn spend(amount: u64) {
+                spent = spent + amount;
+                require(spent <= balance);
+            }
+            fn withdraw(amount: u64): u64 {
+                require(amount <= balance);
+                balance = balance - amount;
+                return balance;
+            }
+            fn deposit(amount: u64) {
+                balance = balance + amount;
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_require_holds() {
+        let contract = parse_rsl(VAULT).unwrap();
+        let mut vault = Interpreter::new(&contract).unwrap();
+        vault.call("deposit", vec![Value::U64(100)]).unwrap();
+        let remaining = vault.call("withdraw", vec![Value::U64(30)]).unwrap();
+        assert_eq!(remaining, Some(Value::U64(70)));
+        assert_eq!(vault.get("balance"), Some(&Value::U64(70)));
+    }
+
+    #[test]
+    fn test_require_failure_leaves_state_unchanged() {
+        let contract = parse_rsl(VAULT).unwrap();
+        let mut vault = Interpreter::new(&contract).unwrap();
+        vault.call("deposit", vec![Value::U64(10)]).unwrap();
+        assert_eq!(vault.call("withdraw", vec![Value::U64(30)]), Err(InterpError::RequireFailed));
+        assert_eq!(vault.get("balance"), Some(&Value::U64(10)));
+        // `spend` mutates before its require fails; the mutation is rolled back.
+        assert_eq!(vault.call("spend", vec![Value::U64(11)]), Err(InterpError::RequireFailed));
+        assert_eq!(vault.get("spent"), Some(&Value::U64(0)));
+        vault.call("spend", vec![Value::U64(10)]).unwrap();
+        assert_eq!(vault.get("spent"), Some(&Value::U64(10)));
+    }
+
+    #[test]
+    fn test_constant_readable() {
+        let contract = parse_rsl(r#"
+            contract Token {
+                const MAX_SUPPLY: u64 = 1000;
+                let supply: u64;
+                fn mint(amount: u64) {
+                    require(supply + amount <= MAX_SUPPLY);
+                    supply = supply + amount;
+                }
+            }
+        "#).unwrap();
+        let mut token = Interpreter::new(&contract).unwrap();
+        token.call("mint", vec![Value::U64(1000)]).unwrap();
+        assert_eq!(token.call("mint", vec![Value::U64(1)]), Err(InterpError::RequireFailed));
+    }
+
+    #[test]
+    fn test_emit_records_event_on_success_only() {
+        let contract = parse_rsl(r#"
+            contract Token {
+                event Minted(amount: u64);
+                let supply: u64;
+                fn mint(amount: u64) {
+                    emit Minted(amount);
+                    require(amount > 0);
+                    supply = supply + amount;
+                }
+            }
+        "#).unwrap();
+        let mut token = Interpreter::new(&contract).unwrap();
+        token.call("mint", vec![Value::U64(5)]).unwrap();
+        assert_eq!(token.call("mint", vec![Value::U64(0)]), Err(InterpError::RequireFailed));
+        assert_eq!(token.events(), &[EmittedEvent { name: "Minted".into(), args: vec![Value::U64(5)] }]);
+    }
+
+    #[test]
+    fn test_argument_count_checked() {
+        let contract = parse_rsl(VAULT).unwrap();
+        let mut vault = Interpreter::new(&contract).unwrap();
+        assert_eq!(
+            vault.call("deposit", vec![]),
+            Err(InterpError::ArgumentCount { expected: 1, found: 0 })
+        );
+    }
+
+    #[test]
+    fn test_checked_arithmetic_rejects_overflow() {
+        let contract = parse_rsl(VAULT).unwrap();
+        let mut vault = Interpreter::new(&contract).unwrap();
+        vault.call("deposit", vec![Value::U64(u64::MAX - 1)]).unwrap();
+        vault.call("deposit", vec![Value::U64(1)]).unwrap();
+        assert_eq!(vault.get("balance"), Some(&Value::U64(u64::MAX)));
+        assert_eq!(
+            vault.call("deposit", vec![Value::U64(1)]),
+            Err(InterpError::ArithmeticOverflow { op: BinOp::Add, left: u64::MAX, right: 1 })
+        );
+        assert_eq!(vault.get("balance"), Some(&Value::U64(u64::MAX)));
+    }
+
+    #[test]
+    fn test_wrapping_mode_wraps() {
+        let contract = parse_rsl(VAULT).unwrap();
+        let mut vault = Interpreter::with_overflow_mode(&contract, OverflowMode::Wrapping).unwrap();
+        vault.call("deposit", vec![Value::U64(40)]).unwrap();
+        vault.call("deposit", vec![Value::U64(2)]).unwrap();
+        assert_eq!(vault.get("balance"), Some(&Value::U64(42)));
+        vault.call("deposit", vec![Value::U64(u64::MAX)]).unwrap();
+        assert_eq!(vault.get("balance"), Some(&Value::U64(41)));
+    }
+}
@@ -0,0 +1,168 @@
+//! Tokenizer for RSL function bodies.
+//!
+//! Produces a flat token stream consumed by the statement/expression parser.
+//! `//` comments are skipped, except inside string literals.
+
+use super::RslError;
+
+/// A lexical token of RSL source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Int(i128),
+    Str(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    EqEq,
+    NotEq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Assign,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Semicolon,
+    Dot,
+}
+
+/// Splits `source` into tokens.
+pub fn tokenize(source: &str) -> Result<Vec<Token>, RslError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' => {
+                let (literal, next) = read_string(&chars, i + 1)?;
+                tokens.push(Token::Str(literal));
+                i = next;
+            }
+            c if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse::<i128>()
+                    .map_err(|_| RslError::ParseError(format!("Integer literal out of range: {}", text)))?;
+                tokens.push(Token::Int(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => {
+                let next = chars.get(i + 1).copied();
+                let (token, width) = match (c, next) {
+                    ('=', Some('=')) => (Token::EqEq, 2),
+                    ('!', Some('=')) => (Token::NotEq, 2),
+                    ('<', Some('=')) => (Token::Le, 2),
+                    ('>', Some('=')) => (Token::Ge, 2),
+                    ('=', _) => (Token::Assign, 1),
+                    ('<', _) => (Token::Lt, 1),
+                    ('>', _) => (Token::Gt, 1),
+                    ('+', _) => (Token::Plus, 1),
+                    ('-', _) => (Token::Minus, 1),
+                    ('*', _) => (Token::Star, 1),
+                    ('/', _) => (Token::Slash, 1),
+                    ('(', _) => (Token::LParen, 1),
+                    (')', _) => (Token::RParen, 1),
+                    (',', _) => (Token::Comma, 1),
+                    (':', _) => (Token::Colon, 1),
+                    (';', _) => (Token::Semicolon, 1),
+                    ('.', _) => (Token::Dot, 1),
+                    _ => return Err(RslError::ParseError(format!("Unexpected character '{}'", c))),
+                };
+                tokens.push(token);
+                i += width;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Reads a string literal body starting just after the opening quote.
+/// Supports the `\"` and `\\` escapes. Returns the literal and the index after
+/// the closing quote.
+fn read_string(chars: &[char], mut i: usize) -> Result<(String, usize), RslError> {
+    let mut literal = String::new();
+    while i < chars.len() {
+        match chars[i] {
+            '"' => return Ok((literal, i + 1)),
+            '\\' => {
+                match chars.get(i + 1) {
+                    Some('"') => literal.push('"'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => {
+                        return Err(RslError::ParseError(format!("Unsupported escape '\\{}'", other)))
+                    }
+                    None => break,
+                }
+                i += 2;
+            }
+            c => {
+                literal.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(RslError::Expected("closing '\"'".to_string()))
+}
+
+/// Removes a trailing `//` comment from `line`, ignoring `//` inside string literals.
+pub fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            b'/' if !in_string && bytes.get(i + 1) == Some(&b'/') => return &line[..i],
+            _ => {}
+        }
+        i += 1;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_escapes_and_comments() {
+        let tokens = tokenize(r#"msg = "say \"hi\" // \\"; // trailing"#).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("msg".into()),
+                Token::Assign,
+                Token::Str(r#"say "hi" // \"#.into()),
+                Token::Semicolon,
+            ]
+        );
+        assert_eq!(strip_comment(r#"x = "a//b"; // note"#), r#"x = "a//b"; "#);
+    }
+
+    #[test]
+    fn test_unterminated_string_rejected() {
+        assert!(tokenize(r#"msg = "oops;"#).is_err());
+    }
+}
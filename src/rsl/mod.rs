@@ -2,9 +2,18 @@
 //!
 //! RSL is designed to be a safer, simpler smart contract language than Solidity,
 //! with explicit type declarations and a Rust-like syntax. In Phase 1, we support
-//! minimal contract, field, and function declarations. Future phases will add
+//! minimal contract, field, and function declarations; function bodies are parsed
+//! into statements and expressions by the `parser` submodule. Future phases will add
 //! concurrency, advanced validations, and integration with HPC tasks.
 
+pub mod checker;
+pub mod interpreter;
+pub mod lexer;
+pub mod parser;
+
+use lexer::strip_comment;
+use parser::{parse_expression, parse_statements};
+
 /// Error type for RSL parsing.
 #[derive(Debug, PartialEq)]
 pub enum RslError {
@@ -14,16 +23,35 @@ pub enum RslError {
     ParseError(String),
 }
 
-/// Abstract Syntax Tree (AST) definitions for RSL.
+// Abstract Syntax Tree (AST) definitions for RSL.
 
 /// Represents a contract with a name, fields, and functions.
 #[derive(Debug, PartialEq)]
 pub struct Contract {
     pub name: String,
+    pub constants: Vec<Constant>,
+    pub events: Vec<EventDecl>,
     pub fields: Vec<Field>,
     pub functions: Vec<Function>,
 }
 
+/// Represents a constant declaration, e.g., `const MAX_SUPPLY: u64 = 1000000;`
+/// Constants can be read but never assigned.
+#[derive(Debug, PartialEq)]
+pub struct Constant {
+    pub name: String,
+    pub ty: String,
+    pub value: Expr,
+}
+
+/// Represents an event declaration, e.g.,
+/// `event Transfer(from: address, to: address, amount: u64);`
+#[derive(Debug, PartialEq)]
+pub struct EventDecl {
+    pub name: String,
+    pub params: Vec<Param>,
+}
+
 /// Represents a field declaration, e.g., `let counter: u64;`
 #[derive(Debug, PartialEq)]
 pub struct Field {
@@ -37,8 +65,10 @@ pub struct Function {
     pub name: String,
     pub params: Vec<Param>,
     pub return_type: Option<String>,
-    /// For Phase 1, we simply capture the function body as a string.
+    /// The raw function body source, without the enclosing braces.
     pub body: String,
+    /// The function body parsed into statements.
+    pub statements: Vec<Statement>,
 }
 
 /// Represents a function parameter.
@@ -48,11 +78,115 @@ pub struct Param {
     pub param_type: String,
 }
 
+/// A binary operator in an expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+/// An expression in a function body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Integer literal, e.g. `42` or `-1`. Held as `i128` so the checker can
+    /// reject literals that do not fit the declared type instead of wrapping.
+    Int(i128),
+    /// Boolean literal, `true` or `false`.
+    Bool(bool),
+    /// Double-quoted string literal; `\"` and `\\` escapes are resolved.
+    Str(String),
+    /// Reference to a field, parameter, or local.
+    Ident(String),
+    /// Binary operation, e.g. `counter + amount`.
+    Binary { op: BinOp, left: Box<Expr>, right: Box<Expr> },
+}
+
+/// A statement in a function body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// `target = value;`
+    Assign { target: String, value: Expr },
+    /// `let name: ty = value;`, or `let name = value;` with the type left for
+    /// the checker to infer from `value`.
+    Let { name: String, ty: Option<String>, value: Expr },
+    /// `require(condition);` — aborts the call when the condition is false.
+    Require(Expr),
+    /// `emit Name(args);` — emits a declared event.
+    Emit { name: String, args: Vec<Expr> },
+    /// `call Contract.function(args);` — calls a function on another contract.
+    /// Parsed and checked, but not yet executable.
+    Call { contract: String, function: String, args: Vec<Expr> },
+    /// `return;` or `return value;`
+    Return(Option<Expr>),
+    /// A bare expression followed by `;`.
+    Expr(Expr),
+}
+
+/// Parses a comma-separated parameter list such as `a: u64, b: address`.
+fn parse_params(params_str: &str) -> Result<Vec<Param>, RslError> {
+    if params_str.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    params_str.split(',')
+        .map(|p| {
+            let p_parts: Vec<&str> = p.split(':').collect();
+            if p_parts.len() != 2 {
+                return Err(RslError::ParseError("Malformed parameter".to_string()));
+            }
+            Ok(Param {
+                name: p_parts[0].trim().to_string(),
+                param_type: p_parts[1].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Scans `text` for the brace that closes the current block, starting at `depth`
+/// open braces. Braces inside string literals are ignored. Returns the byte index
+/// of the closing brace, or `None` if the block continues past `text`.
+fn find_closing_brace(text: &str, depth: &mut usize) -> Option<usize> {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => *depth += 1,
+            '}' => {
+                *depth -= 1;
+                if *depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Parses an RSL source string into a Contract AST.
 ///  
 ///  The expected syntax is:
 ///
 ///  contract ContractName {
+///      const CONSTANT_NAME: type = expr;
+///      event EventName(param1: type1, param2: type2);
 ///      let field_name: type;
 ///      fn function_name(param1: type1, param2: type2): return_type {
 ///          function body;
@@ -60,8 +194,8 @@ pub struct Param {
 ///      fn another_function() {
 ///          body;
 ///      }
-///  } ```  Phase 1 only extracts the names and bodies.
-
+///  } ```  Function bodies may span multiple lines and are parsed into statements.
+///  `//` comments are ignored outside string literals.
 pub fn parse_rsl(input: &str) -> Result<Contract, RslError> {
     let input = input.trim();
     // Expect the input to start with "contract"
@@ -74,13 +208,16 @@ pub fn parse_rsl(input: &str) -> Result<Contract, RslError> {
         return Err(RslError::Expected("{".to_string()));
     }
     let name = parts[0].trim().to_string();
-    let body_str = parts[1].rsplitn(2, '}').nth(1)
+    let body_str = parts[1].rsplit_once('}').map(|(body, _)| body)
         .ok_or_else(|| RslError::Expected("}".to_string()))?;
+    let mut constants = Vec::new();
+    let mut events = Vec::new();
     let mut fields = Vec::new();
     let mut functions = Vec::new();
-    // For simplicity, split the body by newlines.
-    for line in body_str.lines() {
-        let line = line.trim();
+    // Declarations are line-oriented; function bodies may continue over several lines.
+    let mut lines = body_str.lines();
+    while let Some(line) = lines.next() {
+        let line = strip_comment(line).trim();
         if line.is_empty() {
             continue;
         }
@@ -95,6 +232,27 @@ pub fn parse_rsl(input: &str) -> Result<Contract, RslError> {
             let field_name = parts[0].trim().to_string();
             let field_type = parts[1].trim().trim_end_matches(';').to_string();
             fields.push(Field { name: field_name, field_type });
+        } else if let Some(const_line) = line.strip_prefix("const ") {
+            // Constant: const NAME: type = expr;
+            let (name, rest) = const_line.split_once(':')
+                .ok_or_else(|| RslError::ParseError("Constant missing ':'".to_string()))?;
+            let (ty, value) = rest.split_once('=')
+                .ok_or_else(|| RslError::ParseError("Constant missing '='".to_string()))?;
+            let value = value.trim().strip_suffix(';')
+                .ok_or_else(|| RslError::Expected("';' after constant".to_string()))?;
+            constants.push(Constant {
+                name: name.trim().to_string(),
+                ty: ty.trim().to_string(),
+                value: parse_expression(value)?,
+            });
+        } else if let Some(event_line) = line.strip_prefix("event ") {
+            // Event: event Name(param: type, ...);
+            let (name, rest) = event_line.split_once('(')
+                .ok_or_else(|| RslError::Expected("( after event name".to_string()))?;
+            let params_str = rest.trim().strip_suffix(';')
+                .and_then(|r| r.trim_end().strip_suffix(')'))
+                .ok_or_else(|| RslError::Expected(") and ';' closing event".to_string()))?;
+            events.push(EventDecl { name: name.trim().to_string(), params: parse_params(params_str)? });
         } else if line.starts_with("fn ") {
             // Function: fn name(params) [: return_type] { body }
             // We'll extract until the first '{'
@@ -103,7 +261,23 @@ pub fn parse_rsl(input: &str) -> Result<Contract, RslError> {
                 return Err(RslError::Expected("{".to_string()));
             }
             let header = parts[0].trim();
-            let body = parts[1].trim().trim_end_matches('}').trim().to_string();
+            // Accumulate the body until the function's opening brace is balanced.
+            let mut depth = 1;
+            let mut body = String::new();
+            let mut rest = parts[1].to_string();
+            loop {
+                if let Some(end) = find_closing_brace(&rest, &mut depth) {
+                    body.push_str(&rest[..end]);
+                    break;
+                }
+                body.push_str(&rest);
+                body.push('\n');
+                rest = lines.next()
+                    .map(|l| strip_comment(l).to_string())
+                    .ok_or_else(|| RslError::Expected("} closing function body".to_string()))?;
+            }
+            let body = body.trim().to_string();
+            let statements = parse_statements(&body)?;
             // Remove "fn " prefix
             let header = header.strip_prefix("fn ")
                 .ok_or_else(|| RslError::ParseError("Malformed function header".to_string()))?
@@ -123,26 +297,11 @@ pub fn parse_rsl(input: &str) -> Result<Contract, RslError> {
             }
             let func_name = sig_parts[0].trim().to_string();
             let params_str = sig_parts[1].trim().trim_end_matches(')');
-            let params: Vec<Param> = if params_str.is_empty() {
-                Vec::new()
-            } else {
-                params_str.split(',')
-                    .map(|p| {
-                        let p_parts: Vec<&str> = p.split(':').collect();
-                        if p_parts.len() != 2 {
-                            return Err(RslError::ParseError("Malformed parameter".to_string()));
-                        }
-                        Ok(Param {
-                            name: p_parts[0].trim().to_string(),
-                            param_type: p_parts[1].trim().to_string(),
-                        })
-                    })
-                    .collect::<Result<Vec<Param>, RslError>>()?
-            };
-            functions.push(Function { name: func_name, params, return_type: ret_type, body });
+            let params = parse_params(params_str)?;
+            functions.push(Function { name: func_name, params, return_type: ret_type, body, statements });
         }
     }
-    Ok(Contract { name, fields, functions })
+    Ok(Contract { name, constants, events, fields, functions })
 }
 
 #[cfg(test)]
@@ -176,5 +335,101 @@ mod tests {
         assert_eq!(get_fn.name, "get_counter");
         assert_eq!(get_fn.params.len(), 0);
         assert_eq!(get_fn.return_type, Some("u64".to_string()));
+        assert_eq!(get_fn.statements, vec![Statement::Return(Some(Expr::Ident("counter".to_string())))]);
+    }
+
+    #[test]
+    fn test_parse_literals_in_function_body() {
+        let sample = r#"
+            contract Flags {
+                let msg: String;
+                let flag: bool;
+                fn set(v: bool) {
+                    // A comment with "quotes" and braces {
+                    msg = "ok { // }";
+                    flag = v == true;
+                }
+            }
+        "#;
+        let ast = parse_rsl(sample).expect("Parsing failed");
+        let set_fn = &ast.functions[0];
+        assert_eq!(
+            set_fn.statements,
+            vec![
+                Statement::Assign { target: "msg".into(), value: Expr::Str("ok { // }".into()) },
+                Statement::Assign {
+                    target: "flag".into(),
+                    value: Expr::Binary {
+                        op: BinOp::Eq,
+                        left: Box::new(Expr::Ident("v".into())),
+                        right: Box::new(Expr::Bool(true)),
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_constant() {
+        let sample = r#"
+            contract Token {
+                const MAX_SUPPLY: u64 = 1000000;
+                let supply: u64;
+            }
+        "#;
+        let ast = parse_rsl(sample).expect("Parsing failed");
+        assert_eq!(
+            ast.constants,
+            vec![Constant { name: "MAX_SUPPLY".into(), ty: "u64".into(), value: Expr::Int(1_000_000) }]
+        );
+        assert_eq!(ast.fields.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_event_and_emit() {
+        let sample = r#"
+            contract Token {
+                event Transfer(from: address, to: address, amount: u64);
+                fn send(to: address, amount: u64) {
+                    emit Transfer(to, to, amount);
+                }
+            }
+        "#;
+        let ast = parse_rsl(sample).expect("Parsing failed");
+        assert_eq!(ast.events.len(), 1);
+        assert_eq!(ast.events[0].name, "Transfer");
+        assert_eq!(ast.events[0].params.len(), 3);
+        assert_eq!(ast.events[0].params[2], Param { name: "amount".into(), param_type: "u64".into() });
+        assert_eq!(
+            ast.functions[0].statements,
+            vec![Statement::Emit {
+                name: "Transfer".into(),
+                args: vec![Expr::Ident("to".into()), Expr::Ident("to".into()), Expr::Ident("amount".into())],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_external_call() {
+        let sample = r#"
+            contract Router {
+                fn forward(a: address) {
+                    call Other.transfer(a, 100);
+                }
+            }
+        "#;
+        let ast = parse_rsl(sample).expect("Parsing failed");
+        assert_eq!(
+            ast.functions[0].statements,
+            vec![Statement::Call {
+                contract: "Other".into(),
+                function: "transfer".into(),
+                args: vec![Expr::Ident("a".into()), Expr::Int(100)],
+            }]
+        );
+        assert_eq!(
+            parse_statements("call Other transfer(a);"),
+            Err(RslError::Expected("'.' after contract name".into()))
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Statement and expression parser for RSL function bodies.
+//!
+//! A recursive-descent parser over the tokens produced by `lexer::tokenize`.
+//! Operator precedence, from loosest to tightest: comparisons, `+`/`-`, `*`/`/`.
+//! A `-` in operand position is only accepted as the sign of an integer literal.
+
+use super::lexer::{tokenize, Token};
+use super::{BinOp, Expr, RslError, Statement};
+
+/// Parses a function body into a list of statements.
+pub fn parse_statements(body: &str) -> Result<Vec<Statement>, RslError> {
+    let tokens = tokenize(body)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut statements = Vec::new();
+    while !parser.at_end() {
+        statements.push(parser.statement()?);
+    }
+    Ok(statements)
+}
+
+/// Parses a single expression, e.g. a field initializer.
+pub fn parse_expression(source: &str) -> Result<Expr, RslError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expression()?;
+    if !parser.at_end() {
+        return Err(RslError::ParseError(format!("Unexpected token after expression: {:?}", parser.peek())));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), RslError> {
+        match self.next() {
+            Some(ref token) if *token == expected => Ok(()),
+            _ => Err(RslError::Expected(what.to_string())),
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<String, RslError> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(name),
+            _ => Err(RslError::Expected(what.to_string())),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, RslError> {
+        let statement = match self.peek() {
+            Some(Token::Ident(word)) if word == "return" => {
+                self.next();
+                if self.peek() == Some(&Token::Semicolon) {
+                    Statement::Return(None)
+                } else {
+                    Statement::Return(Some(self.expression()?))
+                }
+            }
+            Some(Token::Ident(word)) if word == "require" && self.peek_at(1) == Some(&Token::LParen) => {
+                self.next();
+                self.next();
+                let condition = self.expression()?;
+                self.expect(Token::RParen, "')' closing require")?;
+                Statement::Require(condition)
+            }
+            Some(Token::Ident(word)) if word == "emit" => {
+                self.next();
+                let name = self.ident("event name")?;
+                self.expect(Token::LParen, "'(' after event name")?;
+                let args = self.arguments()?;
+                Statement::Emit { name, args }
+            }
+            Some(Token::Ident(word)) if word == "call" => {
+                self.next();
+                let contract = self.ident("contract name")?;
+                self.expect(Token::Dot, "'.' after contract name")?;
+                let function = self.ident("function name")?;
+                self.expect(Token::LParen, "'(' after function name")?;
+                let args = self.arguments()?;
+                Statement::Call { contract, function, args }
+            }
+            Some(Token::Ident(word)) if word == "let" => {
+                self.next();
+                let name = self.ident("local name")?;
+                let ty = if self.peek() == Some(&Token::Colon) {
+                    self.next();
+                    Some(self.ident("local type")?)
+                } else {
+                    None
+                };
+                self.expect(Token::Assign, "'=' in let binding")?;
+                let value = self.expression()?;
+                Statement::Let { name, ty, value }
+            }
+            Some(Token::Ident(_)) if self.peek_at(1) == Some(&Token::Assign) => {
+                let target = self.ident("assignment target")?;
+                self.next();
+                let value = self.expression()?;
+                Statement::Assign { target, value }
+            }
+            _ => Statement::Expr(self.expression()?),
+        };
+        self.expect(Token::Semicolon, "';'")?;
+        Ok(statement)
+    }
+
+    /// Parses a comma-separated argument list after the opening `(`, consuming the `)`.
+    fn arguments(&mut self) -> Result<Vec<Expr>, RslError> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.next();
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expression()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RParen) => return Ok(args),
+                _ => return Err(RslError::Expected("',' or ')' in argument list".to_string())),
+            }
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, RslError> {
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, RslError> {
+        let mut left = self.additive()?;
+        while let Some(op) = self.peek().and_then(comparison_op) {
+            self.next();
+            let right = self.additive()?;
+            left = Expr::Binary { op, left: Box::new(left), right: Box::new(right) };
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Expr, RslError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => BinOp::Add,
+                Some(Token::Minus) => BinOp::Sub,
+                _ => return Ok(left),
+            };
+            self.next();
+            let right = self.multiplicative()?;
+            left = Expr::Binary { op, left: Box::new(left), right: Box::new(right) };
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, RslError> {
+        let mut left = self.primary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => BinOp::Mul,
+                Some(Token::Slash) => BinOp::Div,
+                _ => return Ok(left),
+            };
+            self.next();
+            let right = self.primary()?;
+            left = Expr::Binary { op, left: Box::new(left), right: Box::new(right) };
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, RslError> {
+        match self.next() {
+            Some(Token::Int(value)) => Ok(Expr::Int(value)),
+            Some(Token::Minus) => match self.next() {
+                Some(Token::Int(value)) => Ok(Expr::Int(-value)),
+                _ => Err(RslError::Expected("integer literal after '-'".to_string())),
+            },
+            Some(Token::Str(value)) => Ok(Expr::Str(value)),
+            Some(Token::Ident(word)) if word == "true" => Ok(Expr::Bool(true)),
+            Some(Token::Ident(word)) if word == "false" => Ok(Expr::Bool(false)),
+            Some(Token::Ident(name)) => Ok(Expr::Ident(name)),
+            Some(Token::LParen) => {
+                let inner = self.expression()?;
+                self.expect(Token::RParen, "')'")?;
+                Ok(inner)
+            }
+            Some(token) => Err(RslError::ParseError(format!("Unexpected token in expression: {:?}", token))),
+            None => Err(RslError::Expected("expression".to_string())),
+        }
+    }
+}
+
+fn comparison_op(token: &Token) -> Option<BinOp> {
+    match token {
+        Token::EqEq => Some(BinOp::Eq),
+        Token::NotEq => Some(BinOp::Ne),
+        Token::Lt => Some(BinOp::Lt),
+        Token::Gt => Some(BinOp::Gt),
+        Token::Le => Some(BinOp::Le),
+        Token::Ge => Some(BinOp::Ge),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ident(name: &str) -> Box<Expr> {
+        Box::new(Expr::Ident(name.to_string()))
+    }
+
+    #[test]
+    fn test_precedence() {
+        let expr = parse_expression("a + b * 2 == c").unwrap();
+        let expected = Expr::Binary {
+            op: BinOp::Eq,
+            left: Box::new(Expr::Binary {
+                op: BinOp::Add,
+                left: ident("a"),
+                right: Box::new(Expr::Binary { op: BinOp::Mul, left: ident("b"), right: Box::new(Expr::Int(2)) }),
+            }),
+            right: ident("c"),
+        };
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn test_string_literal_assignment() {
+        let statements = parse_statements(r#"msg = "ok // not a comment";"#).unwrap();
+        assert_eq!(
+            statements,
+            vec![Statement::Assign { target: "msg".into(), value: Expr::Str("ok // not a comment".into()) }]
+        );
+    }
+
+    #[test]
+    fn test_boolean_comparison() {
+        let statements = parse_statements("ready = flag == true;\nreturn false;").unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Assign {
+                    target: "ready".into(),
+                    value: Expr::Binary { op: BinOp::Eq, left: ident("flag"), right: Box::new(Expr::Bool(true)) },
+                },
+                Statement::Return(Some(Expr::Bool(false))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_require_statement() {
+        let statements = parse_statements("require(amount > 0);").unwrap();
+        assert_eq!(
+            statements,
+            vec![Statement::Require(Expr::Binary {
+                op: BinOp::Gt,
+                left: ident("amount"),
+                right: Box::new(Expr::Int(0)),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_missing_semicolon_rejected() {
+        assert_eq!(parse_statements("x = 1"), Err(RslError::Expected("';'".to_string())));
+    }
+}
@@ -0,0 +1,318 @@
+//! Account State for Reina.
+//!
+//! `Ledger` tracks account balances and applies blocks atomically: every
+//! transaction moves `amount` from sender to recipient and burns its fee,
+//! and a block that fails part-way leaves balances untouched.
+//! `NonceTracker` rejects replayed or out-of-sequence nonces per sender.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::consensus::block_producer::Block;
+use crate::utils::serialization::Transaction;
+
+/// Reasons a block cannot be applied to the ledger.
+#[derive(Debug, PartialEq)]
+pub enum StateError {
+    /// The sender cannot cover the transaction's amount plus fee.
+    InsufficientBalance { tx_id: u64, needed: u64, available: u64 },
+    /// The fee is negative or not a finite number.
+    InvalidFee { tx_id: u64 },
+    /// A credit or debit total would overflow a u64 balance, or the sender
+    /// has used every nonce.
+    Overflow { address: String },
+    /// The nonce is not the one following the sender's last applied nonce.
+    NonceMismatch { tx_id: u64, expected: u64, found: u64 },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::InsufficientBalance { tx_id, needed, available } => write!(
+                f,
+                "Transaction {} needs {} but the sender has {}",
+                tx_id, needed, available
+            ),
+            StateError::InvalidFee { tx_id } => write!(f, "Transaction {} has an invalid fee", tx_id),
+            StateError::Overflow { address } => write!(f, "Balance of {} would overflow", address),
+            StateError::NonceMismatch { tx_id, expected, found } => {
+                write!(f, "Transaction {} has nonce {} but {} was expected", tx_id, found, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// Highest applied nonce per sender. A sender's first transaction must carry
+/// nonce 0 and every later one the previous nonce plus one, so a transaction
+/// cannot be replayed once applied.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NonceTracker {
+    last_applied: HashMap<String, u64>,
+}
+
+impl NonceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the highest nonce applied for `sender`, if any.
+    pub fn last_applied(&self, sender: &str) -> Option<u64> {
+        self.last_applied.get(sender).copied()
+    }
+
+    /// Records `tx`'s nonce if it is exactly the next one for its sender.
+    pub fn record(&mut self, tx: &Transaction) -> Result<(), StateError> {
+        let expected = match self.last_applied(&tx.sender) {
+            Some(last) => last.checked_add(1).ok_or_else(|| StateError::Overflow { address: tx.sender.clone() })?,
+            None => 0,
+        };
+        if tx.nonce != expected {
+            return Err(StateError::NonceMismatch { tx_id: tx.id, expected, found: tx.nonce });
+        }
+        self.last_applied.insert(tx.sender.clone(), tx.nonce);
+        Ok(())
+    }
+
+    /// Sets `sender`'s last applied nonce back to `last`, as read before a
+    /// `record` that is being undone.
+    fn restore(&mut self, sender: String, last: Option<u64>) {
+        match last {
+            Some(nonce) => self.last_applied.insert(sender, nonce),
+            None => self.last_applied.remove(&sender),
+        };
+    }
+}
+
+/// Prior values of the entries a block has changed so far, in change order,
+/// so a failed block can be undone without copying the whole ledger.
+#[derive(Default)]
+struct UndoLog {
+    balances: Vec<(String, Option<u64>)>,
+    nonces: Vec<(String, Option<u64>)>,
+}
+
+/// Account balances keyed by address, and the nonces applied per sender.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Ledger {
+    balances: HashMap<String, u64>,
+    nonces: NonceTracker,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the balance of `addr`; unknown addresses hold zero.
+    pub fn balance(&self, addr: &str) -> u64 {
+        self.balances.get(addr).copied().unwrap_or(0)
+    }
+
+    /// Credits `amount` to `addr`, e.g. for genesis allocations.
+    pub fn credit(&mut self, addr: &str, amount: u64) -> Result<(), StateError> {
+        let balance = self.balances.entry(addr.to_string()).or_insert(0);
+        *balance = balance.checked_add(amount).ok_or_else(|| StateError::Overflow { address: addr.to_string() })?;
+        Ok(())
+    }
+
+    /// Returns the per-sender nonces applied so far.
+    pub fn nonces(&self) -> &NonceTracker {
+        &self.nonces
+    }
+
+    /// Applies every transaction in `block` in order. Each must carry its
+    /// sender's next nonce. If any fails, the entries changed so far are
+    /// restored from an undo log, leaving the ledger exactly as it was
+    /// before the call.
+    pub fn apply_block(&mut self, block: &Block) -> Result<(), StateError> {
+        let mut undo = UndoLog::default();
+        for tx in &block.transactions {
+            undo.nonces.push((tx.sender.clone(), self.nonces.last_applied(&tx.sender)));
+            let applied = self
+                .nonces
+                .record(tx)
+                .and_then(|()| apply_transaction(&mut self.balances, &mut undo.balances, tx));
+            if let Err(e) = applied {
+                self.undo(undo);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores every entry in `undo`, latest change first.
+    fn undo(&mut self, undo: UndoLog) {
+        for (addr, balance) in undo.balances.into_iter().rev() {
+            match balance {
+                Some(balance) => self.balances.insert(addr, balance),
+                None => self.balances.remove(&addr),
+            };
+        }
+        for (sender, last) in undo.nonces.into_iter().rev() {
+            self.nonces.restore(sender, last);
+        }
+    }
+}
+
+/// Fee charged against the sender's balance, rounded up to whole units.
+pub fn fee_units(tx: &Transaction) -> Result<u64, StateError> {
+    if !tx.fee.is_finite() || tx.fee < 0.0 {
+        return Err(StateError::InvalidFee { tx_id: tx.id });
+    }
+    Ok(tx.fee.ceil() as u64)
+}
+
+/// Moves `tx`'s amount and burns its fee, logging each balance's prior value
+/// in `undo` before changing it.
+fn apply_transaction(
+    balances: &mut HashMap<String, u64>,
+    undo: &mut Vec<(String, Option<u64>)>,
+    tx: &Transaction,
+) -> Result<(), StateError> {
+    let needed = tx
+        .amount
+        .checked_add(fee_units(tx)?)
+        .ok_or_else(|| StateError::Overflow { address: tx.sender.clone() })?;
+    let available = balances.get(&tx.sender).copied().unwrap_or(0);
+    if available < needed {
+        return Err(StateError::InsufficientBalance { tx_id: tx.id, needed, available });
+    }
+    undo.push((tx.sender.clone(), balances.get(&tx.sender).copied()));
+    balances.insert(tx.sender.clone(), available - needed);
+    undo.push((tx.recipient.clone(), balances.get(&tx.recipient).copied()));
+    let recipient = balances.entry(tx.recipient.clone()).or_insert(0);
+    *recipient = recipient
+        .checked_add(tx.amount)
+        .ok_or_else(|| StateError::Overflow { address: tx.recipient.clone() })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(id: u64, sender: &str, recipient: &str, amount: u64, fee: f64) -> Transaction {
+        Transaction {
+            id,
+            amount,
+            fee,
+            version: 1,
+            nonce: 0,
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            signature: vec![1, 2, 3, 4].into(),
+        }
+    }
+
+    fn block(transactions: Vec<Transaction>) -> Block {
+        let tx_count = transactions.len() as u64;
+        Block {
+            block_number: 1,
+            previous_hash: [0u8; 32],
+            transactions,
+            tx_count,
+            merkle_root: [0u8; 32],
+            timestamp: 0,
+            signature: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_valid_transfer() {
+        let mut ledger = Ledger::new();
+        ledger.credit("Alice", 100).unwrap();
+        ledger.apply_block(&block(vec![tx(1, "Alice", "Bob", 60, 1.0)])).unwrap();
+        assert_eq!(ledger.balance("Alice"), 39);
+        assert_eq!(ledger.balance("Bob"), 60);
+    }
+
+    #[test]
+    fn test_insufficient_balance_rolls_back_block() {
+        let mut ledger = Ledger::new();
+        ledger.credit("Alice", 100).unwrap();
+        let before = ledger.clone();
+        let result = ledger.apply_block(&block(vec![
+            tx(1, "Alice", "Bob", 50, 1.0),
+            tx(2, "Bob", "Carol", 60, 1.0),
+        ]));
+        assert_eq!(result, Err(StateError::InsufficientBalance { tx_id: 2, needed: 61, available: 50 }));
+        assert_eq!(ledger, before);
+    }
+
+    #[test]
+    fn test_failure_after_debit_restores_sender() {
+        let mut ledger = Ledger::new();
+        ledger.credit("Alice", 100).unwrap();
+        ledger.credit("Bob", u64::MAX).unwrap();
+        let before = ledger.clone();
+        // The second transfer debits Alice, then overflows Bob's balance.
+        let mut second = tx(2, "Alice", "Bob", 10, 1.0);
+        second.nonce = 1;
+        let result = ledger.apply_block(&block(vec![tx(1, "Alice", "Carol", 20, 1.0), second]));
+        assert_eq!(result, Err(StateError::Overflow { address: "Bob".into() }));
+        assert_eq!(ledger, before);
+        assert_eq!(ledger.nonces().last_applied("Alice"), None);
+    }
+
+    #[test]
+    fn test_fees_are_debited_and_burned() {
+        let mut ledger = Ledger::new();
+        ledger.credit("Alice", 10).unwrap();
+        // A fractional fee rounds up: 2.5 costs 3 units.
+        ledger.apply_block(&block(vec![tx(1, "Alice", "Bob", 7, 2.5)])).unwrap();
+        assert_eq!(ledger.balance("Alice"), 0);
+        assert_eq!(ledger.balance("Bob"), 7);
+        assert!(ledger.apply_block(&block(vec![tx(2, "Bob", "Alice", 7, 0.5)])).is_err());
+    }
+
+    fn tx_with_nonce(id: u64, nonce: u64) -> Transaction {
+        Transaction { nonce, ..tx(id, "Alice", "Bob", 1, 1.0) }
+    }
+
+    #[test]
+    fn test_sequential_nonces_accepted() {
+        let mut ledger = Ledger::new();
+        ledger.credit("Alice", 100).unwrap();
+        ledger.apply_block(&block(vec![tx_with_nonce(1, 0), tx_with_nonce(2, 1)])).unwrap();
+        ledger.apply_block(&block(vec![tx_with_nonce(3, 2)])).unwrap();
+        assert_eq!(ledger.nonces().last_applied("Alice"), Some(2));
+        assert_eq!(ledger.nonces().last_applied("Bob"), None);
+        assert_eq!(ledger.balance("Bob"), 3);
+    }
+
+    #[test]
+    fn test_replayed_nonce_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.credit("Alice", 100).unwrap();
+        ledger.apply_block(&block(vec![tx_with_nonce(1, 0)])).unwrap();
+        let before = ledger.clone();
+        assert_eq!(
+            ledger.apply_block(&block(vec![tx_with_nonce(1, 0)])),
+            Err(StateError::NonceMismatch { tx_id: 1, expected: 1, found: 0 })
+        );
+        // The same nonce twice within one block is a replay too.
+        assert_eq!(
+            ledger.apply_block(&block(vec![tx_with_nonce(2, 1), tx_with_nonce(3, 1)])),
+            Err(StateError::NonceMismatch { tx_id: 3, expected: 2, found: 1 })
+        );
+        assert_eq!(ledger, before);
+    }
+
+    #[test]
+    fn test_nonce_gap_rejected() {
+        let mut ledger = Ledger::new();
+        ledger.credit("Alice", 100).unwrap();
+        assert_eq!(
+            ledger.apply_block(&block(vec![tx_with_nonce(1, 1)])),
+            Err(StateError::NonceMismatch { tx_id: 1, expected: 0, found: 1 })
+        );
+        ledger.apply_block(&block(vec![tx_with_nonce(1, 0)])).unwrap();
+        assert_eq!(
+            ledger.apply_block(&block(vec![tx_with_nonce(2, 2)])),
+            Err(StateError::NonceMismatch { tx_id: 2, expected: 1, found: 2 })
+        );
+        assert_eq!(ledger.nonces().last_applied("Alice"), Some(0));
+    }
+}
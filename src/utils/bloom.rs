@@ -0,0 +1,61 @@
+//! Bloom filter over `u64` keys.
+//!
+//! Answers "possibly seen" or "definitely not seen" in constant time. Used by
+//! the mempool to skip exact lookups for transaction ids it has never held.
+
+use std::f64::consts::LN_2;
+
+/// A fixed-size Bloom filter sized for an expected number of keys and a
+/// target false-positive rate. Keys cannot be removed; `clear` resets it.
+#[derive(Debug, Clone)]
+pub struct Bloom {
+    bits: Vec<u64>,
+    /// Number of bits in the filter.
+    num_bits: u64,
+    /// Number of bit positions set per key.
+    num_hashes: u32,
+}
+
+impl Bloom {
+    /// Creates a filter that holds `expected_items` keys at roughly
+    /// `false_positive_rate`. Both are clamped to usable minimums.
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = if false_positive_rate > 0.0 && false_positive_rate < 1.0 { false_positive_rate } else { 0.01 };
+        let num_bits = (-n * p.ln() / (LN_2 * LN_2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * LN_2).round().max(1.0) as u32;
+        Self { bits: vec![0; num_bits.div_ceil(64) as usize], num_bits, num_hashes }
+    }
+
+    /// Records `key`.
+    pub fn insert(&mut self, key: u64) {
+        for bit in self.positions(key) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns false if `key` was never inserted; true means it probably was.
+    pub fn contains(&self, key: u64) -> bool {
+        self.positions(key).all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Forgets every key.
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// Bit positions for `key`, by double hashing two independent mixes.
+    fn positions(&self, key: u64) -> impl Iterator<Item = u64> {
+        let h1 = mix(key);
+        let h2 = mix(key ^ 0x9e37_79b9_7f4a_7c15) | 1;
+        let num_bits = self.num_bits;
+        (0..u64::from(self.num_hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+/// SplitMix64 finalizer: spreads sequential ids across all 64 bits.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,80 @@
+//! Pluggable 32-byte hash functions.
+//!
+//! Block hashes, Merkle roots and PoCUP puzzles default to Blake3; their
+//! `_with` variants take any `Hasher`, e.g. `Sha256Hasher` to interoperate
+//! with SHA-256 based systems. A chain fixes its hasher through the `H`
+//! parameter of `ChainManager` and `BlockProducer`; every node on it must use
+//! the same one.
+
+use sha2::{Digest, Sha256};
+
+/// A hash function with a 32-byte output.
+pub trait Hasher {
+    /// Returns the digest of `data`.
+    fn hash(data: &[u8]) -> [u8; 32];
+}
+
+/// Blake3; the default.
+pub struct Blake3Hasher;
+
+impl Hasher for Blake3Hasher {
+    #[inline(always)]
+    fn hash(data: &[u8]) -> [u8; 32] {
+        *blake3::hash(data).as_bytes()
+    }
+}
+
+/// SHA-256.
+pub struct Sha256Hasher;
+
+impl Hasher for Sha256Hasher {
+    #[inline(always)]
+    fn hash(data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+}
+
+/// Returns the Merkle root of `leaves` under `H`. Each parent is the hash of
+/// its two children concatenated; a level with an odd number of nodes pairs
+/// its last node with itself. The root of no leaves is the hash of nothing.
+pub fn merkle_root<H: Hasher>(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return H::hash(&[]);
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut joined = [0u8; 64];
+                joined[..32].copy_from_slice(&pair[0]);
+                joined[32..].copy_from_slice(pair.last().expect("chunks are non-empty"));
+                H::hash(&joined)
+            })
+            .collect();
+    }
+    level[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_known_digest() {
+        let digest = Sha256Hasher::hash(b"abc");
+        assert_eq!(digest[..4], [0xba, 0x78, 0x16, 0xbf]);
+        assert_ne!(digest, Blake3Hasher::hash(b"abc"));
+    }
+
+    #[test]
+    fn test_merkle_root_pairs_odd_leaf_with_itself() {
+        let leaves: Vec<[u8; 32]> = (0..3u8).map(|i| Blake3Hasher::hash(&[i])).collect();
+        let pair = |a: &[u8; 32], b: &[u8; 32]| Blake3Hasher::hash(&[&a[..], &b[..]].concat());
+        let expected = pair(&pair(&leaves[0], &leaves[1]), &pair(&leaves[2], &leaves[2]));
+        assert_eq!(merkle_root::<Blake3Hasher>(&leaves), expected);
+        assert_eq!(merkle_root::<Blake3Hasher>(&leaves[..1]), leaves[0]);
+        assert_eq!(merkle_root::<Sha256Hasher>(&[]), Sha256Hasher::hash(&[]));
+        assert_ne!(merkle_root::<Sha256Hasher>(&leaves), expected);
+    }
+}
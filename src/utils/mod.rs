@@ -1 +1,3 @@
+pub mod bloom;
+pub mod hasher;
 pub mod serialization;
\ No newline at end of file
@@ -1,20 +1,36 @@
 // File: src/utils/serialization.rs
 
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt;
-use std::io::Cursor;
+use std::cell::{Cell, RefCell};
+use std::io::{Cursor, ErrorKind, Read};
+use std::ops::Deref;
 use std::hint::black_box;
 use blake3; // Blake3 leverages SIMD and multithreading
 use rayon::prelude::*;
+use fixed_encoding::Fixed;
 
 /// Supported endianness.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Endianness {
     Little,
     Big,
 }
 
+/// How integers and length prefixes are laid out on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    /// LEB128 varints for integers and length prefixes; the crate's native format.
+    #[default]
+    CompactVarint,
+    /// Every integer at its full width and every length prefix as a 4-byte
+    /// u32, all big-endian regardless of the `Endianness` passed. Easier to
+    /// parse from other languages, at the cost of size.
+    FixedBigEndian,
+}
+
 impl Endianness {
     /// Writes a u32 value into a buffer using the selected endianness.
     #[inline(always)]
@@ -50,6 +66,9 @@ pub enum SerializationError {
     InvalidData(String),
     BufferTooSmall,
     Overflow,
+    /// The frame's length prefix marks it as written with `found`, but the
+    /// caller asked to read it as `expected`.
+    EndiannessMismatch { expected: Endianness, found: Endianness },
 }
 
 impl From<std::io::Error> for SerializationError {
@@ -70,6 +89,9 @@ impl fmt::Display for SerializationError {
             SerializationError::InvalidData(msg) => write!(f, "Invalid data: {}", msg),
             SerializationError::BufferTooSmall => write!(f, "Buffer too small"),
             SerializationError::Overflow => write!(f, "Integer overflow in length calculation"),
+            SerializationError::EndiannessMismatch { expected, found } => {
+                write!(f, "Endianness mismatch: frame was written {:?}, read as {:?}", found, expected)
+            }
         }
     }
 }
@@ -88,6 +110,13 @@ pub trait Decode: Sized {
     fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)>;
 }
 
+/// Implemented by types whose `encoded_size` is the same for every value, so
+/// an exactly-sized buffer can be allocated on the stack. See
+/// `Serializer::serialize_fixed_stack`.
+pub trait ConstEncodedSize {
+    const SIZE: usize;
+}
+
 /// --- Varint and ZigZag Helper Functions ---
 #[inline(always)]
 fn encode_varint_u64(mut value: u64, buffer: &mut [u8]) -> SerializationResult<usize> {
@@ -110,23 +139,55 @@ fn encode_varint_u64(mut value: u64, buffer: &mut [u8]) -> SerializationResult<u
     Ok(i)
 }
 
+/// Longest valid LEB128 encoding of a u64: ceil(64 / 7) bytes.
+pub const MAX_VARINT_BYTES: usize = 10;
+
+/// How varint decoding treats overlong encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VarintMode {
+    /// Only the minimal encoding is accepted; the default.
+    #[default]
+    Strict,
+    /// Overlong encodings such as `0x80 0x00` are accepted, for reading data
+    /// written before decoding became strict.
+    Lenient,
+}
+
+thread_local! {
+    /// Varint mode of decodes on this thread; only `Serializer::deserialize_lenient`
+    /// changes it, for the duration of one call.
+    static VARINT_MODE: Cell<VarintMode> = const { Cell::new(VarintMode::Strict) };
+}
+
+/// Decodes a LEB128 varint, accepting only the minimal encoding that
+/// `encode_varint_u64` produces. An overlong form such as `0x80 0x00` for zero
+/// is `InvalidData("non-canonical varint")`, so every value has exactly one
+/// encoding and content hashes over encoded data stay unique. Inside
+/// `Serializer::deserialize_lenient`, overlong forms are accepted instead.
 #[inline(always)]
 fn decode_varint_u64(buffer: &[u8]) -> SerializationResult<(u64, usize)> {
     let mut value = 0u64;
-    let mut shift = 0;
-    let mut i = 0;
-    while i < buffer.len() {
-        let byte = buffer[i];
+    for (i, &byte) in buffer.iter().take(MAX_VARINT_BYTES).enumerate() {
         let part = (byte & 0x7F) as u64;
-        value |= part.checked_shl(shift).ok_or(SerializationError::Overflow)?;
-        i += 1;
-        if byte & 0x80 == 0 {
-            return Ok((value, i));
-        }
-        shift += 7;
-        if shift >= 64 {
+        // The final byte may only carry the single remaining bit of a u64.
+        if i == MAX_VARINT_BYTES - 1 && part > 1 {
             return Err(SerializationError::InvalidData("varint overflow".into()));
         }
+        value |= part << (7 * i);
+        if byte & 0x80 == 0 {
+            // A zero final byte after a continuation adds nothing: the previous
+            // byte could have ended the varint.
+            if i > 0 && byte == 0 && VARINT_MODE.get() == VarintMode::Strict {
+                return Err(SerializationError::InvalidData("non-canonical varint".into()));
+            }
+            return Ok((value, i + 1));
+        }
+    }
+    if buffer.len() >= MAX_VARINT_BYTES {
+        return Err(SerializationError::InvalidData(format!(
+            "varint longer than {} bytes",
+            MAX_VARINT_BYTES
+        )));
     }
     Err(SerializationError::InvalidData("buffer ended unexpectedly while reading varint".into()))
 }
@@ -256,6 +317,33 @@ impl Decode for i64 {
     }
 }
 
+impl Encode for u8 {
+    #[inline(always)]
+    fn encoded_size(&self) -> usize { 1 }
+    #[inline(always)]
+    fn encode_to(&self, buffer: &mut [u8], _endianness: Endianness) -> SerializationResult<usize> {
+        if buffer.is_empty() {
+            return Err(SerializationError::BufferTooSmall);
+        }
+        buffer[0] = *self;
+        Ok(1)
+    }
+}
+
+impl ConstEncodedSize for u8 {
+    const SIZE: usize = 1;
+}
+
+impl Decode for u8 {
+    #[inline(always)]
+    fn decode_from(buffer: &[u8], _endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        match buffer.first() {
+            Some(&byte) => Ok((byte, 1)),
+            None => Err(SerializationError::InvalidData("Empty buffer when expecting u8".into())),
+        }
+    }
+}
+
 impl Encode for bool {
     #[inline(always)]
     fn encoded_size(&self) -> usize { 1 }
@@ -269,6 +357,10 @@ impl Encode for bool {
     }
 }
 
+impl ConstEncodedSize for bool {
+    const SIZE: usize = 1;
+}
+
 impl Decode for bool {
     #[inline(always)]
     fn decode_from(buffer: &[u8], _endianness: Endianness) -> SerializationResult<(Self, usize)> {
@@ -283,6 +375,35 @@ impl Decode for bool {
     }
 }
 
+/// An `Option` is a presence byte encoded like `bool`, followed by the value
+/// when present.
+impl<T: Encode> Encode for Option<T> {
+    #[inline(always)]
+    fn encoded_size(&self) -> usize {
+        1 + self.as_ref().map_or(0, T::encoded_size)
+    }
+    #[inline(always)]
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let tag = self.is_some().encode_to(buffer, endianness)?;
+        match self {
+            Some(value) => Ok(tag + value.encode_to(&mut buffer[tag..], endianness)?),
+            None => Ok(tag),
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    #[inline(always)]
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let (present, tag) = bool::decode_from(buffer, endianness)?;
+        if !present {
+            return Ok((None, tag));
+        }
+        let (value, consumed) = T::decode_from(&buffer[tag..], endianness)?;
+        Ok((Some(value), tag + consumed))
+    }
+}
+
 impl Encode for f64 {
     #[inline(always)]
     fn encoded_size(&self) -> usize { 8 }
@@ -299,6 +420,10 @@ impl Encode for f64 {
     }
 }
 
+impl ConstEncodedSize for f64 {
+    const SIZE: usize = 8;
+}
+
 impl Decode for f64 {
     #[inline(always)]
     fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
@@ -322,7 +447,7 @@ impl Decode for f64 {
 impl Encode for String {
     #[inline(always)]
     fn encoded_size(&self) -> usize {
-        let len = self.as_bytes().len();
+        let len = self.len();
         let mut size = 0;
         let mut temp = len as u64;
         while temp >= 0x80 { size += 1; temp >>= 7; }
@@ -361,41 +486,256 @@ impl Decode for String {
     }
 }
 
+/// Encoded length of a varint length prefix followed by `bytes`.
+#[inline(always)]
+fn byte_string_size(bytes: &[u8]) -> usize {
+    let len = bytes.len();
+    let mut size = 0;
+    let mut temp = len as u64;
+    while temp >= 0x80 { size += 1; temp >>= 7; }
+    size + 1 + len
+}
+
+/// Writes a varint length prefix followed by `bytes`.
+#[inline(always)]
+fn encode_byte_string(bytes: &[u8], buffer: &mut [u8]) -> SerializationResult<usize> {
+    let len = bytes.len();
+    if buffer.len() < byte_string_size(bytes) {
+        return Err(SerializationError::BufferTooSmall);
+    }
+    let written = encode_varint_u64(len as u64, buffer)?;
+    buffer[written..written+len].copy_from_slice(bytes);
+    Ok(written + len)
+}
+
+/// Reads a varint length prefix and returns the bytes it covers, borrowed
+/// from `buffer`, with the total bytes consumed.
+#[inline(always)]
+fn decode_byte_string(buffer: &[u8]) -> SerializationResult<(&[u8], usize)> {
+    let (len, varint_size) = decode_varint_u64(buffer)?;
+    let total = varint_size.checked_add(len as usize).ok_or(SerializationError::Overflow)?;
+    if buffer.len() < total {
+        return Err(SerializationError::InvalidData("Not enough bytes for Vec<u8>".into()));
+    }
+    Ok((&buffer[varint_size..total], total))
+}
+
 impl Encode for Vec<u8> {
     #[inline(always)]
     fn encoded_size(&self) -> usize {
-        let len = self.len();
-        let mut size = 0;
-        let mut temp = len as u64;
-        while temp >= 0x80 { size += 1; temp >>= 7; }
-        size + 1 + len
+        byte_string_size(self)
     }
     #[inline(always)]
     fn encode_to(&self, buffer: &mut [u8], _endianness: Endianness) -> SerializationResult<usize> {
-        let len = self.len();
-        let mut varint_size = 0;
-        let mut temp = len as u64;
-        while temp >= 0x80 { varint_size += 1; temp >>= 7; }
-        varint_size += 1;
-        if buffer.len() < varint_size + len {
+        encode_byte_string(self, buffer)
+    }
+}
+
+impl Decode for Vec<u8> {
+    #[inline(always)]
+    fn decode_from(buffer: &[u8], _endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let (bytes, total) = decode_byte_string(buffer)?;
+        Ok((bytes.to_vec(), total))
+    }
+}
+
+/// Signatures up to this length are stored inline, without a heap
+/// allocation, when the `small_signature` feature is enabled.
+pub const INLINE_SIGNATURE_LEN: usize = 64;
+
+#[cfg(feature = "small_signature")]
+type SignatureStorage = smallvec::SmallVec<[u8; INLINE_SIGNATURE_LEN]>;
+#[cfg(not(feature = "small_signature"))]
+type SignatureStorage = Vec<u8>;
+
+/// Signature bytes of a `Transaction`.
+///
+/// Backed by a `Vec<u8>` by default. With the `small_signature` feature, a
+/// `SmallVec` keeps signatures of up to `INLINE_SIGNATURE_LEN` bytes (every
+/// Ed25519 signature) on the stack. Either way the wire format is that of
+/// `Vec<u8>`: a varint length followed by the bytes. The inline storage makes
+/// every `Transaction` larger, so compare both builds with the
+/// `signature_storage_decode` benchmark before enabling it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignatureBytes(SignatureStorage);
+
+impl SignatureBytes {
+    /// Creates an empty signature.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the signature bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Deref for SignatureBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for SignatureBytes {
+    #[cfg(feature = "small_signature")]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(SignatureStorage::from_vec(bytes))
+    }
+
+    #[cfg(not(feature = "small_signature"))]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&[u8]> for SignatureBytes {
+    #[cfg(feature = "small_signature")]
+    fn from(bytes: &[u8]) -> Self {
+        // `from_slice` copies in one go; the generic `From<&[T]>` clones bytewise.
+        Self(SignatureStorage::from_slice(bytes))
+    }
+
+    #[cfg(not(feature = "small_signature"))]
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl Encode for SignatureBytes {
+    #[inline(always)]
+    fn encoded_size(&self) -> usize {
+        byte_string_size(self)
+    }
+    #[inline(always)]
+    fn encode_to(&self, buffer: &mut [u8], _endianness: Endianness) -> SerializationResult<usize> {
+        encode_byte_string(self, buffer)
+    }
+}
+
+impl Decode for SignatureBytes {
+    #[inline(always)]
+    fn decode_from(buffer: &[u8], _endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let (bytes, total) = decode_byte_string(buffer)?;
+        Ok((bytes.into(), total))
+    }
+}
+
+impl<const N: usize> Encode for [u8; N] {
+    #[inline(always)]
+    fn encoded_size(&self) -> usize { N }
+    #[inline(always)]
+    fn encode_to(&self, buffer: &mut [u8], _endianness: Endianness) -> SerializationResult<usize> {
+        if buffer.len() < N {
             return Err(SerializationError::BufferTooSmall);
         }
-        let written = encode_varint_u64(len as u64, buffer)?;
-        buffer[written..written+len].copy_from_slice(self);
-        Ok(written + len)
+        buffer[..N].copy_from_slice(self);
+        Ok(N)
     }
 }
 
-impl Decode for Vec<u8> {
+impl<const N: usize> ConstEncodedSize for [u8; N] {
+    const SIZE: usize = N;
+}
+
+impl<const N: usize> Decode for [u8; N] {
     #[inline(always)]
     fn decode_from(buffer: &[u8], _endianness: Endianness) -> SerializationResult<(Self, usize)> {
-        let (len, varint_size) = decode_varint_u64(buffer)?;
-        let total = varint_size.checked_add(len as usize).ok_or(SerializationError::Overflow)?;
-        if buffer.len() < total {
-            return Err(SerializationError::InvalidData("Not enough bytes for Vec<u8>".into()));
+        if buffer.len() < N {
+            return Err(SerializationError::InvalidData(format!("Not enough bytes for [u8; {}]", N)));
+        }
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(&buffer[..N]);
+        Ok((bytes, N))
+    }
+}
+
+/// --- Field Sequencing Helpers ---
+/// Writes struct fields one after another, tracking the offset into the output
+/// buffer so `Encode` impls don't repeat the bookkeeping by hand:
+/// `FieldWriter::new(buf, e).field(&self.id)?.field(&self.amount)?.finish()`.
+pub struct FieldWriter<'a> {
+    buffer: &'a mut [u8],
+    offset: usize,
+    endianness: Endianness,
+}
+
+impl<'a> FieldWriter<'a> {
+    #[inline(always)]
+    pub fn new(buffer: &'a mut [u8], endianness: Endianness) -> Self {
+        FieldWriter { buffer, offset: 0, endianness }
+    }
+
+    /// Encodes `value` at the current offset and advances past it.
+    #[inline(always)]
+    pub fn field<T: Encode + ?Sized>(mut self, value: &T) -> SerializationResult<Self> {
+        self.offset += value.encode_to(&mut self.buffer[self.offset..], self.endianness)?;
+        Ok(self)
+    }
+
+    /// Returns the total number of bytes written.
+    #[inline(always)]
+    pub fn finish(self) -> usize {
+        self.offset
+    }
+}
+
+/// Reads struct fields in sequence; the counterpart of `FieldWriter`.
+pub struct FieldReader<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+    endianness: Endianness,
+}
+
+impl<'a> FieldReader<'a> {
+    #[inline(always)]
+    pub fn new(buffer: &'a [u8], endianness: Endianness) -> Self {
+        FieldReader { buffer, offset: 0, endianness }
+    }
+
+    /// Decodes the next field and advances past it.
+    #[inline(always)]
+    pub fn field<T: Decode>(&mut self) -> SerializationResult<T> {
+        let (value, consumed) = T::decode_from(self.remaining()?, self.endianness)?;
+        self.offset += consumed;
+        Ok(value)
+    }
+
+    /// Reads a varint-length-prefixed byte string as a slice of the input,
+    /// without copying.
+    #[inline(always)]
+    pub fn borrowed_bytes(&mut self) -> SerializationResult<&'a [u8]> {
+        let (len, varint_size) = decode_varint_u64(self.remaining()?)?;
+        let start = self.offset + varint_size;
+        let end = start.checked_add(len as usize).ok_or(SerializationError::Overflow)?;
+        if end > self.buffer.len() {
+            return Err(SerializationError::InvalidData("Not enough bytes for byte string".into()));
         }
-        let bytes = buffer[varint_size..total].to_vec();
-        Ok((bytes, total))
+        self.offset = end;
+        Ok(&self.buffer[start..end])
+    }
+
+    /// Reads a length-prefixed UTF-8 string as a slice of the input.
+    #[inline(always)]
+    pub fn borrowed_str(&mut self) -> SerializationResult<&'a str> {
+        std::str::from_utf8(self.borrowed_bytes()?)
+            .map_err(|e| SerializationError::InvalidData(format!("UTF-8 error: {:?}", e)))
+    }
+
+    /// Returns the unread part of the buffer.
+    #[inline(always)]
+    fn remaining(&self) -> SerializationResult<&'a [u8]> {
+        self.buffer
+            .get(self.offset..)
+            .ok_or_else(|| SerializationError::InvalidData("Field offset past end of buffer".into()))
+    }
+
+    /// Returns the total number of bytes consumed.
+    #[inline(always)]
+    pub fn finish(self) -> usize {
+        self.offset
     }
 }
 
@@ -407,9 +747,11 @@ pub struct Transaction {
     pub amount: u64,
     pub fee: f64,
     pub version: u8,
+    /// Per-sender sequence number; a sender's transactions apply in ascending nonce order.
+    pub nonce: u64,
     pub sender: String,
     pub recipient: String,
-    pub signature: Vec<u8>,
+    pub signature: SignatureBytes,
 }
 
 impl Encode for Transaction {
@@ -418,129 +760,533 @@ impl Encode for Transaction {
         self.id.encoded_size() +
         self.amount.encoded_size() +
         self.fee.encoded_size() +
-        1 + // version
+        self.version.encoded_size() +
+        self.nonce.encoded_size() +
         self.sender.encoded_size() +
         self.recipient.encoded_size() +
         self.signature.encoded_size()
     }
     #[inline(always)]
     fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
-        let mut offset = 0;
-        offset += self.id.encode_to(&mut buffer[offset..], endianness)?;
-        offset += self.amount.encode_to(&mut buffer[offset..], endianness)?;
-        offset += self.fee.encode_to(&mut buffer[offset..], endianness)?;
-        if buffer.len() < offset + 1 { return Err(SerializationError::BufferTooSmall); }
-        buffer[offset] = self.version;
-        offset += 1;
-        offset += self.sender.encode_to(&mut buffer[offset..], endianness)?;
-        offset += self.recipient.encode_to(&mut buffer[offset..], endianness)?;
-        offset += self.signature.encode_to(&mut buffer[offset..], endianness)?;
-        Ok(offset)
+        Ok(FieldWriter::new(buffer, endianness)
+            .field(&self.id)?
+            .field(&self.amount)?
+            .field(&self.fee)?
+            .field(&self.version)?
+            .field(&self.nonce)?
+            .field(&self.sender)?
+            .field(&self.recipient)?
+            .field(&self.signature)?
+            .finish())
     }
 }
 
 impl Decode for Transaction {
     #[inline(always)]
     fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
-        let mut offset = 0;
-        let (id, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        let (amount, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        let (fee, consumed) = f64::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        if buffer.len() < offset + 1 { return Err(SerializationError::BufferTooSmall); }
-        let version = buffer[offset];
-        offset += 1;
-        let (sender, consumed) = String::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        let (recipient, consumed) = String::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        let (signature, consumed) = Vec::<u8>::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        Ok((Transaction { id, amount, fee, version, sender, recipient, signature }, offset))
+        let mut reader = FieldReader::new(buffer, endianness);
+        let tx = Transaction {
+            id: reader.field()?,
+            amount: reader.field()?,
+            fee: reader.field()?,
+            version: reader.field()?,
+            nonce: reader.field()?,
+            sender: reader.field()?,
+            recipient: reader.field()?,
+            signature: reader.field()?,
+        };
+        tx.validate()?;
+        Ok((tx, reader.finish()))
     }
 }
 
-/// --- Block Struct ---
-#[derive(Debug, PartialEq)]
-pub struct Block {
-    pub version: u8,
-    pub block_number: u64,
-    pub previous_hash: Vec<u8>,
-    pub transactions: Vec<Transaction>,
-}
-
-impl Encode for Block {
-    #[inline(always)]
-    fn encoded_size(&self) -> usize {
-        1 + self.block_number.encoded_size() +
-        self.previous_hash.encoded_size() +
-        {
-            let mut size = 0;
-            let count = self.transactions.len();
-            let mut temp = count as u64;
-            while temp >= 0x80 { size += 1; temp >>= 7; }
-            size + 1 + self.transactions.iter().map(|tx| tx.encoded_size()).sum::<usize>()
+impl Transaction {
+    /// Returns the encoded length of this transaction in `format`.
+    pub fn encoded_size_in(&self, format: WireFormat) -> usize {
+        match format {
+            WireFormat::CompactVarint => self.encoded_size(),
+            WireFormat::FixedBigEndian => {
+                8 + 8 + 8 + 1 + 8 + 4 * 3 + self.sender.len() + self.recipient.len() + self.signature.len()
+            }
         }
     }
-    #[inline(always)]
-    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
-        let mut offset = 0;
-        if buffer.is_empty() { return Err(SerializationError::BufferTooSmall); }
-        buffer[0] = self.version;
-        offset += 1;
-        offset += self.block_number.encode_to(&mut buffer[offset..], endianness)?;
-        offset += self.previous_hash.encode_to(&mut buffer[offset..], endianness)?;
-        let tx_count = self.transactions.len() as u64;
-        offset += encode_varint_u64(tx_count, &mut buffer[offset..])?;
-        for tx in &self.transactions {
-            offset += tx.encode_to(&mut buffer[offset..], endianness)?;
+
+    /// Returns the fee paid per encoded byte, `fee / encoded_size()`: what the
+    /// transaction pays for the block space it takes up.
+    pub fn fee_per_byte(&self) -> f64 {
+        self.fee / self.encoded_size() as f64
+    }
+
+    /// Encodes the transaction in `format`. `CompactVarint` is identical to
+    /// `encode_to`; `FixedBigEndian` writes, in field order, `id`, `amount`,
+    /// `fee` (f64) and `nonce` as 8 bytes each, `version` as 1 byte, and
+    /// `sender`, `recipient` and `signature` as a u32 length then the bytes.
+    pub fn encode_to_format(&self, buffer: &mut [u8], endianness: Endianness, format: WireFormat) -> SerializationResult<usize> {
+        match format {
+            WireFormat::CompactVarint => self.encode_to(buffer, endianness),
+            WireFormat::FixedBigEndian => Ok(FieldWriter::new(buffer, Endianness::Big)
+                .field(&Fixed(self.id))?
+                .field(&Fixed(self.amount))?
+                .field(&self.fee)?
+                .field(&self.version)?
+                .field(&Fixed(self.nonce))?
+                .field(&Fixed(self.sender.as_bytes()))?
+                .field(&Fixed(self.recipient.as_bytes()))?
+                .field(&Fixed(self.signature.as_slice()))?
+                .finish()),
         }
-        Ok(offset)
     }
-}
 
-impl Decode for Block {
-    #[inline(always)]
-    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
-        if buffer.is_empty() {
-            return Err(SerializationError::InvalidData("Empty buffer for Block".into()));
+    /// Decodes a transaction written by `encode_to_format` in the same `format`.
+    pub fn decode_from_format(buffer: &[u8], endianness: Endianness, format: WireFormat) -> SerializationResult<(Self, usize)> {
+        match format {
+            WireFormat::CompactVarint => Self::decode_from(buffer, endianness),
+            WireFormat::FixedBigEndian => {
+                let mut reader = FieldReader::new(buffer, Endianness::Big);
+                let tx = Transaction {
+                    id: reader.field::<Fixed<u64>>()?.0,
+                    amount: reader.field::<Fixed<u64>>()?.0,
+                    fee: reader.field()?,
+                    version: reader.field()?,
+                    nonce: reader.field::<Fixed<u64>>()?.0,
+                    sender: reader.field::<Fixed<String>>()?.0,
+                    recipient: reader.field::<Fixed<String>>()?.0,
+                    signature: reader.field::<Fixed<Vec<u8>>>()?.0.into(),
+                };
+                tx.validate()?;
+                Ok((tx, reader.finish()))
+            }
         }
-        let version = buffer[0];
-        let mut offset = 1;
-        let (block_number, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        let (previous_hash, consumed) = Vec::<u8>::decode_from(&buffer[offset..], endianness)?;
-        offset += consumed;
-        let (tx_count, consumed) = decode_varint_u64(&buffer[offset..])?;
-        offset += consumed;
-        let mut transactions = Vec::with_capacity(tx_count as usize);
-        for _ in 0..tx_count {
-            let (tx, consumed) = Transaction::decode_from(&buffer[offset..], endianness)?;
-            offset += consumed;
-            transactions.push(tx);
+    }
+
+    /// Checks field invariants that the wire format cannot express.
+    /// Rejects a NaN or infinite `fee`, which would break fee comparisons and ordering.
+    pub fn validate(&self) -> SerializationResult<()> {
+        if !self.fee.is_finite() {
+            return Err(SerializationError::InvalidData(format!("Non-finite transaction fee: {}", self.fee)));
         }
-        Ok((Block { version, block_number, previous_hash, transactions }, offset))
+        Ok(())
     }
-}
 
-/// --- Serializer with Length Prefix & Checksum ---
-/// Format: [length: u32][payload][Blake3 checksum (32 bytes)]
-pub struct Serializer;
+    /// Returns true if both describe the same transfer: equal sender,
+    /// recipient, amount, fee, version, and nonce. `id` and `signature` are
+    /// ignored, since different nodes may assign different ids and relay the
+    /// transaction before or after signing. Fees are compared bit for bit.
+    pub fn canonical_eq(&self, other: &Transaction) -> bool {
+        self.sender == other.sender
+            && self.recipient == other.recipient
+            && self.amount == other.amount
+            && self.fee.to_bits() == other.fee.to_bits()
+            && self.version == other.version
+            && self.nonce == other.nonce
+    }
 
-impl Serializer {
-    #[inline(always)]
-    fn compute_hash(payload: &[u8]) -> blake3::Hash {
-        blake3::hash(payload)
+    /// Content-addressed hash: Blake3 over the little-endian encoding of the
+    /// fields `canonical_eq` compares, so canonically equal transactions share
+    /// a hash whatever their `id` and whether or not they are signed.
+    pub fn content_hash(&self) -> [u8; 32] {
+        let size = self.encoded_size() - self.id.encoded_size() - self.signature.encoded_size();
+        let mut buffer = vec![0u8; size];
+        let written = FieldWriter::new(&mut buffer, Endianness::Little)
+            .field(&self.amount)
+            .and_then(|w| w.field(&self.fee))
+            .and_then(|w| w.field(&self.version))
+            .and_then(|w| w.field(&self.nonce))
+            .and_then(|w| w.field(&self.sender))
+            .and_then(|w| w.field(&self.recipient))
+            .map(FieldWriter::finish)
+            .expect("buffer sized from encoded_size");
+        debug_assert_eq!(written, size);
+        *blake3::hash(&buffer).as_bytes()
     }
+}
 
-    #[inline(always)]
-    pub fn serialize<T: Encode>(data: &T, endianness: Endianness) -> SerializationResult<Vec<u8>> {
-        let payload_size = data.encoded_size();
+/// Bit of a frame's last length-prefix byte that is set when the frame was
+/// written big-endian; see `Serializer::write_length_prefix`.
+const ENDIANNESS_MARKER: u8 = 0x80;
+
+/// Largest length a frame's prefix can record beside the endianness marker.
+pub const MAX_FRAME_LEN: u32 = 0x7FFF_FFFF;
+
+/// Largest combined full encoding of the transactions in a delta batch. Each
+/// delta can expand to a whole transaction, so this bounds what a small
+/// frame makes `deserialize_delta_batch` allocate.
+pub const MAX_DELTA_BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// Bytes reserved for each of sender and recipient in the ultra-fixed layout.
+pub const ULTRA_ADDRESS_LEN: usize = 16;
+
+/// Bytes reserved for the signature in the ultra-fixed layout.
+pub const ULTRA_SIGNATURE_LEN: usize = 64;
+
+/// Encodes the wrapped transaction in the 129-byte ultra-fixed layout of
+/// `Serializer::serialize_ultra_fixed`, so it can be used wherever an
+/// `Encode + ConstEncodedSize` value is expected.
+#[derive(Debug, Clone, Copy)]
+pub struct UltraFixed<'a>(pub &'a Transaction);
+
+impl Encode for UltraFixed<'_> {
+    #[inline(always)]
+    fn encoded_size(&self) -> usize { Self::SIZE }
+    #[inline(always)]
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let out: &mut [u8; Serializer::ULTRA_TX_SIZE] = buffer
+            .get_mut(..Self::SIZE)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(SerializationError::BufferTooSmall)?;
+        Serializer::serialize_ultra_fixed_into(self.0, endianness, out)?;
+        Ok(Self::SIZE)
+    }
+}
+
+impl ConstEncodedSize for UltraFixed<'_> {
+    const SIZE: usize = Serializer::ULTRA_TX_SIZE;
+}
+
+/// Encodes `value` onto the end of `out`.
+fn append_field<T: Encode + ?Sized>(out: &mut Vec<u8>, value: &T, endianness: Endianness) -> SerializationResult<()> {
+    let start = out.len();
+    out.resize(start + value.encoded_size(), 0);
+    let written = value.encode_to(&mut out[start..], endianness)?;
+    out.truncate(start + written);
+    Ok(())
+}
+
+/// Copies as much of `src` as fits into `slot` and zeroes the rest.
+#[inline(always)]
+fn write_padded(src: &[u8], slot: &mut [u8]) {
+    let len = src.len().min(slot.len());
+    slot[..len].copy_from_slice(&src[..len]);
+    slot[len..].fill(0);
+}
+
+/// Chained construction of a `Transaction` with validation in `build`.
+/// `version` defaults to 1 and `signature` to empty.
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    tx: Transaction,
+    fixed_mode: bool,
+}
+
+impl Default for TransactionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionBuilder {
+    pub fn new() -> Self {
+        TransactionBuilder {
+            tx: Transaction {
+                id: 0,
+                amount: 0,
+                fee: 0.0,
+                version: 1,
+                nonce: 0,
+                sender: String::new(),
+                recipient: String::new(),
+                signature: SignatureBytes::new(),
+            },
+            fixed_mode: false,
+        }
+    }
+
+    pub fn id(mut self, id: u64) -> Self {
+        self.tx.id = id;
+        self
+    }
+
+    pub fn amount(mut self, amount: u64) -> Self {
+        self.tx.amount = amount;
+        self
+    }
+
+    pub fn fee(mut self, fee: f64) -> Self {
+        self.tx.fee = fee;
+        self
+    }
+
+    pub fn version(mut self, version: u8) -> Self {
+        self.tx.version = version;
+        self
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.tx.nonce = nonce;
+        self
+    }
+
+    pub fn sender(mut self, sender: impl Into<String>) -> Self {
+        self.tx.sender = sender.into();
+        self
+    }
+
+    pub fn recipient(mut self, recipient: impl Into<String>) -> Self {
+        self.tx.recipient = recipient.into();
+        self
+    }
+
+    pub fn signature(mut self, signature: impl Into<SignatureBytes>) -> Self {
+        self.tx.signature = signature.into();
+        self
+    }
+
+    /// Requires the transaction to fit the ultra-fixed layout without truncation.
+    pub fn fixed_mode(mut self, fixed_mode: bool) -> Self {
+        self.fixed_mode = fixed_mode;
+        self
+    }
+
+    /// Validates and returns the transaction: the fee must be finite and
+    /// non-negative, sender and recipient non-empty, and in fixed mode the
+    /// addresses and signature must fit their ultra-fixed slots.
+    pub fn build(self) -> SerializationResult<Transaction> {
+        let tx = self.tx;
+        tx.validate()?;
+        if tx.fee < 0.0 {
+            return Err(SerializationError::InvalidData(format!("Negative transaction fee: {}", tx.fee)));
+        }
+        if tx.sender.is_empty() || tx.recipient.is_empty() {
+            return Err(SerializationError::InvalidData("Sender and recipient must be non-empty".into()));
+        }
+        if self.fixed_mode {
+            if tx.sender.len() > ULTRA_ADDRESS_LEN || tx.recipient.len() > ULTRA_ADDRESS_LEN {
+                return Err(SerializationError::InvalidData(format!(
+                    "Addresses must be at most {} bytes in fixed mode",
+                    ULTRA_ADDRESS_LEN
+                )));
+            }
+            if tx.signature.len() > ULTRA_SIGNATURE_LEN {
+                return Err(SerializationError::InvalidData(format!(
+                    "Signature must be at most {} bytes in fixed mode",
+                    ULTRA_SIGNATURE_LEN
+                )));
+            }
+        }
+        Ok(tx)
+    }
+}
+
+/// A `Transaction` whose strings and signature may borrow from the buffer it
+/// was decoded from. `decode_cow_from` always borrows; owned values arise when
+/// callers build or modify one, and `into_owned` converts to a `Transaction`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CowTransaction<'a> {
+    pub id: u64,
+    pub amount: u64,
+    pub fee: f64,
+    pub version: u8,
+    pub nonce: u64,
+    pub sender: Cow<'a, str>,
+    pub recipient: Cow<'a, str>,
+    pub signature: Cow<'a, [u8]>,
+}
+
+impl<'a> CowTransaction<'a> {
+    /// Decodes the `Transaction` wire format without copying the strings or
+    /// signature. Applies the same validation as `Transaction::decode_from`,
+    /// including UTF-8 checks. Returns the value and the bytes consumed.
+    pub fn decode_cow_from(buffer: &'a [u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        let mut reader = FieldReader::new(buffer, endianness);
+        let tx = CowTransaction {
+            id: reader.field()?,
+            amount: reader.field()?,
+            fee: reader.field()?,
+            version: reader.field()?,
+            nonce: reader.field()?,
+            sender: Cow::Borrowed(reader.borrowed_str()?),
+            recipient: Cow::Borrowed(reader.borrowed_str()?),
+            signature: Cow::Borrowed(reader.borrowed_bytes()?),
+        };
+        if !tx.fee.is_finite() {
+            return Err(SerializationError::InvalidData(format!("Non-finite transaction fee: {}", tx.fee)));
+        }
+        Ok((tx, reader.finish()))
+    }
+
+    /// Converts into an owned `Transaction`, copying any borrowed data.
+    pub fn into_owned(self) -> Transaction {
+        Transaction {
+            id: self.id,
+            amount: self.amount,
+            fee: self.fee,
+            version: self.version,
+            nonce: self.nonce,
+            sender: self.sender.into_owned(),
+            recipient: self.recipient.into_owned(),
+            signature: self.signature.as_ref().into(),
+        }
+    }
+}
+
+/// --- Block Struct ---
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    pub version: u8,
+    pub block_number: u64,
+    pub previous_hash: Vec<u8>,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Encode for Block {
+    #[inline(always)]
+    fn encoded_size(&self) -> usize {
+        1 + self.block_number.encoded_size() +
+        self.previous_hash.encoded_size() +
+        {
+            let mut size = 0;
+            let count = self.transactions.len();
+            let mut temp = count as u64;
+            while temp >= 0x80 { size += 1; temp >>= 7; }
+            size + 1 + self.transactions.iter().map(|tx| tx.encoded_size()).sum::<usize>()
+        }
+    }
+    #[inline(always)]
+    fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        let mut offset = 0;
+        if buffer.is_empty() { return Err(SerializationError::BufferTooSmall); }
+        buffer[0] = self.version;
+        offset += 1;
+        offset += self.block_number.encode_to(&mut buffer[offset..], endianness)?;
+        offset += self.previous_hash.encode_to(&mut buffer[offset..], endianness)?;
+        let tx_count = self.transactions.len() as u64;
+        offset += encode_varint_u64(tx_count, &mut buffer[offset..])?;
+        for tx in &self.transactions {
+            offset += tx.encode_to(&mut buffer[offset..], endianness)?;
+        }
+        Ok(offset)
+    }
+}
+
+impl Decode for Block {
+    #[inline(always)]
+    fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+        if buffer.is_empty() {
+            return Err(SerializationError::InvalidData("Empty buffer for Block".into()));
+        }
+        let version = buffer[0];
+        let mut offset = 1;
+        let (block_number, consumed) = u64::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (previous_hash, consumed) = Vec::<u8>::decode_from(&buffer[offset..], endianness)?;
+        offset += consumed;
+        let (tx_count, consumed) = decode_varint_u64(&buffer[offset..])?;
+        offset += consumed;
+        // Every transaction takes at least one byte, so a count beyond the buffer is corrupt.
+        if tx_count > buffer.len() as u64 {
+            return Err(SerializationError::InvalidData("Transaction count exceeds buffer".into()));
+        }
+        let mut transactions = Vec::with_capacity(tx_count as usize);
+        for _ in 0..tx_count {
+            let (tx, consumed) = Transaction::decode_from(&buffer[offset..], endianness)?;
+            offset += consumed;
+            transactions.push(tx);
+        }
+        Ok((Block { version, block_number, previous_hash, transactions }, offset))
+    }
+}
+
+/// --- Pluggable Frame Checksums ---
+/// Longest checksum any `Checksum` implementation may produce.
+pub const MAX_CHECKSUM_LEN: usize = 32;
+
+/// Integrity check appended to every frame by `Serializer::serialize_with`.
+pub trait Checksum {
+    /// Number of checksum bytes that follow the payload; at most `MAX_CHECKSUM_LEN`.
+    const LEN: usize;
+
+    /// Writes the checksum of `payload` into `out`, which is exactly `LEN` bytes.
+    fn compute(payload: &[u8], out: &mut [u8]);
+}
+
+/// 32-byte Blake3 digest; the default, suited to data at rest.
+pub struct Blake3Checksum;
+
+impl Checksum for Blake3Checksum {
+    const LEN: usize = 32;
+
+    #[inline(always)]
+    fn compute(payload: &[u8], out: &mut [u8]) {
+        out.copy_from_slice(blake3::hash(payload).as_bytes());
+    }
+}
+
+/// 4-byte CRC-32 (IEEE), stored little-endian. Catches accidental corruption
+/// on links that are already integrity-protected.
+pub struct Crc32Checksum;
+
+impl Checksum for Crc32Checksum {
+    const LEN: usize = 4;
+
+    #[inline(always)]
+    fn compute(payload: &[u8], out: &mut [u8]) {
+        out.copy_from_slice(&crc32(payload).to_le_bytes());
+    }
+}
+
+/// No checksum at all; frames carry only the length prefix and payload.
+pub struct NoChecksum;
+
+impl Checksum for NoChecksum {
+    const LEN: usize = 0;
+
+    #[inline(always)]
+    fn compute(_payload: &[u8], _out: &mut [u8]) {}
+}
+
+/// Lookup table for the reflected IEEE CRC-32 polynomial.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+/// Outcome of the checksum comparison in `Serializer::deserialize_checked`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// The stored checksum matches the payload.
+    Valid,
+    /// The stored checksum does not match; the payload may be corrupt.
+    Invalid,
+}
+
+/// --- Serializer with Length Prefix & Checksum ---
+/// Format: [length: u32][payload][checksum]. `serialize`/`deserialize` use a
+/// 32-byte Blake3 checksum; the `_with` variants take any `Checksum`.
+pub struct Serializer;
+
+impl Serializer {
+    #[inline(always)]
+    fn compute_hash(payload: &[u8]) -> blake3::Hash {
+        blake3::hash(payload)
+    }
+
+    #[inline(always)]
+    pub fn serialize<T: Encode>(data: &T, endianness: Endianness) -> SerializationResult<Vec<u8>> {
+        Self::serialize_with::<Blake3Checksum, T>(data, endianness)
+    }
+
+    /// Serializes `data` like `serialize`, but closes the frame with checksum `C`.
+    #[inline(always)]
+    pub fn serialize_with<C: Checksum, T: Encode>(data: &T, endianness: Endianness) -> SerializationResult<Vec<u8>> {
+        let payload_size = data.encoded_size();
         let total_size = 4_usize
             .checked_add(payload_size)
-            .and_then(|v| v.checked_add(32))
+            .and_then(|v| v.checked_add(C::LEN))
             .ok_or(SerializationError::Overflow)?;
         let mut buffer = vec![0u8; total_size];
         let offset = 4;
@@ -548,51 +1294,231 @@ impl Serializer {
         if written != payload_size {
             return Err(SerializationError::InvalidData("Encoded size mismatch".into()));
         }
-        let payload = &buffer[4..4+payload_size];
-        let hash = Self::compute_hash(payload);
-        buffer[4+payload_size..].copy_from_slice(hash.as_bytes());
-        match endianness {
-            Endianness::Little => {
-                (&mut buffer[..4]).write_u32::<LittleEndian>((payload_size + 32) as u32)?;
-            },
-            Endianness::Big => {
-                (&mut buffer[..4]).write_u32::<BigEndian>((payload_size + 32) as u32)?;
-            },
-        }
+        Self::seal::<C>(&mut buffer, payload_size, endianness)?;
         Ok(buffer)
     }
 
+    /// Fills in the length prefix and checksum around a payload already written
+    /// at `buffer[4..4 + payload_size]`.
     #[inline(always)]
-    pub fn deserialize<T: Decode>(buffer: &[u8], endianness: Endianness) -> SerializationResult<T> {
+    fn seal<C: Checksum>(buffer: &mut [u8], payload_size: usize, endianness: Endianness) -> SerializationResult<()> {
+        let (payload, checksum) = buffer[4..].split_at_mut(payload_size);
+        C::compute(payload, checksum);
+        Self::write_length_prefix(payload_size + C::LEN, endianness, &mut buffer[..4])
+    }
+
+    /// Serializes `block` like `serialize`, but encodes its transactions into
+    /// per-transaction buffers across Rayon threads before concatenating them
+    /// behind the block header. The output is byte-identical to
+    /// `Serializer::serialize(block, endianness)`.
+    pub fn serialize_block_parallel(block: &Block, endianness: Endianness) -> SerializationResult<Vec<u8>> {
+        let encoded: Vec<Vec<u8>> = block
+            .transactions
+            .par_iter()
+            .map(|tx| {
+                let mut buf = vec![0u8; tx.encoded_size()];
+                let written = tx.encode_to(&mut buf, endianness)?;
+                buf.truncate(written);
+                Ok(buf)
+            })
+            .collect::<SerializationResult<_>>()?;
+        let tx_count = block.transactions.len() as u64;
+        let mut count_prefix = [0u8; MAX_VARINT_BYTES];
+        let count_len = encode_varint_u64(tx_count, &mut count_prefix)?;
+        let header_size = 1 + block.block_number.encoded_size() + block.previous_hash.encoded_size() + count_len;
+        let payload_size = encoded
+            .iter()
+            .try_fold(header_size, |size, tx| size.checked_add(tx.len()))
+            .ok_or(SerializationError::Overflow)?;
+        let total_size = payload_size.checked_add(4 + 32).ok_or(SerializationError::Overflow)?;
+        let mut buffer = vec![0u8; total_size];
+        let mut offset = 4;
+        buffer[offset] = block.version;
+        offset += 1;
+        offset += block.block_number.encode_to(&mut buffer[offset..], endianness)?;
+        offset += block.previous_hash.encode_to(&mut buffer[offset..], endianness)?;
+        buffer[offset..offset + count_len].copy_from_slice(&count_prefix[..count_len]);
+        offset += count_len;
+        for tx in &encoded {
+            buffer[offset..offset + tx.len()].copy_from_slice(tx);
+            offset += tx.len();
+        }
+        Self::seal::<Blake3Checksum>(&mut buffer, payload_size, endianness)?;
+        Ok(buffer)
+    }
+
+    /// Writes a frame's 4-byte length prefix, marking it with `endianness`.
+    ///
+    /// The top bit of the prefix's last byte is the marker: clear for
+    /// `Little`, set for `Big`. In a little-endian prefix that is the top bit
+    /// of the length, so little-endian frames read as before; a big-endian
+    /// prefix carries the length in its other 31 bits.
+    fn write_length_prefix(len: usize, endianness: Endianness, out: &mut [u8]) -> SerializationResult<()> {
+        let len = u32::try_from(len)
+            .ok()
+            .filter(|&len| len <= MAX_FRAME_LEN)
+            .ok_or(SerializationError::Overflow)?;
+        let prefix = match endianness {
+            Endianness::Little => len,
+            Endianness::Big => ((len >> 7) << 8) | u32::from(ENDIANNESS_MARKER) | (len & 0x7F),
+        };
+        endianness.write_u32(prefix, out)?;
+        Ok(())
+    }
+
+    /// Reads the 4-byte length prefix at the start of a frame, failing with
+    /// `EndiannessMismatch` if its marker contradicts `endianness`.
+    fn length_prefix(buffer: &[u8], endianness: Endianness) -> SerializationResult<usize> {
         if buffer.len() < 4 {
             return Err(SerializationError::InvalidData("Buffer too small for length prefix".into()));
         }
+        let found = if buffer[3] & ENDIANNESS_MARKER == 0 { Endianness::Little } else { Endianness::Big };
+        if found != endianness {
+            return Err(SerializationError::EndiannessMismatch { expected: endianness, found });
+        }
         let mut cursor = Cursor::new(&buffer[..4]);
-        let len_prefix = match endianness {
+        Ok(match endianness {
             Endianness::Little => cursor.read_u32::<LittleEndian>()?,
-            Endianness::Big => cursor.read_u32::<BigEndian>()?,
-        } as usize;
+            Endianness::Big => {
+                let prefix = cursor.read_u32::<BigEndian>()?;
+                ((prefix >> 8) << 7) | (prefix & 0x7F)
+            }
+        } as usize)
+    }
+
+    #[inline(always)]
+    pub fn deserialize<T: Decode>(buffer: &[u8], endianness: Endianness) -> SerializationResult<T> {
+        Self::deserialize_with::<Blake3Checksum, T>(buffer, endianness)
+    }
+
+    /// Decodes a frame like `deserialize`, but accepts overlong varints (see
+    /// `VarintMode::Lenient`), for reading legacy data. The value is the one
+    /// the minimal encoding would give; re-encoding it yields canonical bytes.
+    pub fn deserialize_lenient<T: Decode>(buffer: &[u8], endianness: Endianness) -> SerializationResult<T> {
+        /// Puts the previous mode back even if decoding panics.
+        struct Restore(VarintMode);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                VARINT_MODE.set(self.0);
+            }
+        }
+        let _restore = Restore(VARINT_MODE.replace(VarintMode::Lenient));
+        Self::deserialize(buffer, endianness)
+    }
+
+    /// Decodes a frame produced by `serialize_with::<C, _>`, verifying checksum `C`.
+    #[inline(always)]
+    pub fn deserialize_with<C: Checksum, T: Decode>(buffer: &[u8], endianness: Endianness) -> SerializationResult<T> {
+        let payload = Self::verified_payload::<C>(buffer, endianness)?;
+        Self::decode_payload(payload, endianness)
+    }
+
+    /// Decodes a frame like `deserialize`, but reports a checksum mismatch as
+    /// `ChecksumStatus::Invalid` alongside the value instead of failing, so the
+    /// caller can decide how far to trust it. Length and decoding errors still fail.
+    #[inline(always)]
+    pub fn deserialize_checked<T: Decode>(buffer: &[u8], endianness: Endianness) -> SerializationResult<(T, ChecksumStatus)> {
+        Self::deserialize_checked_with::<Blake3Checksum, T>(buffer, endianness)
+    }
+
+    /// Like `deserialize_checked`, for a frame closed with checksum `C`.
+    pub fn deserialize_checked_with<C: Checksum, T: Decode>(
+        buffer: &[u8],
+        endianness: Endianness,
+    ) -> SerializationResult<(T, ChecksumStatus)> {
+        let (payload, stored_checksum) = Self::split_frame::<C>(buffer, endianness)?;
+        let value = Self::decode_payload(payload, endianness)?;
+        let mut computed = [0u8; MAX_CHECKSUM_LEN];
+        let computed = &mut computed[..C::LEN];
+        C::compute(payload, computed);
+        let status = if stored_checksum == computed { ChecksumStatus::Valid } else { ChecksumStatus::Invalid };
+        Ok((value, status))
+    }
+
+    /// Decodes a whole payload, failing if bytes are left over.
+    fn decode_payload<T: Decode>(payload: &[u8], endianness: Endianness) -> SerializationResult<T> {
+        let (value, consumed) = T::decode_from(payload, endianness)?;
+        if consumed != payload.len() {
+            return Err(SerializationError::InvalidData("Extra bytes found in payload after decoding".into()));
+        }
+        Ok(value)
+    }
+
+    /// Checks the length prefix of a whole frame closed with checksum `C` and
+    /// splits it into the payload and the stored checksum.
+    fn split_frame<C: Checksum>(buffer: &[u8], endianness: Endianness) -> SerializationResult<(&[u8], &[u8])> {
+        const { assert!(C::LEN <= MAX_CHECKSUM_LEN) };
+        let len_prefix = Self::length_prefix(buffer, endianness)?;
         if buffer.len() != 4 + len_prefix {
             return Err(SerializationError::InvalidData("Length prefix does not match buffer size".into()));
         }
-        if len_prefix < 32 {
+        if len_prefix < C::LEN {
             return Err(SerializationError::InvalidData("Payload length too small to contain checksum".into()));
         }
-        let payload_end = 4 + len_prefix - 32;
-        let payload = &buffer[4..payload_end];
-        let stored_checksum = &buffer[payload_end..4+len_prefix];
-        let computed_hash = Self::compute_hash(payload);
-        if stored_checksum != computed_hash.as_bytes() {
+        Ok(buffer[4..].split_at(len_prefix - C::LEN))
+    }
+
+    /// Checks the length prefix and checksum `C` of a whole frame and returns
+    /// the payload between them.
+    fn verified_payload<C: Checksum>(buffer: &[u8], endianness: Endianness) -> SerializationResult<&[u8]> {
+        let (payload, stored_checksum) = Self::split_frame::<C>(buffer, endianness)?;
+        let mut computed = [0u8; MAX_CHECKSUM_LEN];
+        let computed = &mut computed[..C::LEN];
+        C::compute(payload, computed);
+        if stored_checksum != computed {
             return Err(SerializationError::ChecksumMismatch {
                 stored: stored_checksum.to_vec(),
-                computed: computed_hash.as_bytes().to_vec(),
+                computed: computed.to_vec(),
             });
         }
-        let (value, consumed) = T::decode_from(payload, endianness)?;
-        if consumed != payload.len() {
-            return Err(SerializationError::InvalidData("Extra bytes found in payload after decoding".into()));
+        Ok(payload)
+    }
+
+    /// Decodes the first frame in `buffer`, which may be followed by more data,
+    /// and returns the value together with the bytes after that frame.
+    ///
+    /// Lets callers walk a stream of concatenated frames by feeding the
+    /// returned tail back in until it is empty.
+    pub fn deserialize_prefix<T: Decode>(buffer: &[u8], endianness: Endianness) -> SerializationResult<(T, &[u8])> {
+        let frame_len = 4 + Self::length_prefix(buffer, endianness)?;
+        if buffer.len() < frame_len {
+            return Err(SerializationError::InvalidData("Buffer shorter than length prefix".into()));
         }
-        Ok(value)
+        let (frame, rest) = buffer.split_at(frame_len);
+        Ok((Self::deserialize(frame, endianness)?, rest))
+    }
+
+    /// Serializes `data` like `serialize` and returns the frame as lowercase hex.
+    pub fn serialize_hex<T: Encode>(data: &T, endianness: Endianness) -> SerializationResult<String> {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let bytes = Self::serialize(data, endianness)?;
+        let mut hex = String::with_capacity(bytes.len() * 2);
+        for byte in bytes {
+            hex.push(DIGITS[(byte >> 4) as usize] as char);
+            hex.push(DIGITS[(byte & 0x0f) as usize] as char);
+        }
+        Ok(hex)
+    }
+
+    /// Decodes a hex frame produced by `serialize_hex` and deserializes it like
+    /// `deserialize`. Either case of hex digit is accepted; odd-length input or
+    /// non-hex characters are `InvalidData`.
+    pub fn deserialize_hex<T: Decode>(hex: &str, endianness: Endianness) -> SerializationResult<T> {
+        if !hex.len().is_multiple_of(2) {
+            return Err(SerializationError::InvalidData("Hex input has odd length".into()));
+        }
+        let nibble = |c: u8| {
+            (c as char)
+                .to_digit(16)
+                .map(|d| d as u8)
+                .ok_or_else(|| SerializationError::InvalidData(format!("Invalid hex character: {:?}", c as char)))
+        };
+        let bytes = hex
+            .as_bytes()
+            .chunks_exact(2)
+            .map(|pair| Ok((nibble(pair[0])? << 4) | nibble(pair[1])?))
+            .collect::<SerializationResult<Vec<u8>>>()?;
+        Self::deserialize(&bytes, endianness)
     }
 
     // --- Batch Serialization ---
@@ -617,23 +1543,205 @@ impl Serializer {
         Ok(output)
     }
 
+    /// Decodes a buffer written by `serialize_batch`, verifying its checksum
+    /// before decoding items back to back until the payload is used up. The
+    /// last item must end exactly at the checksum; trailing bytes that do not
+    /// form a whole item are rejected.
+    pub fn deserialize_batch<T: Decode>(buffer: &[u8], endianness: Endianness) -> SerializationResult<Vec<T>> {
+        // `serialize_batch` always writes its length prefix little-endian.
+        let payload = Self::verified_payload::<Blake3Checksum>(buffer, Endianness::Little)?;
+        let mut items = Vec::new();
+        let mut offset = 0;
+        while offset < payload.len() {
+            let (item, consumed) = T::decode_from(&payload[offset..], endianness)?;
+            if consumed == 0 {
+                return Err(SerializationError::InvalidData("Batch item decoded from zero bytes".into()));
+            }
+            items.push(item);
+            offset += consumed;
+        }
+        if offset != payload.len() {
+            return Err(SerializationError::InvalidData("Batch items do not end at the checksum".into()));
+        }
+        Ok(items)
+    }
+
+    // --- Delta Batch Serialization ---
+    /// Encodes `txs` as `[len][count][first tx][delta]…[Blake3 checksum]`, where
+    /// each delta is a one-byte field-presence bitmap (bit 0 = `id` through
+    /// bit 7 = `signature`, in declaration order) followed by only the fields
+    /// that differ from the previous transaction.
+    ///
+    /// The checksum covers the full encodings of the transactions, i.e. the
+    /// same payload `serialize_batch` hashes, so it is checked against what
+    /// the decoder reconstructs rather than against the compressed bytes.
+    ///
+    /// Fails if a transaction repeats the one before it, which would encode
+    /// as an empty delta, or if the full encodings add up to more than
+    /// `MAX_DELTA_BATCH_BYTES`; the decoder refuses both.
+    pub fn serialize_delta_batch(txs: &[Transaction], endianness: Endianness) -> SerializationResult<Vec<u8>> {
+        let mut output = vec![0u8; 4];
+        append_field(&mut output, &(txs.len() as u64), endianness)?;
+        let mut hasher = blake3::Hasher::new();
+        let mut full = Vec::new();
+        let mut total = 0usize;
+        let mut previous: Option<&Transaction> = None;
+        for tx in txs {
+            full.clear();
+            append_field(&mut full, tx, endianness)?;
+            total += full.len();
+            if total > MAX_DELTA_BATCH_BYTES {
+                return Err(SerializationError::InvalidData("Delta batch exceeds the size limit".into()));
+            }
+            hasher.update(&full);
+            let Some(prev) = previous else {
+                output.extend_from_slice(&full);
+                previous = Some(tx);
+                continue;
+            };
+            let changed = [
+                tx.id != prev.id,
+                tx.amount != prev.amount,
+                tx.fee.to_bits() != prev.fee.to_bits(),
+                tx.version != prev.version,
+                tx.nonce != prev.nonce,
+                tx.sender != prev.sender,
+                tx.recipient != prev.recipient,
+                tx.signature != prev.signature,
+            ];
+            let mask = changed.iter().enumerate().fold(0u8, |mask, (bit, &set)| mask | ((set as u8) << bit));
+            if mask == 0 {
+                return Err(SerializationError::InvalidData("Delta batch repeats a transaction".into()));
+            }
+            append_field(&mut output, &mask, endianness)?;
+            if changed[0] { append_field(&mut output, &tx.id, endianness)?; }
+            if changed[1] { append_field(&mut output, &tx.amount, endianness)?; }
+            if changed[2] { append_field(&mut output, &tx.fee, endianness)?; }
+            if changed[3] { append_field(&mut output, &tx.version, endianness)?; }
+            if changed[4] { append_field(&mut output, &tx.nonce, endianness)?; }
+            if changed[5] { append_field(&mut output, &tx.sender, endianness)?; }
+            if changed[6] { append_field(&mut output, &tx.recipient, endianness)?; }
+            if changed[7] { append_field(&mut output, &tx.signature, endianness)?; }
+            previous = Some(tx);
+        }
+        output.extend_from_slice(hasher.finalize().as_bytes());
+        Self::write_length_prefix(output.len() - 4, endianness, &mut output[..4])?;
+        Ok(output)
+    }
+
+    /// Decodes a buffer written by `serialize_delta_batch`, rebuilding each
+    /// transaction from its predecessor and verifying the checksum over the
+    /// reconstructed encodings. Empty deltas and batches that would rebuild
+    /// more than `MAX_DELTA_BATCH_BYTES` are rejected before the allocation.
+    pub fn deserialize_delta_batch(buffer: &[u8], endianness: Endianness) -> SerializationResult<Vec<Transaction>> {
+        let len_prefix = Self::length_prefix(buffer, endianness)?;
+        if buffer.len() != 4 + len_prefix {
+            return Err(SerializationError::InvalidData("Length prefix does not match buffer size".into()));
+        }
+        if len_prefix < 32 {
+            return Err(SerializationError::InvalidData("Payload length too small to contain checksum".into()));
+        }
+        let (payload, stored_checksum) = buffer[4..].split_at(len_prefix - 32);
+        let mut reader = FieldReader::new(payload, endianness);
+        let count: u64 = reader.field()?;
+        // Every delta takes at least its bitmap byte, which bounds a sane count.
+        let mut txs: Vec<Transaction> = Vec::with_capacity((count as usize).min(payload.len()));
+        let mut hasher = blake3::Hasher::new();
+        let mut full = Vec::new();
+        let mut total = 0usize;
+        for _ in 0..count {
+            let tx = match txs.last() {
+                None => reader.field()?,
+                Some(prev) => {
+                    let mask: u8 = reader.field()?;
+                    if mask == 0 {
+                        return Err(SerializationError::InvalidData("Delta batch repeats a transaction".into()));
+                    }
+                    // `full` still holds the previous encoding, so this bounds the clone.
+                    if total + full.len() > MAX_DELTA_BATCH_BYTES {
+                        return Err(SerializationError::InvalidData("Delta batch exceeds the size limit".into()));
+                    }
+                    let mut tx = prev.clone();
+                    if mask & 1 != 0 { tx.id = reader.field()?; }
+                    if mask & 1 << 1 != 0 { tx.amount = reader.field()?; }
+                    if mask & 1 << 2 != 0 { tx.fee = reader.field()?; }
+                    if mask & 1 << 3 != 0 { tx.version = reader.field()?; }
+                    if mask & 1 << 4 != 0 { tx.nonce = reader.field()?; }
+                    if mask & 1 << 5 != 0 { tx.sender = reader.field()?; }
+                    if mask & 1 << 6 != 0 { tx.recipient = reader.field()?; }
+                    if mask & 1 << 7 != 0 { tx.signature = reader.field()?; }
+                    tx.validate()?;
+                    tx
+                }
+            };
+            full.clear();
+            append_field(&mut full, &tx, endianness)?;
+            total += full.len();
+            if total > MAX_DELTA_BATCH_BYTES {
+                return Err(SerializationError::InvalidData("Delta batch exceeds the size limit".into()));
+            }
+            hasher.update(&full);
+            txs.push(tx);
+        }
+        if reader.finish() != payload.len() {
+            return Err(SerializationError::InvalidData("Extra bytes found in delta batch".into()));
+        }
+        let computed = hasher.finalize();
+        if stored_checksum != computed.as_bytes() {
+            return Err(SerializationError::ChecksumMismatch {
+                stored: stored_checksum.to_vec(),
+                computed: computed.as_bytes().to_vec(),
+            });
+        }
+        Ok(txs)
+    }
+
     // --- Deserialization with Preallocated Buffer ---
-    /// For inputs ≤ 4096 bytes, copies the data into a fixed-size stack buffer and calls deserialize().
-    #[inline(always)]
-    pub fn deserialize_with_pool<T: Decode>(data: &[u8], endianness: Endianness) -> SerializationResult<T> {
-        if data.len() <= 4096 {
-            let mut stack_buf = [0u8; 4096];
-            stack_buf[..data.len()].copy_from_slice(data);
-            // Call the full deserialize() so that header and checksum are parsed.
-            Serializer::deserialize(&stack_buf[..data.len()], endianness)
-        } else {
-            Serializer::deserialize(data, endianness)
+    /// Default pooled buffer size for `deserialize_with_pool`.
+    pub const DEFAULT_POOL_BUFFER_SIZE: usize = 4096;
+
+    /// Reads one frame from `reader` and deserializes it. Frames of up to `N`
+    /// bytes are read into a thread-local buffer that is reused across calls
+    /// and decoded straight from it, so steady-state receives allocate nothing
+    /// for the frame; larger frames get a one-off buffer. Both paths run the
+    /// full `deserialize()`, so the length prefix and checksum are always
+    /// validated, and both report a frame cut short as `UnexpectedEof`.
+    ///
+    /// `N` is a const generic so callers can size the pool to their message
+    /// profile, e.g. `deserialize_with_pool::<_, Transaction, 2048>`. The
+    /// thread-local buffer is shared by every `N` and `T`, so it grows to the
+    /// largest `N` used on that thread and is never shrunk.
+    pub fn deserialize_with_pool<R: Read, T: Decode, const N: usize>(
+        reader: &mut R,
+        endianness: Endianness,
+    ) -> SerializationResult<T> {
+        thread_local! {
+            static POOL: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
         }
+        let mut prefix = [0u8; 4];
+        reader.read_exact(&mut prefix)?;
+        let len = Self::length_prefix(&prefix, endianness)?;
+        let frame_len = 4 + len;
+        if frame_len > N {
+            let mut frame = prefix.to_vec();
+            reader.take(len as u64).read_to_end(&mut frame)?;
+            if frame.len() < frame_len {
+                return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+            }
+            return Serializer::deserialize(&frame, endianness);
+        }
+        POOL.with_borrow_mut(|frame| {
+            frame.clear();
+            frame.extend_from_slice(&prefix);
+            frame.resize(frame_len, 0);
+            reader.read_exact(&mut frame[4..])?;
+            Serializer::deserialize(frame, endianness)
+        })
     }
 
     // --- Fixed Serialization ---
-    /// Uses a fixed-size (121 bytes) buffer for ultra–low–latency serialization.
-    const ULTRA_TX_SIZE: usize = 8 + 8 + 8 + 1 + 16 + 16 + 64; // = 121 bytes
+    /// Uses a fixed-size (129 bytes) buffer for ultra–low–latency serialization.
+    pub const ULTRA_TX_SIZE: usize = 8 + 8 + 8 + 1 + 8 + ULTRA_ADDRESS_LEN * 2 + ULTRA_SIGNATURE_LEN; // = 129 bytes
 
     #[inline(always)]
     pub fn serialize_ultra_fixed(tx: &Transaction, endianness: Endianness) -> SerializationResult<[u8; Self::ULTRA_TX_SIZE]> {
@@ -655,6 +1763,9 @@ impl Serializer {
         if buf.len() < offset + 1 { return Err(SerializationError::BufferTooSmall); }
         buf[offset] = tx.version;
         offset += 1;
+        // Write nonce (8 bytes)
+        endianness.write_u64(tx.nonce, &mut buf[offset..offset+8])?;
+        offset += 8;
         // Write sender: fixed 16 bytes (padded with zeros)
         let sender_bytes = tx.sender.as_bytes();
         let sender_len = if sender_bytes.len() > 16 { 16 } else { sender_bytes.len() };
@@ -676,6 +1787,54 @@ impl Serializer {
         Ok(buf)
     }
 
+    /// Writes the same 129 bytes as `serialize_ultra_fixed` into `out`, which
+    /// may hold stale data: every byte is overwritten, with the unused tail of
+    /// each address and signature slot zeroed explicitly. Reusing one buffer
+    /// this way skips the zero-initialization of a fresh array per call.
+    #[inline(always)]
+    pub fn serialize_ultra_fixed_into(
+        tx: &Transaction,
+        endianness: Endianness,
+        out: &mut [u8; Self::ULTRA_TX_SIZE],
+    ) -> SerializationResult<()> {
+        let (id, rest) = out.split_at_mut(8);
+        let (amount, rest) = rest.split_at_mut(8);
+        let (fee, rest) = rest.split_at_mut(8);
+        let (version, rest) = rest.split_at_mut(1);
+        let (nonce, rest) = rest.split_at_mut(8);
+        let (sender, rest) = rest.split_at_mut(ULTRA_ADDRESS_LEN);
+        let (recipient, signature) = rest.split_at_mut(ULTRA_ADDRESS_LEN);
+        endianness.write_u64(tx.id, id)?;
+        endianness.write_u64(tx.amount, amount)?;
+        match endianness {
+            Endianness::Little => (&mut fee[..]).write_f64::<LittleEndian>(tx.fee)?,
+            Endianness::Big => (&mut fee[..]).write_f64::<BigEndian>(tx.fee)?,
+        }
+        version[0] = tx.version;
+        endianness.write_u64(tx.nonce, nonce)?;
+        write_padded(tx.sender.as_bytes(), sender);
+        write_padded(tx.recipient.as_bytes(), recipient);
+        write_padded(&tx.signature, signature);
+        Ok(())
+    }
+
+    /// Encodes a constant-size value into a stack array. `N` must equal
+    /// `T::SIZE`; a mismatch fails to compile rather than at runtime, e.g.
+    /// `let buf: [u8; 8] = Serializer::serialize_fixed_stack(&Fixed(1u64), e)?;`
+    #[inline(always)]
+    pub fn serialize_fixed_stack<T: Encode + ConstEncodedSize, const N: usize>(
+        value: &T,
+        endianness: Endianness,
+    ) -> SerializationResult<[u8; N]> {
+        const { assert!(N == T::SIZE, "stack buffer length must equal T::SIZE") };
+        let mut buf = [0u8; N];
+        let written = value.encode_to(&mut buf, endianness)?;
+        if written != N {
+            return Err(SerializationError::InvalidData("Constant-size encoding wrote an unexpected length".into()));
+        }
+        Ok(buf)
+    }
+
     #[inline(always)]
     pub fn deserialize_ultra_fixed(buf: &[u8; Self::ULTRA_TX_SIZE], endianness: Endianness) -> SerializationResult<Transaction> {
         let mut offset = 0;
@@ -709,6 +1868,15 @@ impl Serializer {
         if buf.len() < offset + 1 { return Err(SerializationError::BufferTooSmall); }
         let version = buf[offset];
         offset += 1;
+        let nonce = {
+            let slice = &buf[offset..offset+8];
+            let mut rdr = Cursor::new(slice);
+            match endianness {
+                Endianness::Little => rdr.read_u64::<LittleEndian>()?,
+                Endianness::Big => rdr.read_u64::<BigEndian>()?,
+            }
+        };
+        offset += 8;
         let sender_bytes = &buf[offset..offset+16];
         let sender = String::from_utf8(sender_bytes.iter().cloned().take_while(|&b| b != 0).collect())
             .map_err(|e| SerializationError::InvalidData(format!("Sender UTF-8 error: {}", e)))?;
@@ -717,80 +1885,299 @@ impl Serializer {
         let recipient = String::from_utf8(recipient_bytes.iter().cloned().take_while(|&b| b != 0).collect())
             .map_err(|e| SerializationError::InvalidData(format!("Recipient UTF-8 error: {}", e)))?;
         offset += 16;
-        let signature = buf[offset..offset+64].to_vec();
+        let signature = SignatureBytes::from(&buf[offset..offset+64]);
         offset += 64;
         if offset != Self::ULTRA_TX_SIZE {
             return Err(SerializationError::InvalidData("Ultra TX size mismatch on deserialization".into()));
         }
-        Ok(Transaction { id, amount, fee, version, sender, recipient, signature })
+        let tx = Transaction { id, amount, fee, version, nonce, sender, recipient, signature };
+        tx.validate()?;
+        Ok(tx)
+    }
+
+    // --- Fixed Batch Serialization ---
+    /// Writes `txs` as `[count: u32][count × 129-byte ultra-fixed records][Blake3 checksum]`.
+    /// The checksum covers the count and all records, so the whole batch is
+    /// verified once instead of per transaction. Record `i` starts at byte
+    /// `4 + i * 129`, which suits memory-mapped access.
+    pub fn serialize_ultra_batch(txs: &[Transaction], endianness: Endianness) -> SerializationResult<Vec<u8>> {
+        let count = u32::try_from(txs.len()).map_err(|_| SerializationError::Overflow)?;
+        let body_len = txs.len()
+            .checked_mul(Self::ULTRA_TX_SIZE)
+            .and_then(|v| v.checked_add(4))
+            .ok_or(SerializationError::Overflow)?;
+        let mut output = vec![0u8; body_len + 32];
+        endianness.write_u32(count, &mut output[..4])?;
+        for (tx, record) in txs.iter().zip(output[4..body_len].chunks_exact_mut(Self::ULTRA_TX_SIZE)) {
+            let record: &mut [u8; Self::ULTRA_TX_SIZE] = record.try_into().expect("chunk is ULTRA_TX_SIZE bytes");
+            Self::serialize_ultra_fixed_into(tx, endianness, record)?;
+        }
+        let hash = Self::compute_hash(&output[..body_len]);
+        output[body_len..].copy_from_slice(hash.as_bytes());
+        Ok(output)
+    }
+
+    /// Decodes a batch written by `serialize_ultra_batch`, verifying the length
+    /// against the count and the trailing checksum before decoding any record.
+    pub fn deserialize_ultra_batch(buffer: &[u8], endianness: Endianness) -> SerializationResult<Vec<Transaction>> {
+        if buffer.len() < 4 + 32 {
+            return Err(SerializationError::InvalidData("Buffer too small for ultra batch".into()));
+        }
+        let count = match endianness {
+            Endianness::Little => Cursor::new(&buffer[..4]).read_u32::<LittleEndian>()?,
+            Endianness::Big => Cursor::new(&buffer[..4]).read_u32::<BigEndian>()?,
+        } as usize;
+        let body_len = count
+            .checked_mul(Self::ULTRA_TX_SIZE)
+            .and_then(|v| v.checked_add(4))
+            .ok_or(SerializationError::Overflow)?;
+        if buffer.len() != body_len + 32 {
+            return Err(SerializationError::InvalidData("Ultra batch length does not match record count".into()));
+        }
+        let stored_checksum = &buffer[body_len..];
+        let computed_hash = Self::compute_hash(&buffer[..body_len]);
+        if stored_checksum != computed_hash.as_bytes() {
+            return Err(SerializationError::ChecksumMismatch {
+                stored: stored_checksum.to_vec(),
+                computed: computed_hash.as_bytes().to_vec(),
+            });
+        }
+        buffer[4..body_len]
+            .chunks_exact(Self::ULTRA_TX_SIZE)
+            .map(|record| {
+                let record: &[u8; Self::ULTRA_TX_SIZE] = record.try_into().expect("chunk is ULTRA_TX_SIZE bytes");
+                Self::deserialize_ultra_fixed(record, endianness)
+            })
+            .collect()
     }
 
     /// --- Parallel Deserialization ---
-    /// Uses par_chunks_exact(512) for even workload distribution.
+    /// Splits the work into runs of at least 512 frames for even workload
+    /// distribution. Results keep the order of `batches`; the first frame that
+    /// fails to decode fails the whole call.
     #[inline(always)]
     pub fn parallel_deserialize<T: Decode + Send + 'static>(
         batches: &[Vec<u8>],
         endianness: Endianness,
     ) -> SerializationResult<Vec<T>> {
-        let results: Vec<T> = batches.par_chunks_exact(512)
-            .flat_map(|chunk| {
-                chunk.iter().map(|data| {
-                    Serializer::deserialize::<T>(black_box(data), endianness)
-                        .expect("Deserialization failed")
-                }).collect::<Vec<T>>()
-            })
-            .collect();
-        Ok(results)
+        batches
+            .par_iter()
+            .with_min_len(512)
+            .map(|data| Serializer::deserialize::<T>(black_box(data), endianness))
+            .collect()
+    }
+
+    /// Like `parallel_deserialize`, but runs on `pool` instead of Rayon's
+    /// global pool, so deserialization can be kept off threads the caller
+    /// uses for other parallel work.
+    pub fn parallel_deserialize_in<T: Decode + Send + 'static>(
+        batches: &[Vec<u8>],
+        endianness: Endianness,
+        pool: &rayon::ThreadPool,
+    ) -> SerializationResult<Vec<T>> {
+        pool.install(|| Self::parallel_deserialize(batches, endianness))
+    }
+}
+
+/// --- Fixed-Length Encoding Utilities ---
+pub mod fixed_encoding {
+    use super::*;
+    #[inline(always)]
+    pub fn encode_fixed_u64(value: u64, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        if buffer.len() < 8 { return Err(SerializationError::BufferTooSmall); }
+        endianness.write_u64(value, buffer)
+    }
+    
+    #[inline(always)]
+    pub fn decode_fixed_u64(buffer: &[u8], endianness: Endianness) -> SerializationResult<(u64, usize)> {
+        if buffer.len() < 8 { return Err(SerializationError::InvalidData("Buffer too small for fixed u64".into())); }
+        let value = match endianness {
+            Endianness::Little => {
+                let mut rdr = Cursor::new(&buffer[..8]);
+                rdr.read_u64::<LittleEndian>()?
+            },
+            Endianness::Big => {
+                let mut rdr = Cursor::new(&buffer[..8]);
+                rdr.read_u64::<BigEndian>()?
+            },
+        };
+        Ok((value, 8))
+    }
+    
+    #[inline(always)]
+    pub fn encode_fixed_u32(value: u32, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        if buffer.len() < 4 { return Err(SerializationError::BufferTooSmall); }
+        endianness.write_u32(value, buffer)
+    }
+    
+    #[inline(always)]
+    pub fn decode_fixed_u32(buffer: &[u8], endianness: Endianness) -> SerializationResult<(u32, usize)> {
+        if buffer.len() < 4 { return Err(SerializationError::InvalidData("Buffer too small for fixed u32".into())); }
+        let value = match endianness {
+            Endianness::Little => {
+                let mut rdr = Cursor::new(&buffer[..4]);
+                rdr.read_u32::<LittleEndian>()?
+            },
+            Endianness::Big => {
+                let mut rdr = Cursor::new(&buffer[..4]);
+                rdr.read_u32::<BigEndian>()?
+            },
+        };
+        Ok((value, 4))
+    }
+
+    /// Two's-complement fixed 4-byte encoding of an i32.
+    #[inline(always)]
+    pub fn encode_fixed_i32(value: i32, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        encode_fixed_u32(value as u32, buffer, endianness)
+    }
+
+    #[inline(always)]
+    pub fn decode_fixed_i32(buffer: &[u8], endianness: Endianness) -> SerializationResult<(i32, usize)> {
+        let (value, consumed) = decode_fixed_u32(buffer, endianness)?;
+        Ok((value as i32, consumed))
+    }
+
+    /// Two's-complement fixed 8-byte encoding of an i64.
+    #[inline(always)]
+    pub fn encode_fixed_i64(value: i64, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+        encode_fixed_u64(value as u64, buffer, endianness)
+    }
+
+    #[inline(always)]
+    pub fn decode_fixed_i64(buffer: &[u8], endianness: Endianness) -> SerializationResult<(i64, usize)> {
+        let (value, consumed) = decode_fixed_u64(buffer, endianness)?;
+        Ok((value as i64, consumed))
+    }
+
+    /// Field-level override: wraps an integer so it is encoded at fixed width
+    /// (honouring `Endianness`) instead of as a varint. Fixed and varint fields
+    /// mix freely in a hand-written impl, e.g.
+    /// `FieldWriter::new(buf, e).field(&Fixed(self.height))?.field(&self.id)?`
+    /// paired with `reader.field::<Fixed<u64>>()?.0` then `reader.field::<u64>()?`.
+    /// Both sides must agree on which fields are fixed; the choice is not
+    /// recorded on the wire.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Fixed<T>(pub T);
+
+    /// Encodes `value` at fixed width; shorthand for `Fixed(value).encode_to`.
+    #[inline(always)]
+    pub fn encode_field_fixed<T: Copy>(value: T, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize>
+    where
+        Fixed<T>: Encode,
+    {
+        Fixed(value).encode_to(buffer, endianness)
+    }
+
+    /// Decodes a fixed-width field; shorthand for `Fixed::<T>::decode_from`.
+    #[inline(always)]
+    pub fn decode_field_fixed<T>(buffer: &[u8], endianness: Endianness) -> SerializationResult<(T, usize)>
+    where
+        Fixed<T>: Decode,
+    {
+        let (Fixed(value), consumed) = Fixed::<T>::decode_from(buffer, endianness)?;
+        Ok((value, consumed))
+    }
+
+    impl Encode for Fixed<u32> {
+        fn encoded_size(&self) -> usize { 4 }
+        fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+            encode_fixed_u32(self.0, buffer, endianness)
+        }
+    }
+
+    impl ConstEncodedSize for Fixed<u32> {
+        const SIZE: usize = 4;
+    }
+
+    impl Decode for Fixed<u32> {
+        fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+            decode_fixed_u32(buffer, endianness).map(|(v, n)| (Fixed(v), n))
+        }
+    }
+
+    impl Encode for Fixed<u64> {
+        fn encoded_size(&self) -> usize { 8 }
+        fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+            encode_fixed_u64(self.0, buffer, endianness)
+        }
+    }
+
+    impl ConstEncodedSize for Fixed<u64> {
+        const SIZE: usize = 8;
+    }
+
+    impl Decode for Fixed<u64> {
+        fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+            decode_fixed_u64(buffer, endianness).map(|(v, n)| (Fixed(v), n))
+        }
+    }
+
+    impl Encode for Fixed<i32> {
+        fn encoded_size(&self) -> usize { 4 }
+        fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+            encode_fixed_i32(self.0, buffer, endianness)
+        }
+    }
+
+    impl ConstEncodedSize for Fixed<i32> {
+        const SIZE: usize = 4;
+    }
+
+    impl Decode for Fixed<i32> {
+        fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+            decode_fixed_i32(buffer, endianness).map(|(v, n)| (Fixed(v), n))
+        }
+    }
+
+    impl Encode for Fixed<i64> {
+        fn encoded_size(&self) -> usize { 8 }
+        fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+            encode_fixed_i64(self.0, buffer, endianness)
+        }
+    }
+
+    impl ConstEncodedSize for Fixed<i64> {
+        const SIZE: usize = 8;
     }
-}
 
-/// --- Fixed-Length Encoding Utilities ---
-pub mod fixed_encoding {
-    use super::*;
-    #[inline(always)]
-    pub fn encode_fixed_u64(value: u64, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
-        if buffer.len() < 8 { return Err(SerializationError::BufferTooSmall); }
-        endianness.write_u64(value, buffer)
+    impl Decode for Fixed<i64> {
+        fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+            decode_fixed_i64(buffer, endianness).map(|(v, n)| (Fixed(v), n))
+        }
     }
-    
-    #[inline(always)]
-    pub fn decode_fixed_u64(buffer: &[u8], endianness: Endianness) -> SerializationResult<(u64, usize)> {
-        if buffer.len() < 8 { return Err(SerializationError::InvalidData("Buffer too small for fixed u64".into())); }
-        let value = match endianness {
-            Endianness::Little => {
-                let mut rdr = Cursor::new(&buffer[..8]);
-                rdr.read_u64::<LittleEndian>()?
-            },
-            Endianness::Big => {
-                let mut rdr = Cursor::new(&buffer[..8]);
-                rdr.read_u64::<BigEndian>()?
-            },
-        };
-        Ok((value, 8))
+
+    /// Byte strings with a fixed 4-byte length prefix instead of a varint.
+    impl Encode for Fixed<&[u8]> {
+        fn encoded_size(&self) -> usize { 4 + self.0.len() }
+        fn encode_to(&self, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
+            let len = u32::try_from(self.0.len()).map_err(|_| SerializationError::Overflow)?;
+            if buffer.len() < 4 + self.0.len() { return Err(SerializationError::BufferTooSmall); }
+            encode_fixed_u32(len, buffer, endianness)?;
+            buffer[4..4 + self.0.len()].copy_from_slice(self.0);
+            Ok(4 + self.0.len())
+        }
     }
-    
-    #[inline(always)]
-    pub fn encode_fixed_u32(value: u32, buffer: &mut [u8], endianness: Endianness) -> SerializationResult<usize> {
-        if buffer.len() < 4 { return Err(SerializationError::BufferTooSmall); }
-        endianness.write_u32(value, buffer)
+
+    impl Decode for Fixed<Vec<u8>> {
+        fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+            let (len, _) = decode_fixed_u32(buffer, endianness)?;
+            let total = 4usize.checked_add(len as usize).ok_or(SerializationError::Overflow)?;
+            if buffer.len() < total {
+                return Err(SerializationError::InvalidData("Not enough bytes for fixed-prefix byte string".into()));
+            }
+            Ok((Fixed(buffer[4..total].to_vec()), total))
+        }
     }
-    
-    #[inline(always)]
-    pub fn decode_fixed_u32(buffer: &[u8], endianness: Endianness) -> SerializationResult<(u32, usize)> {
-        if buffer.len() < 4 { return Err(SerializationError::InvalidData("Buffer too small for fixed u32".into())); }
-        let value = match endianness {
-            Endianness::Little => {
-                let mut rdr = Cursor::new(&buffer[..4]);
-                rdr.read_u32::<LittleEndian>()?
-            },
-            Endianness::Big => {
-                let mut rdr = Cursor::new(&buffer[..4]);
-                rdr.read_u32::<BigEndian>()?
-            },
-        };
-        Ok((value, 4))
+
+    impl Decode for Fixed<String> {
+        fn decode_from(buffer: &[u8], endianness: Endianness) -> SerializationResult<(Self, usize)> {
+            let (Fixed(bytes), consumed) = Fixed::<Vec<u8>>::decode_from(buffer, endianness)?;
+            String::from_utf8(bytes)
+                .map(|s| (Fixed(s), consumed))
+                .map_err(|e| SerializationError::InvalidData(format!("UTF-8 error: {:?}", e)))
+        }
     }
-    // Additional functions for i32, i64 can be added if needed.
 }
 
 #[cfg(test)]
@@ -808,6 +2195,49 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_varint_length_cap() {
+        assert!(matches!(decode_varint_u64(&[0x80; 11]), Err(SerializationError::InvalidData(_))));
+        assert!(decode_varint_u64(&[0x80; 5]).is_err());
+        let mut buf = [0u8; MAX_VARINT_BYTES];
+        let written = encode_varint_u64(u64::MAX, &mut buf).unwrap();
+        assert_eq!(written, MAX_VARINT_BYTES);
+        assert_eq!(decode_varint_u64(&buf).unwrap(), (u64::MAX, MAX_VARINT_BYTES));
+        // A tenth byte carrying more than one bit would overflow.
+        buf[MAX_VARINT_BYTES - 1] = 0x02;
+        assert!(decode_varint_u64(&buf).is_err());
+    }
+
+    #[test]
+    fn test_non_canonical_varint_rejected() {
+        assert_eq!(decode_varint_u64(&[0x00]).unwrap(), (0, 1));
+        assert_eq!(decode_varint_u64(&[0x81, 0x01]).unwrap(), (129, 2));
+        for overlong in [&[0x80, 0x00][..], &[0x81, 0x00], &[0xff, 0x80, 0x00]] {
+            match decode_varint_u64(overlong) {
+                Err(SerializationError::InvalidData(msg)) => assert_eq!(msg, "non-canonical varint"),
+                other => panic!("{:?} decoded as {:?}", overlong, other),
+            }
+        }
+
+        // `deserialize` rejects a frame whose payload pads a varint.
+        let frame = |payload: &[u8]| {
+            let mut buffer = vec![0u8; 4 + payload.len() + 32];
+            buffer[4..4 + payload.len()].copy_from_slice(payload);
+            Serializer::seal::<Blake3Checksum>(&mut buffer, payload.len(), Endianness::Little).unwrap();
+            buffer
+        };
+        assert_eq!(Serializer::deserialize::<u64>(&frame(&[0x00]), Endianness::Little).unwrap(), 0);
+        assert!(matches!(
+            Serializer::deserialize::<u64>(&frame(&[0x80, 0x00]), Endianness::Little),
+            Err(SerializationError::InvalidData(msg)) if msg == "non-canonical varint"
+        ));
+
+        // The lenient entry point still reads legacy overlong encodings, and
+        // strict decoding resumes afterwards.
+        assert_eq!(Serializer::deserialize_lenient::<u64>(&frame(&[0x81, 0x80, 0x00]), Endianness::Little).unwrap(), 1);
+        assert!(Serializer::deserialize::<u64>(&frame(&[0x80, 0x00]), Endianness::Little).is_err());
+    }
+
     #[test]
     fn test_primitive_encoding() -> SerializationResult<()> {
         let mut buf = [0u8; 16];
@@ -838,9 +2268,10 @@ mod tests {
             amount: 1000,
             fee: 0.01,
             version: 1,
+            nonce: 0,
             sender: "Alice".into(),
             recipient: "Bob".into(),
-            signature: vec![1, 2, 3, 4],
+            signature: vec![1, 2, 3, 4].into(),
         };
         let ser = Serializer::serialize(&tx, Endianness::Little)?;
         let de: Transaction = Serializer::deserialize(&ser, Endianness::Little)?;
@@ -855,18 +2286,20 @@ mod tests {
             amount: 500,
             fee: 0.02,
             version: 1,
+            nonce: 0,
             sender: "Alice".into(),
             recipient: "Bob".into(),
-            signature: vec![1, 2, 3],
+            signature: vec![1, 2, 3].into(),
         };
         let tx2 = Transaction {
             id: 2,
             amount: 750,
             fee: 0.03,
             version: 1,
+            nonce: 0,
             sender: "Charlie".into(),
             recipient: "Dave".into(),
-            signature: vec![4, 5, 6],
+            signature: vec![4, 5, 6].into(),
         };
         let block = Block {
             version: 1,
@@ -880,6 +2313,41 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parallel_block_serialization_matches_sequential() -> SerializationResult<()> {
+        // Enough transactions that the varint count prefix takes two bytes.
+        let transactions = (0..300u64)
+            .map(|i| Transaction {
+                id: i,
+                amount: i * 7,
+                fee: 0.01 * i as f64,
+                version: 1,
+                nonce: 0,
+                sender: format!("sender-{}", i),
+                recipient: format!("recipient-{}", i % 13),
+                signature: vec![i as u8; (i % 70) as usize].into(),
+            })
+            .collect();
+        let block = Block {
+            version: 2,
+            block_number: 99,
+            previous_hash: vec![0xab; 32],
+            transactions,
+        };
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let parallel = Serializer::serialize_block_parallel(&block, endianness)?;
+            assert_eq!(parallel, Serializer::serialize(&block, endianness)?);
+            let de: Block = Serializer::deserialize(&parallel, endianness)?;
+            assert_eq!(de, block);
+        }
+        let empty = Block { version: 1, block_number: 0, previous_hash: Vec::new(), transactions: Vec::new() };
+        assert_eq!(
+            Serializer::serialize_block_parallel(&empty, Endianness::Little)?,
+            Serializer::serialize(&empty, Endianness::Little)?
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_ultra_fixed_serialization() -> SerializationResult<()> {
         let tx = Transaction {
@@ -887,9 +2355,10 @@ mod tests {
             amount: 5000,
             fee: 0.05,
             version: 1,
+            nonce: 0,
             sender: "Alice".into(),
             recipient: "Bob".into(),
-            signature: vec![1, 2, 3, 4],
+            signature: vec![1, 2, 3, 4].into(),
         };
         let ultra = Serializer::serialize_ultra_fixed(&tx, Endianness::Little)?;
         let tx_decoded = Serializer::deserialize_ultra_fixed(&ultra, Endianness::Little)?;
@@ -902,4 +2371,643 @@ mod tests {
         assert_eq!(&tx.signature[..], &tx_decoded.signature[..tx.signature.len()]);
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ultra_fixed_into_matches_zeroed_encoding() -> SerializationResult<()> {
+        let tx = Transaction {
+            id: 987654321,
+            amount: 42,
+            fee: 1.25,
+            version: 2,
+            nonce: 7,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![9; 10].into(),
+        };
+        for endianness in [Endianness::Little, Endianness::Big] {
+            // Stale bytes from a previous use must not leak into the padding.
+            let mut reused = [0xAA; Serializer::ULTRA_TX_SIZE];
+            Serializer::serialize_ultra_fixed_into(&tx, endianness, &mut reused)?;
+            assert_eq!(reused, Serializer::serialize_ultra_fixed(&tx, endianness)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_fixed_stack() -> SerializationResult<()> {
+        let buf: [u8; <Fixed<u64>>::SIZE] = Serializer::serialize_fixed_stack(&Fixed(0x0102u64), Endianness::Big)?;
+        assert_eq!(buf, [0, 0, 0, 0, 0, 0, 1, 2]);
+        let buf: [u8; 4] = Serializer::serialize_fixed_stack(&[7u8; 4], Endianness::Little)?;
+        assert_eq!(buf, [7; 4]);
+
+        let tx = Transaction {
+            id: 987654321,
+            amount: 42,
+            fee: 1.25,
+            version: 2,
+            nonce: 7,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![9; 10].into(),
+        };
+        let buf: [u8; UltraFixed::SIZE] = Serializer::serialize_fixed_stack(&UltraFixed(&tx), Endianness::Little)?;
+        assert_eq!(buf, Serializer::serialize_ultra_fixed(&tx, Endianness::Little)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_with_pool_validates_checksum() -> SerializationResult<()> {
+        let tx = Transaction {
+            id: 7,
+            amount: 100,
+            fee: 0.5,
+            version: 1,
+            nonce: 0,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![9; 64].into(),
+        };
+        let mut ser = Serializer::serialize(&tx, Endianness::Little)?;
+        // Back-to-back frames on one stream: both the pooled path and the
+        // one-off path decode the same value and consume exactly one frame.
+        let stream = [ser.clone(), ser.clone()].concat();
+        let mut reader = Cursor::new(&stream[..]);
+        let pooled: Transaction = Serializer::deserialize_with_pool::<_, _, 4096>(&mut reader, Endianness::Little)?;
+        let one_off: Transaction = Serializer::deserialize_with_pool::<_, _, 16>(&mut reader, Endianness::Little)?;
+        assert_eq!(pooled, tx);
+        assert_eq!(one_off, tx);
+        assert_eq!(reader.position() as usize, stream.len());
+        let last = ser.len() - 1;
+        ser[last] ^= 0xff;
+        for result in [
+            Serializer::deserialize_with_pool::<_, Transaction, 4096>(&mut &ser[..], Endianness::Little),
+            Serializer::deserialize_with_pool::<_, Transaction, 16>(&mut &ser[..], Endianness::Little),
+        ] {
+            assert!(matches!(result, Err(SerializationError::ChecksumMismatch { .. })));
+        }
+        // A truncated frame fails instead of decoding stale pool contents.
+        for truncated in [
+            Serializer::deserialize_with_pool::<_, Transaction, 4096>(&mut &ser[..10], Endianness::Little),
+            Serializer::deserialize_with_pool::<_, Transaction, 16>(&mut &ser[..10], Endianness::Little),
+        ] {
+            let eof = matches!(&truncated, Err(SerializationError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof);
+            assert!(eof, "{:?}", truncated.err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_checked_reports_checksum_status() -> SerializationResult<()> {
+        let tx = TransactionBuilder::new().id(7).amount(500).fee(2.0).sender("Alice").recipient("Bob").build()?;
+        let mut ser = Serializer::serialize(&tx, Endianness::Little)?;
+        assert_eq!(Serializer::deserialize_checked(&ser, Endianness::Little)?, (tx.clone(), ChecksumStatus::Valid));
+
+        let last = ser.len() - 1;
+        ser[last] ^= 0xff;
+        assert_eq!(Serializer::deserialize_checked(&ser, Endianness::Little)?, (tx, ChecksumStatus::Invalid));
+        assert!(matches!(
+            Serializer::deserialize::<Transaction>(&ser, Endianness::Little),
+            Err(SerializationError::ChecksumMismatch { .. })
+        ));
+        // Structural errors still fail outright.
+        assert!(Serializer::deserialize_checked::<Transaction>(&ser[..ser.len() - 1], Endianness::Little).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_writer_reader_round_trip() -> SerializationResult<()> {
+        let mut buf = [0u8; 16];
+        let written = FieldWriter::new(&mut buf, Endianness::Little)
+            .field(&300u64)?
+            .field(&7u8)?
+            .field(&String::from("hi"))?
+            .finish();
+        assert_eq!(written, 2 + 1 + 3);
+        let mut reader = FieldReader::new(&buf[..written], Endianness::Little);
+        assert_eq!(reader.field::<u64>()?, 300);
+        assert_eq!(reader.field::<u8>()?, 7);
+        assert_eq!(reader.field::<String>()?, "hi");
+        assert_eq!(reader.finish(), written);
+        let mut small = [0u8; 2];
+        assert!(matches!(
+            FieldWriter::new(&mut small, Endianness::Little).field(&300u64)?.field(&String::from("hi")),
+            Err(SerializationError::BufferTooSmall)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hex_round_trip() -> SerializationResult<()> {
+        let tx = Transaction {
+            id: 7,
+            amount: 1000,
+            fee: 0.5,
+            version: 1,
+            nonce: 3,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![0xde, 0xad, 0xbe, 0xef].into(),
+        };
+        let hex = Serializer::serialize_hex(&tx, Endianness::Little)?;
+        assert!(hex.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
+        assert_eq!(hex.len(), Serializer::serialize(&tx, Endianness::Little)?.len() * 2);
+        assert_eq!(Serializer::deserialize_hex::<Transaction>(&hex, Endianness::Little)?, tx);
+        assert_eq!(Serializer::deserialize_hex::<Transaction>(&hex.to_uppercase(), Endianness::Little)?, tx);
+
+        assert!(matches!(
+            Serializer::deserialize_hex::<Transaction>(&hex[1..], Endianness::Little),
+            Err(SerializationError::InvalidData(_))
+        ));
+        let mut bad = hex.clone();
+        bad.replace_range(0..2, "zz");
+        assert!(matches!(
+            Serializer::deserialize_hex::<Transaction>(&bad, Endianness::Little),
+            Err(SerializationError::InvalidData(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_prefix_walks_concatenated_frames() -> SerializationResult<()> {
+        let txs = (1..=3)
+            .map(|id| TransactionBuilder::new().id(id).amount(id * 10).fee(0.1).sender("Alice").recipient("Bob").build())
+            .collect::<SerializationResult<Vec<_>>>()?;
+        let mut stream = Vec::new();
+        for tx in &txs {
+            stream.extend(Serializer::serialize(tx, Endianness::Big)?);
+        }
+
+        let mut rest = stream.as_slice();
+        let mut decoded = Vec::new();
+        while !rest.is_empty() {
+            let (tx, tail) = Serializer::deserialize_prefix::<Transaction>(rest, Endianness::Big)?;
+            decoded.push(tx);
+            rest = tail;
+        }
+        assert_eq!(decoded, txs);
+
+        let first_len = Serializer::serialize(&txs[0], Endianness::Big)?.len();
+        assert!(matches!(
+            Serializer::deserialize_prefix::<Transaction>(&stream[..first_len - 1], Endianness::Big),
+            Err(SerializationError::InvalidData(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parallel_deserialize_in_custom_pool() -> SerializationResult<()> {
+        // Not a multiple of 512, so the last run is a partial one.
+        let txs: Vec<Transaction> = (0..1100u64)
+            .map(|id| Transaction {
+                id,
+                amount: id * 3,
+                fee: 0.25,
+                version: 1,
+                nonce: id,
+                sender: format!("sender-{}", id),
+                recipient: "Bob".into(),
+                signature: vec![id as u8; 4].into(),
+            })
+            .collect();
+        let batches = txs
+            .iter()
+            .map(|tx| Serializer::serialize(tx, Endianness::Little))
+            .collect::<SerializationResult<Vec<_>>>()?;
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().expect("thread pool");
+        let decoded: Vec<Transaction> = Serializer::parallel_deserialize_in(&batches, Endianness::Little, &pool)?;
+        assert_eq!(decoded, txs);
+        assert_eq!(pool.current_num_threads(), 2);
+
+        let mut corrupt = batches.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last][6] ^= 0xFF;
+        assert!(Serializer::parallel_deserialize_in::<Transaction>(&corrupt, Endianness::Little, &pool).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksum_choices_round_trip() -> SerializationResult<()> {
+        let block = Block { version: 1, block_number: 9, previous_hash: vec![7; 32], transactions: Vec::new() };
+        let payload = block.encoded_size();
+
+        let stored = Serializer::serialize(&block, Endianness::Little)?;
+        assert_eq!(stored.len(), 4 + payload + 32);
+        assert_eq!(&stored[4 + payload..], blake3::hash(&stored[4..4 + payload]).as_bytes());
+        assert_eq!(Serializer::deserialize::<Block>(&stored, Endianness::Little)?, block);
+
+        let crc = Serializer::serialize_with::<Crc32Checksum, _>(&block, Endianness::Little)?;
+        assert_eq!(crc.len(), 4 + payload + 4);
+        assert_eq!(Serializer::deserialize_with::<Crc32Checksum, Block>(&crc, Endianness::Little)?, block);
+        let mut corrupt = crc.clone();
+        corrupt[5] ^= 0x01;
+        assert!(matches!(
+            Serializer::deserialize_with::<Crc32Checksum, Block>(&corrupt, Endianness::Little),
+            Err(SerializationError::ChecksumMismatch { .. })
+        ));
+
+        let bare = Serializer::serialize_with::<NoChecksum, _>(&block, Endianness::Big)?;
+        assert_eq!(bare.len(), 4 + payload);
+        assert_eq!(Serializer::deserialize_with::<NoChecksum, Block>(&bare, Endianness::Big)?, block);
+
+        // Standard CRC-32 check value.
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        Ok(())
+    }
+
+    #[test]
+    fn test_endianness_mismatch_reported() {
+        let tx = TransactionBuilder::new().id(3).amount(10).fee(1.0).sender("Alice").recipient("Bob").build().unwrap();
+        let little = Serializer::serialize(&tx, Endianness::Little).unwrap();
+        assert!(matches!(
+            Serializer::deserialize::<Transaction>(&little, Endianness::Big),
+            Err(SerializationError::EndiannessMismatch { expected: Endianness::Big, found: Endianness::Little })
+        ));
+        let big = Serializer::serialize(&tx, Endianness::Big).unwrap();
+        assert!(matches!(
+            Serializer::deserialize::<Transaction>(&big, Endianness::Little),
+            Err(SerializationError::EndiannessMismatch { expected: Endianness::Little, found: Endianness::Big })
+        ));
+    }
+
+    #[test]
+    fn test_big_endian_length_prefix_round_trip() {
+        // Lengths on both sides of the marker bit's position in a big-endian prefix.
+        for len in [0, 100, 200, 70_000] {
+            let tx = TransactionBuilder::new()
+                .id(1)
+                .fee(1.0)
+                .sender("Alice")
+                .recipient("Bob")
+                .signature(vec![7; len])
+                .build()
+                .unwrap();
+            let frame = Serializer::serialize(&tx, Endianness::Big).unwrap();
+            assert_eq!(Serializer::deserialize::<Transaction>(&frame, Endianness::Big).unwrap(), tx);
+        }
+    }
+
+    #[test]
+    fn test_signature_bytes_round_trip() {
+        for len in [64, 0] {
+            let tx = Transaction {
+                id: 7,
+                amount: 500,
+                fee: 1.5,
+                version: 1,
+                nonce: 2,
+                sender: "Alice".to_string(),
+                recipient: "Bob".to_string(),
+                signature: vec![0x5A; len].into(),
+            };
+            let encoded = Serializer::serialize(&tx, Endianness::Little).unwrap();
+            let decoded: Transaction = Serializer::deserialize(&encoded, Endianness::Little).unwrap();
+            assert_eq!(decoded, tx);
+            assert_eq!(decoded.signature.len(), len);
+            // The wire format is that of a plain Vec<u8>, whatever the storage.
+            let mut as_vec = vec![0u8; tx.signature.encoded_size()];
+            vec![0x5A; len].encode_to(&mut as_vec, Endianness::Little).unwrap();
+            let mut as_signature = vec![0u8; tx.signature.encoded_size()];
+            tx.signature.encode_to(&mut as_signature, Endianness::Little).unwrap();
+            assert_eq!(as_signature, as_vec);
+        }
+    }
+
+    #[test]
+    fn test_content_hash_ignores_signature() {
+        let tx = Transaction {
+            id: 1,
+            amount: 1000,
+            fee: 0.5,
+            version: 1,
+            nonce: 0,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![1, 2, 3].into(),
+        };
+        let resigned = Transaction { signature: vec![9; 64].into(), ..tx.clone() };
+        assert_eq!(tx.content_hash(), resigned.content_hash());
+        let changed = Transaction { amount: 1001, ..tx.clone() };
+        assert_ne!(tx.content_hash(), changed.content_hash());
+    }
+
+    #[test]
+    fn test_canonical_eq_ignores_id_and_signature() {
+        let tx = TransactionBuilder::new().id(1).amount(1000).fee(1.5).nonce(4).sender("Alice").recipient("Bob").build().unwrap();
+        let relayed = Transaction { id: 77, signature: vec![9; 64].into(), ..tx.clone() };
+        assert_ne!(tx, relayed);
+        assert!(tx.canonical_eq(&relayed));
+        assert_eq!(tx.content_hash(), relayed.content_hash());
+
+        let changed = Transaction { amount: 1001, ..relayed.clone() };
+        assert!(!tx.canonical_eq(&changed));
+        assert_ne!(tx.content_hash(), changed.content_hash());
+        assert!(!tx.canonical_eq(&Transaction { nonce: 5, ..tx.clone() }));
+    }
+
+    #[test]
+    fn test_non_finite_fee_rejected() -> SerializationResult<()> {
+        let mut tx = Transaction {
+            id: 1,
+            amount: 10,
+            fee: f64::NAN,
+            version: 1,
+            nonce: 0,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![].into(),
+        };
+        assert!(tx.validate().is_err());
+        let ser = Serializer::serialize(&tx, Endianness::Little)?;
+        assert!(matches!(
+            Serializer::deserialize::<Transaction>(&ser, Endianness::Little),
+            Err(SerializationError::InvalidData(_))
+        ));
+        tx.fee = f64::INFINITY;
+        let ser = Serializer::serialize(&tx, Endianness::Little)?;
+        assert!(Serializer::deserialize::<Transaction>(&ser, Endianness::Little).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ultra_batch_round_trip() -> SerializationResult<()> {
+        let txs: Vec<Transaction> = (0..1000u64)
+            .map(|i| Transaction {
+                id: i,
+                amount: i * 10,
+                fee: 1.5,
+                version: 1,
+                nonce: 0,
+                sender: format!("sender{}", i % 7),
+                recipient: "Bob".into(),
+                signature: vec![(i % 251) as u8; 64].into(),
+            })
+            .collect();
+        let mut batch = Serializer::serialize_ultra_batch(&txs, Endianness::Big)?;
+        assert_eq!(batch.len(), 4 + 1000 * 129 + 32);
+        assert_eq!(Serializer::deserialize_ultra_batch(&batch, Endianness::Big)?, txs);
+        batch[10] ^= 1;
+        assert!(matches!(
+            Serializer::deserialize_ultra_batch(&batch, Endianness::Big),
+            Err(SerializationError::ChecksumMismatch { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_big_endian_wire_format() -> SerializationResult<()> {
+        let tx = Transaction {
+            id: 0x0102,
+            amount: 1000,
+            fee: 1.5,
+            version: 1,
+            nonce: 7,
+            sender: "Al".into(),
+            recipient: "Bo".into(),
+            signature: vec![0xAB, 0xCD, 0xEF].into(),
+        };
+        let format = WireFormat::FixedBigEndian;
+        let mut buf = vec![0u8; tx.encoded_size_in(format)];
+        // Endianness is ignored: the fixed format is always big-endian.
+        let written = tx.encode_to_format(&mut buf, Endianness::Little, format)?;
+        assert_eq!(written, buf.len());
+        #[rustfmt::skip]
+        let expected: Vec<u8> = [
+            &[0, 0, 0, 0, 0, 0, 0x01, 0x02][..],            // id: u64
+            &[0, 0, 0, 0, 0, 0, 0x03, 0xE8],                // amount: u64 (1000)
+            &[0x3F, 0xF8, 0, 0, 0, 0, 0, 0],                // fee: f64 (1.5)
+            &[1],                                           // version: u8
+            &[0, 0, 0, 0, 0, 0, 0, 7],                      // nonce: u64
+            &[0, 0, 0, 2, b'A', b'l'],                      // sender: u32 length + UTF-8
+            &[0, 0, 0, 2, b'B', b'o'],                      // recipient: u32 length + UTF-8
+            &[0, 0, 0, 3, 0xAB, 0xCD, 0xEF],                // signature: u32 length + bytes
+        ]
+        .concat();
+        assert_eq!(buf, expected);
+        assert_eq!(Transaction::decode_from_format(&buf, Endianness::Little, format)?, (tx.clone(), buf.len()));
+
+        // The default format is the compact varint encoding.
+        let mut compact = vec![0u8; tx.encoded_size()];
+        tx.encode_to_format(&mut compact, Endianness::Little, WireFormat::default())?;
+        assert_eq!(Transaction::decode_from(&compact, Endianness::Little)?.0, tx);
+        assert!(compact.len() < buf.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_rejects_trailing_payload_byte() -> SerializationResult<()> {
+        let txs = vec![
+            TransactionBuilder::new().id(1).fee(1.5).sender("Alice").recipient("Bob").build()?,
+            TransactionBuilder::new().id(2).fee(2.5).sender("Carol").recipient("Dave").build()?,
+        ];
+        let batch = Serializer::serialize_batch(&txs, Endianness::Little)?;
+        assert_eq!(Serializer::deserialize_batch::<Transaction>(&batch, Endianness::Little)?, txs);
+
+        // Re-frame the payload with one extra byte and a matching checksum and
+        // length, so only the item boundary check can catch it.
+        let mut payload = batch[4..batch.len() - 32].to_vec();
+        payload.push(0);
+        let checksum = blake3::hash(&payload);
+        payload.extend_from_slice(checksum.as_bytes());
+        let mut padded = (payload.len() as u32).to_le_bytes().to_vec();
+        padded.extend_from_slice(&payload);
+        assert!(matches!(
+            Serializer::deserialize_batch::<Transaction>(&padded, Endianness::Little),
+            Err(SerializationError::InvalidData(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_delta_batch_round_trip() -> SerializationResult<()> {
+        let template = Transaction {
+            id: 0,
+            amount: 2500,
+            fee: 1.5,
+            version: 1,
+            nonce: 3,
+            sender: "exchange-hot-wallet".into(),
+            recipient: "merchant-settlement".into(),
+            signature: vec![0x5A; 64].into(),
+        };
+        let txs: Vec<Transaction> = (0..1000u64).map(|id| Transaction { id, ..template.clone() }).collect();
+        let delta = Serializer::serialize_delta_batch(&txs, Endianness::Little)?;
+        let plain = Serializer::serialize_batch(&txs, Endianness::Little)?;
+        assert_eq!(Serializer::deserialize_delta_batch(&delta, Endianness::Little)?, txs);
+        assert!(delta.len() * 10 < plain.len(), "delta {} bytes vs plain {}", delta.len(), plain.len());
+        // The checksum matches the plain batch's, since both hash the full encodings.
+        assert_eq!(delta[delta.len() - 32..], plain[plain.len() - 32..]);
+
+        // Arbitrary field changes survive, including the empty batch.
+        let mut mixed = txs[..3].to_vec();
+        mixed[1].fee = 9.75;
+        mixed[2].signature = vec![1, 2, 3].into();
+        mixed[2].sender = "someone-else".into();
+        let encoded = Serializer::serialize_delta_batch(&mixed, Endianness::Big)?;
+        assert_eq!(Serializer::deserialize_delta_batch(&encoded, Endianness::Big)?, mixed);
+        let empty = Serializer::serialize_delta_batch(&[], Endianness::Big)?;
+        assert!(Serializer::deserialize_delta_batch(&empty, Endianness::Big)?.is_empty());
+
+        let mut corrupt = delta.clone();
+        corrupt[delta.len() - 40] ^= 1;
+        assert!(Serializer::deserialize_delta_batch(&corrupt, Endianness::Little).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_delta_batch_limits_expansion() -> SerializationResult<()> {
+        let big = Transaction {
+            id: 0,
+            amount: 1,
+            fee: 1.0,
+            version: 1,
+            nonce: 0,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![7u8; 1024 * 1024].into(),
+        };
+        let repeated = vec![big.clone(), big.clone()];
+        assert!(Serializer::serialize_delta_batch(&repeated, Endianness::Little).is_err());
+
+        // An empty delta is refused when decoding too.
+        let pair = vec![big.clone(), Transaction { id: 1, ..big.clone() }];
+        let mut encoded = Serializer::serialize_delta_batch(&pair, Endianness::Little)?;
+        encoded[4 + 1 + big.encoded_size()] = 0;
+        assert!(matches!(
+            Serializer::deserialize_delta_batch(&encoded, Endianness::Little),
+            Err(SerializationError::InvalidData(msg)) if msg.contains("repeats")
+        ));
+
+        // A 1 MiB transaction followed by id-only deltas: about 1 MiB on the
+        // wire, but more than the limit once rebuilt.
+        let count = MAX_DELTA_BATCH_BYTES / big.encoded_size() + 2;
+        let batch: Vec<Transaction> = (0..count as u64).map(|id| Transaction { id, ..big.clone() }).collect();
+        assert!(Serializer::serialize_delta_batch(&batch, Endianness::Little).is_err());
+        let mut crafted = vec![0u8; 4];
+        append_field(&mut crafted, &(count as u64), Endianness::Little)?;
+        append_field(&mut crafted, &big, Endianness::Little)?;
+        for id in 1..count as u64 {
+            append_field(&mut crafted, &1u8, Endianness::Little)?;
+            append_field(&mut crafted, &id, Endianness::Little)?;
+        }
+        crafted.extend_from_slice(&[0u8; 32]);
+        Serializer::write_length_prefix(crafted.len() - 4, Endianness::Little, &mut crafted[..4])?;
+        assert!(crafted.len() < 2 * 1024 * 1024);
+        assert!(matches!(
+            Serializer::deserialize_delta_batch(&crafted, Endianness::Little),
+            Err(SerializationError::InvalidData(msg)) if msg.contains("limit")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cow_transaction_borrows() -> SerializationResult<()> {
+        let tx = Transaction {
+            id: 5,
+            amount: 250,
+            fee: 2.0,
+            version: 1,
+            nonce: 0,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![1, 2, 3].into(),
+        };
+        let mut buf = vec![0u8; tx.encoded_size()];
+        tx.encode_to(&mut buf, Endianness::Little)?;
+        let (cow, consumed) = CowTransaction::decode_cow_from(&buf, Endianness::Little)?;
+        assert_eq!(consumed, buf.len());
+        assert!(matches!(cow.sender, Cow::Borrowed("Alice")));
+        assert!(matches!(cow.recipient, Cow::Borrowed(_)));
+        assert!(matches!(cow.signature, Cow::Borrowed(_)));
+        assert_eq!(cow.into_owned(), tx);
+        Ok(())
+    }
+
+    /// Asserts that decoding every proper prefix of `encoded` fails without
+    /// panicking, and that single-byte corruptions never panic.
+    fn assert_decode_never_panics<T: Decode + std::fmt::Debug>(encoded: &[u8]) {
+        for len in 0..encoded.len() {
+            assert!(T::decode_from(&encoded[..len], Endianness::Little).is_err(), "prefix of {} bytes decoded", len);
+        }
+        for i in 0..encoded.len() {
+            for flip in [0x01u8, 0x80, 0xff] {
+                let mut corrupt = encoded.to_vec();
+                corrupt[i] ^= flip;
+                let _ = T::decode_from(&corrupt, Endianness::Little);
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_input_never_panics() -> SerializationResult<()> {
+        let tx = Transaction {
+            id: 300,
+            amount: 70_000,
+            fee: 2.5,
+            version: 1,
+            nonce: 0,
+            sender: "Alice".into(),
+            recipient: "Bob".into(),
+            signature: vec![1, 2, 3, 4].into(),
+        };
+        let mut encoded = vec![0u8; tx.encoded_size()];
+        tx.encode_to(&mut encoded, Endianness::Little)?;
+        assert_decode_never_panics::<Transaction>(&encoded);
+
+        let block = Block { version: 1, block_number: 9, previous_hash: vec![7; 32], transactions: vec![tx.clone(), tx] };
+        let mut encoded = vec![0u8; block.encoded_size()];
+        block.encode_to(&mut encoded, Endianness::Little)?;
+        assert_decode_never_panics::<Block>(&encoded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_signed_round_trip() -> SerializationResult<()> {
+        use fixed_encoding::*;
+        let mut buf = [0u8; 8];
+        for value in [0i32, -1, i32::MIN, i32::MAX, 12345] {
+            assert_eq!(encode_fixed_i32(value, &mut buf, Endianness::Big)?, 4);
+            assert_eq!(decode_fixed_i32(&buf, Endianness::Big)?, (value, 4));
+        }
+        encode_fixed_i32(-2, &mut buf, Endianness::Big)?;
+        assert_eq!(&buf[..4], &[0xff, 0xff, 0xff, 0xfe]);
+        for value in [0i64, -1, i64::MIN, i64::MAX, -9_876_543_210] {
+            assert_eq!(encode_field_fixed(value, &mut buf, Endianness::Little)?, 8);
+            assert_eq!(decode_field_fixed::<i64>(&buf, Endianness::Little)?, (value, 8));
+        }
+
+        // Mixing a fixed field with varint fields in one struct layout.
+        let mut buf = [0u8; 16];
+        let written = FieldWriter::new(&mut buf, Endianness::Little)
+            .field(&Fixed(-7i64))?
+            .field(&300u64)?
+            .finish();
+        assert_eq!(written, 8 + 2);
+        let mut reader = FieldReader::new(&buf[..written], Endianness::Little);
+        assert_eq!(reader.field::<Fixed<i64>>()?, Fixed(-7));
+        assert_eq!(reader.field::<u64>()?, 300);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_builder() {
+        let tx = TransactionBuilder::new()
+            .id(9)
+            .amount(100)
+            .fee(1.5)
+            .sender("Alice")
+            .recipient("Bob")
+            .signature(vec![1, 2])
+            .fixed_mode(true)
+            .build()
+            .unwrap();
+        assert_eq!((tx.id, tx.amount, tx.version, tx.sender.as_str()), (9, 100, 1, "Alice"));
+
+        let base = TransactionBuilder::new().sender("Alice").recipient("Bob").fee(1.0);
+        assert!(base.clone().fee(f64::NAN).build().is_err());
+        assert!(base.clone().fee(-1.0).build().is_err());
+        assert!(base.clone().sender("").build().is_err());
+        let long = "x".repeat(ULTRA_ADDRESS_LEN + 1);
+        assert!(base.clone().recipient(long.clone()).build().is_ok());
+        assert!(base.recipient(long).fixed_mode(true).build().is_err());
+    }
+}